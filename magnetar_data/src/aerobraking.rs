@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use crate::celestial::Object;
+use crate::orbital::GRAVITATIONAL_CONSTANT;
+
+/// Universal gas constant in J mol⁻¹ K⁻¹
+pub(crate) const GAS_CONSTANT: f64 = 8.314462618;
+
+pub(crate) const PASCAL_PER_ATMOSPHERE: f64 = 101_325.0;
+
+/// Molar mass of dry air in kg/mol, used for gases without a known molar mass
+pub(crate) const AIR_MOLAR_MASS: f64 = 0.028_965;
+
+/// Sutton-Graves constant for the stagnation point heat flux in kg^0.5 m⁻¹, measured for air
+const SUTTON_GRAVES: f64 = 1.7415e-4;
+
+/// Temperature in K assumed when estimating the scale height of an atmosphere which does not define one
+pub const REFERENCE_TEMPERATURE: f64 = 288.0;
+
+/// Height of the top of an atmosphere in scale heights, where the pressure has dropped to a millionth
+pub const TOP_SCALE_HEIGHTS: f64 = 13.8;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Isothermal exponential model of an object's atmosphere
+pub struct Atmosphere {
+    /// Pressure at the surface in Pa
+    pub surface_pressure: f64,
+    /// Height in km over which the pressure drops by a factor of e
+    pub scale_height: f64,
+    /// Gravitational acceleration at the surface in m/s²
+    pub surface_gravity: f64,
+}
+
+impl Atmosphere {
+    /// Atmosphere model of an object, `None` if it has no atmosphere.
+    /// Uses the object's scale height or estimates it from the composition at [`REFERENCE_TEMPERATURE`].
+    pub fn of(object: &Object) -> Option<Atmosphere> {
+        let surface_pressure = object.atmosphere.values().filter(|pressure| **pressure > 0.0).sum::<f64>() * PASCAL_PER_ATMOSPHERE;
+        if surface_pressure <= 0.0 || object.radius <= 0.0 {
+            return None;
+        }
+        // km s⁻² to m s⁻²
+        let surface_gravity = GRAVITATIONAL_CONSTANT * object.mass / object.radius.powi(2) * 1000.0;
+        let scale_height = object.scale_height.unwrap_or_else(|| {
+            GAS_CONSTANT * REFERENCE_TEMPERATURE / (mean_molar_mass(&object.atmosphere) * surface_gravity) / 1000.0
+        });
+        Some(Atmosphere { surface_pressure, scale_height, surface_gravity })
+    }
+
+    /// Pressure in Pa at `altitude` km above the surface
+    pub fn pressure(&self, altitude: f64) -> f64 {
+        self.surface_pressure * (-altitude / self.scale_height).exp()
+    }
+
+    /// Density in kg/m³ at `altitude` km above the surface, from hydrostatic equilibrium
+    pub fn density(&self, altitude: f64) -> f64 {
+        self.pressure(altitude) / (self.surface_gravity * self.scale_height * 1000.0)
+    }
+
+    /// Altitude in km above which drag is negligible
+    pub fn top(&self) -> f64 {
+        TOP_SCALE_HEIGHTS * self.scale_height
+    }
+}
+
+/// Mean molar mass in kg/mol of a mix of gases weighted by their partial pressures, unknown gases count as air
+pub fn mean_molar_mass(atmosphere: &HashMap<String, f64>) -> f64 {
+    let total: f64 = atmosphere.values().filter(|pressure| **pressure > 0.0).sum();
+    if total <= 0.0 {
+        return AIR_MOLAR_MASS;
+    }
+    atmosphere.iter()
+        .filter(|(_, pressure)| **pressure > 0.0)
+        .map(|(gas, pressure)| molar_mass(gas).unwrap_or(AIR_MOLAR_MASS) * pressure / total)
+        .sum()
+}
+
+/// Molar mass in kg/mol of common atmospheric gases by formula
+pub(crate) fn molar_mass(gas: &str) -> Option<f64> {
+    Some(match gas {
+        "H2" => 0.002_016,
+        "He" => 0.004_003,
+        "CH4" => 0.016_04,
+        "NH3" => 0.017_03,
+        "H2O" => 0.018_02,
+        "Ne" => 0.020_18,
+        "N2" => 0.028_01,
+        "CO" => 0.028_01,
+        "O2" => 0.032_00,
+        "Ar" => 0.039_95,
+        "CO2" => 0.044_01,
+        "SO2" => 0.064_07,
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Properties of a vehicle relevant for flying through an atmosphere
+pub struct Vehicle {
+    /// Mass divided by drag coefficient and reference area in kg/m²
+    pub ballistic_coefficient: f64,
+    /// Radius of the leading surface in m
+    pub nose_radius: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Estimated effect of a single pass through an atmosphere around the periapsis
+pub struct AtmosphericPass {
+    /// Speed lost to drag in km/s
+    pub delta_v: f64,
+    /// Density at the periapsis in kg/m³
+    pub peak_density: f64,
+    /// Stagnation point heat flux at the periapsis in W/m²
+    pub peak_heat_flux: f64,
+}
+
+/// Estimates drag and heating of a pass through an atmosphere, e.g. to plan aerobraking. Drag is integrated
+/// along a grazing path through an exponential atmosphere, which holds while the speed loss is small
+/// compared to the speed.
+///
+/// * `body_radius` - Radius of the object the atmosphere belongs to in km
+/// * `periapsis_altitude` - Lowest altitude of the pass above the surface in km
+/// * `speed` - Speed at the periapsis in km/s
+pub fn atmospheric_pass(atmosphere: &Atmosphere, body_radius: f64, periapsis_altitude: f64, speed: f64,
+                        vehicle: &Vehicle) -> AtmosphericPass {
+    let peak_density = atmosphere.density(periapsis_altitude);
+    // Mass of the air column along the path in kg/m², the Chapman grazing integral
+    let path_length = (2.0 * PI * (body_radius + periapsis_altitude) * atmosphere.scale_height).sqrt() * 1000.0;
+    let column_mass = peak_density * path_length;
+    let delta_v = (speed * column_mass / (2.0 * vehicle.ballistic_coefficient)).min(speed);
+    let peak_heat_flux = SUTTON_GRAVES * (peak_density / vehicle.nose_radius).sqrt() * (speed * 1000.0).powi(3);
+    AtmosphericPass { delta_v, peak_density, peak_heat_flux }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_earth_atmosphere() {
+        let mut earth = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap().children.remove(0);
+        let atmosphere = Atmosphere::of(&earth).unwrap();
+        assert!((atmosphere.density(0.0) - 1.2).abs() < 0.1, "{}", atmosphere.density(0.0));
+        earth.scale_height = None;
+        let estimated = Atmosphere::of(&earth).unwrap().scale_height;
+        assert!((estimated - 8.4).abs() < 0.2, "{}", estimated);
+
+        let vehicle = Vehicle { ballistic_coefficient: 100.0, nose_radius: 1.0 };
+        let low = atmospheric_pass(&atmosphere, earth.radius, 90.0, 8.0, &vehicle);
+        let high = atmospheric_pass(&atmosphere, earth.radius, 130.0, 8.0, &vehicle);
+        assert!(low.delta_v > high.delta_v && low.peak_heat_flux > high.peak_heat_flux);
+        assert!(low.delta_v < 8.0);
+        assert_eq!(atmospheric_pass(&atmosphere, earth.radius, 0.0, 8.0, &vehicle).delta_v, 8.0);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::aerobraking::{Atmosphere, PASCAL_PER_ATMOSPHERE};
+use crate::celestial::{Object, ObjectType};
+use crate::stellar::{main_sequence_lifetime, stellar_state, SOLAR_LUMINOSITY};
+
+/// Surface pressure in atm below which an atmosphere is too thin to show a haze, about that of Mars
+const VISIBLE_PRESSURE: f64 = 0.005;
+/// Haze reaches up this many scale heights above the surface
+const HAZE_SCALE_HEIGHTS: f64 = 6.0;
+/// Surface pressure in atm at which rocky bodies are about 40 % covered in clouds, like Earth
+const CLOUD_PRESSURE: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Glow around the limb of a body with an atmosphere
+pub struct Haze {
+    /// Linear RGB colour of the scattered light
+    pub color: [f32; 3],
+    /// Height of the visible haze relative to the radius of the body
+    pub thickness: f32,
+    /// Opacity of the haze at the limb, between 0 and 1
+    pub opacity: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Rendering parameters derived from the physical data of a body, so renderers only have to map them onto
+/// materials and shaders instead of interpreting atmospheres and stars themselves
+pub struct Appearance {
+    /// `None` for bodies without an atmosphere or one too thin to see
+    pub haze: Option<Haze>,
+    /// Fraction of the surface covered by clouds, between 0 and 1, e.g. for the opacity of a cloud layer
+    pub cloud_cover: f32,
+    /// Linear RGB colour of the light a star emits, `None` for bodies that do not shine themselves
+    pub emission: Option<[f32; 3]>,
+    /// Strength of the bloom around a star, 1 for a star as luminous as Sol and 0 for other bodies
+    pub bloom: f32,
+}
+
+impl Appearance {
+    /// Appearance of an object, stars are assumed to be halfway through their main sequence
+    pub fn of(object: &Object) -> Appearance {
+        if object.object_type == ObjectType::Star {
+            let state = stellar_state(object.mass, main_sequence_lifetime(object.mass) / 2.0);
+            // Each tenfold luminosity adds a quarter of Sol's bloom
+            let bloom = (1.0 + 0.25 * (state.luminosity / SOLAR_LUMINOSITY).log10()).clamp(0.1, 3.0);
+            return Appearance { haze: None, cloud_cover: 0.0, emission: Some(blackbody_color(state.temperature)), bloom: bloom as f32 };
+        }
+
+        let pressure = Atmosphere::of(object).map_or(0.0, |atmosphere| atmosphere.surface_pressure / PASCAL_PER_ATMOSPHERE);
+        let haze = Atmosphere::of(object).filter(|_| pressure >= VISIBLE_PRESSURE).map(|atmosphere| Haze {
+            color: haze_color(object),
+            thickness: (HAZE_SCALE_HEIGHTS * atmosphere.scale_height / object.radius).min(0.5) as f32,
+            opacity: (1.0 - (-pressure).exp()) as f32,
+        });
+        let cloud_cover = match object.object_type {
+            // Only the cloud decks of giants are ever seen
+            ObjectType::Jovian | ObjectType::IceGiant => 1.0,
+            _ => 1.0 - (-pressure / CLOUD_PRESSURE).exp(),
+        };
+        Appearance { haze, cloud_cover: cloud_cover as f32, emission: None, bloom: 0.0 }
+    }
+}
+
+/// Colour of the haze as the mix of the colours of its gases weighted by their partial pressures
+fn haze_color(object: &Object) -> [f32; 3] {
+    let total: f64 = object.atmosphere.values().filter(|pressure| **pressure > 0.0).sum();
+    let mut color = [0.0; 3];
+    for (gas, pressure) in object.atmosphere.iter().filter(|(_, pressure)| **pressure > 0.0) {
+        let gas_color = match gas.as_str() {
+            // Rayleigh scattering turns thin, clear atmospheres blue
+            "N2" | "O2" | "Ar" => [0.35, 0.55, 1.0],
+            // Sulphuric clouds and dust tint carbon dioxide atmospheres yellow to orange
+            "CO2" | "SO2" => [1.0, 0.85, 0.55],
+            // Photochemical smog, like on Titan
+            "CH4" => [1.0, 0.6, 0.25],
+            "H2" | "He" => [0.95, 0.9, 0.8],
+            "NH3" => [0.95, 0.85, 0.7],
+            _ => [0.85, 0.85, 0.85],
+        };
+        for (channel, gas_channel) in color.iter_mut().zip(gas_color) {
+            *channel += (gas_channel * pressure / total) as f32;
+        }
+    }
+    color
+}
+
+/// Approximate linear RGB colour of a black body at `temperature` K, normalised so the brightest channel is 1
+fn blackbody_color(temperature: f64) -> [f32; 3] {
+    // Fit of the Planck spectrum by Tanner Helland, in units of 100 K
+    let t = (temperature / 100.0).clamp(10.0, 400.0);
+    let red = if t <= 66.0 { 255.0 } else { 329.698_727_446 * (t - 60.0).powf(-0.133_204_759_2) };
+    let green = if t <= 66.0 { 99.470_802_586_1 * t.ln() - 161.119_568_166_1 } else { 288.122_169_528_3 * (t - 60.0).powf(-0.075_514_849_2) };
+    let blue = if t >= 66.0 { 255.0 } else if t <= 19.0 { 0.0 } else { 138.517_731_223_1 * (t - 10.0).ln() - 305.044_792_730_7 };
+    let [red, green, blue] = [red, green, blue].map(|channel| (channel / 255.0).clamp(0.0, 1.0));
+    let brightest = red.max(green).max(blue);
+    [red / brightest, green / brightest, blue / brightest].map(|channel| channel as f32)
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_appearance_follows_data() {
+        let system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let sun = Appearance::of(system.find("Sol").unwrap());
+        let [red, green, blue] = sun.emission.unwrap();
+        assert!((sun.bloom - 1.0).abs() < 0.1 && red >= green && green > blue && blue > 0.7, "{:?}", sun);
+
+        let earth = Appearance::of(system.find("Earth").unwrap());
+        let haze = earth.haze.unwrap();
+        assert!(haze.color[2] > haze.color[0] && haze.thickness < 0.01 && earth.cloud_cover > 0.3 && earth.cloud_cover < 0.5, "{:?}", earth);
+        assert_eq!(Appearance::of(system.find("Luna").unwrap()), Appearance { haze: None, cloud_cover: 0.0, emission: None, bloom: 0.0 });
+    }
+}
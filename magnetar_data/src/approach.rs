@@ -0,0 +1,84 @@
+use crate::orbital::OrbitalParameters;
+use crate::transfer::phase_angle_crossings;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A moment at which two objects around the same parent are close to each other
+pub struct Approach {
+    /// Time of the approach in seconds from now
+    pub time: f64,
+    /// Distance between both objects in km
+    pub distance: f64,
+}
+
+/// Distance in km between two objects around the same parent `seconds` from now
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+pub fn separation(a: &OrbitalParameters, b: &OrbitalParameters, parent_mass: f64, seconds: f64) -> f64 {
+    a.state_at(seconds, parent_mass).position().distance(b.state_at(seconds, parent_mass).position())
+}
+
+/// Finds all conjunctions between `start` and `end`, the moments at which both objects are at the same
+/// longitude as seen from their parent
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+/// * `resolution` - Sampling interval in seconds, has to be shorter than half of the synodic period
+pub fn conjunctions(a: &OrbitalParameters, b: &OrbitalParameters, parent_mass: f64,
+                    start: f64, end: f64, resolution: f64) -> Vec<Approach> {
+    phase_angle_crossings(a, b, parent_mass, 0.0, start, end, resolution).into_iter()
+        .map(|time| Approach { time, distance: separation(a, b, parent_mass, time) })
+        .collect()
+}
+
+/// Finds every local minimum of the distance between both objects between `start` and `end`
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+/// * `resolution` - Sampling interval in seconds
+pub fn closest_approaches(a: &OrbitalParameters, b: &OrbitalParameters, parent_mass: f64,
+                          start: f64, end: f64, resolution: f64) -> Vec<Approach> {
+    let distance = |seconds: f64| separation(a, b, parent_mass, seconds);
+    let samples: Vec<(f64, f64)> = std::iter::successors(Some(start), |time| Some(time + resolution))
+        .take_while(|time| *time <= end)
+        .map(|time| (time, distance(time)))
+        .collect();
+
+    samples.windows(3)
+        .filter(|window| window[1].1 <= window[0].1 && window[1].1 < window[2].1)
+        .map(|window| {
+            // Golden section search between the neighbouring samples
+            let ratio = (5f64.sqrt() - 1.0) / 2.0;
+            let (mut low, mut high) = (window[0].0, window[2].0);
+            for _ in 0..60 {
+                let left = high - ratio * (high - low);
+                let right = low + ratio * (high - low);
+                if distance(left) < distance(right) {
+                    high = right;
+                } else {
+                    low = left;
+                }
+            }
+            let time = (low + high) / 2.0;
+            Approach { time, distance: distance(time) }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer::synodic_period;
+
+    const SOLAR_MASS: f64 = 1.988416e30;
+
+    #[test]
+    fn test_circular_conjunction_is_closest_approach() {
+        let earth = OrbitalParameters { semi_major_axis: 149597870.0, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 0.0 };
+        let mars = OrbitalParameters { semi_major_axis: 227939200.0, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 30.0 };
+        let end = synodic_period(&earth, &mars, SOLAR_MASS);
+        let conjunctions = conjunctions(&earth, &mars, SOLAR_MASS, 0.0, end, 86400.0);
+        let approaches = closest_approaches(&earth, &mars, SOLAR_MASS, 0.0, end, 86400.0);
+        assert_eq!(conjunctions.len(), 1);
+        assert_eq!(approaches.len(), 1);
+        assert!((conjunctions[0].time - approaches[0].time).abs() < 60.0);
+        assert!((approaches[0].distance - (227939200.0 - 149597870.0)).abs() < 1.0);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "lowercase"))]
+/// Groups of sounds with their own volume setting
+pub enum Channel {
+    Ambient,
+    Interface,
+    Notification,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Something that makes a sound, reported by the frontend
+pub enum Sound<'a> {
+    /// The soundtrack of a view, e.g. `system` or the name of a body the camera is close to
+    Ambient(&'a str),
+    /// A button or key was pressed
+    Click,
+    /// A body was selected
+    Select,
+    /// Something happened in the simulation, e.g. `collision` or `approach`
+    Notification(&'a str),
+}
+
+impl Sound<'_> {
+    /// Name of the cue in a [`SoundManifest`], ambient views fall back to the `ambient` cue if they have none
+    pub fn cue(&self) -> String {
+        match self {
+            Sound::Ambient(view) => format!("ambient.{}", view),
+            Sound::Click => "click".to_string(),
+            Sound::Select => "select".to_string(),
+            Sound::Notification(name) => name.to_string(),
+        }
+    }
+
+    pub fn channel(&self) -> Channel {
+        match self {
+            Sound::Ambient(_) => Channel::Ambient,
+            Sound::Click | Sound::Select => Channel::Interface,
+            Sound::Notification(_) => Channel::Notification,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// An asset to play for a [`Sound`]
+pub struct SoundCue {
+    /// Path of the sound file, up to the frontend to load
+    pub asset: String,
+    /// Volume of the asset relative to the others on its channel, 1 to play it as is
+    #[cfg_attr(feature = "serde", serde(default = "full_volume"))]
+    pub volume: f32,
+}
+
+#[cfg(feature = "serde")]
+fn full_volume() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+/// Sound cues by name, see [`Sound::cue`] for the names to use. Shipped as a YAML file next to the sounds so
+/// mod packs can replace them.
+pub struct SoundManifest {
+    cues: HashMap<String, SoundCue>,
+}
+
+impl SoundManifest {
+    pub fn new() -> SoundManifest {
+        SoundManifest::default()
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_str(content: &str) -> Result<SoundManifest, String> {
+        serde_yaml::from_str(content).map_err(|e| format!("Invalid sound manifest: {}", e))
+    }
+
+    pub fn insert(&mut self, name: &str, cue: SoundCue) {
+        self.cues.insert(name.to_string(), cue);
+    }
+
+    /// Adds the cues of `other`, replacing those of the same name so mod packs can replace the base game's sounds
+    pub fn extend(&mut self, other: SoundManifest) {
+        self.cues.extend(other.cues);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SoundCue> {
+        self.cues.get(name)
+    }
+
+    /// What to play for `sound` at the volumes of `mix`, `None` if there is no cue for it
+    pub fn play(&self, sound: Sound, mix: &Mix) -> Option<Playback<'_>> {
+        let cue = self.get(&sound.cue()).or_else(|| match sound {
+            Sound::Ambient(_) => self.get("ambient"),
+            _ => None,
+        })?;
+        let channel = sound.channel();
+        Some(Playback { asset: &cue.asset, channel, volume: cue.volume * mix.volume(channel) })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// A sound to play, made by [`SoundManifest::play`]
+pub struct Playback<'a> {
+    pub asset: &'a str,
+    pub channel: Channel,
+    /// Final volume with the mix applied, 0 for silence
+    pub volume: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+/// Volume settings between 0 and 1, e.g. from the settings menu
+pub struct Mix {
+    pub master: f32,
+    pub ambient: f32,
+    pub interface: f32,
+    pub notification: f32,
+    pub muted: bool,
+}
+
+impl Default for Mix {
+    /// Everything at full volume
+    fn default() -> Mix {
+        Mix { master: 1.0, ambient: 1.0, interface: 1.0, notification: 1.0, muted: false }
+    }
+}
+
+impl Mix {
+    /// Volume of a channel including the master volume, values outside of 0 to 1 are clamped
+    pub fn volume(&self, channel: Channel) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+        let volume = match channel {
+            Channel::Ambient => self.ambient,
+            Channel::Interface => self.interface,
+            Channel::Notification => self.notification,
+        };
+        self.master.clamp(0.0, 1.0) * volume.clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sounds_map_to_cues() {
+        let mut manifest = SoundManifest::from_yaml_str("
+ambient: {asset: space.ogg}
+ambient.Earth: {asset: earth.ogg}
+select: {asset: select.ogg, volume: 0.5}
+").unwrap();
+        let mod_pack = SoundManifest::from_yaml_str("
+approach: {asset: approach.ogg}
+ambient.Earth: {asset: earth_remix.ogg}
+").unwrap();
+        manifest.extend(mod_pack);
+
+        let mix = Mix { master: 0.5, interface: 0.5, ..Mix::default() };
+        assert_eq!(manifest.play(Sound::Ambient("Earth"), &mix).unwrap().asset, "earth_remix.ogg");
+        assert_eq!(manifest.play(Sound::Ambient("Mars"), &mix).unwrap().asset, "space.ogg");
+        assert_eq!(manifest.play(Sound::Select, &mix), Some(Playback { asset: "select.ogg", channel: Channel::Interface, volume: 0.125 }));
+        assert_eq!(manifest.play(Sound::Click, &mix), None);
+        assert_eq!(manifest.play(Sound::Notification("approach"), &Mix { muted: true, ..mix }).unwrap().volume, 0.0);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::store::{ObjectId, SystemStore};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Response of an analog axis like a stick or trigger
+pub struct AxisCurve {
+    /// Deflection below which the axis reads as 0, so worn sticks do not drift. 1 or more disables the axis.
+    pub deadzone: f64,
+    /// Output at full deflection
+    pub sensitivity: f64,
+    /// 1 for a linear response, higher values give finer control around the center
+    pub exponent: f64,
+}
+
+impl Default for AxisCurve {
+    fn default() -> AxisCurve {
+        AxisCurve { deadzone: 0.15, sensitivity: 1.0, exponent: 2.0 }
+    }
+}
+
+impl AxisCurve {
+    /// Output for a raw deflection between -1 and 1, rescaled so it starts at 0 at the edge of the deadzone
+    pub fn apply(&self, raw: f64) -> f64 {
+        let magnitude = raw.abs().min(1.0);
+        if self.deadzone >= 1.0 || magnitude <= self.deadzone {
+            return 0.0;
+        }
+        let scaled = (magnitude - self.deadzone) / (1.0 - self.deadzone);
+        raw.signum() * scaled.powf(self.exponent) * self.sensitivity
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Raw input from any device, translated to [`CameraAction`]s by [`CameraControls`]
+pub enum CameraInput {
+    /// Stick deflection between -1 and 1, held every frame
+    PanStick { x: f64, y: f64 },
+    OrbitStick { x: f64, y: f64 },
+    /// Trigger pressure between -1 and 1, positive to zoom in, e.g. right minus left trigger
+    ZoomTrigger(f64),
+    /// Drag of a finger or mouse as a fraction of the view width
+    Drag { x: f64, y: f64 },
+    /// Ratio of the distance between two fingers to the distance in the previous frame
+    Pinch(f64),
+    /// D-pad or key press, positive for the next body
+    Cycle(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// What the camera should do this frame
+pub enum CameraAction {
+    /// Move the point looked at, in view widths
+    Pan { x: f64, y: f64 },
+    /// Factor to multiply the width of the view with, below 1 zooms in
+    Zoom(f64),
+    /// Rotate around the point looked at, in radians
+    Orbit { yaw: f64, pitch: f64 },
+    /// Select a body this many siblings further, see [`cycle_body`]
+    Cycle(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Device independent camera settings, e.g. from the settings menu
+pub struct CameraControls {
+    pub stick: AxisCurve,
+    pub trigger: AxisCurve,
+    /// View widths per second at full stick deflection
+    pub pan_speed: f64,
+    /// Radians per second at full stick deflection
+    pub orbit_speed: f64,
+    /// Doublings of the zoom per second at full trigger pressure
+    pub zoom_speed: f64,
+}
+
+impl Default for CameraControls {
+    fn default() -> CameraControls {
+        CameraControls {
+            stick: AxisCurve::default(),
+            trigger: AxisCurve { deadzone: 0.05, sensitivity: 1.0, exponent: 1.0 },
+            pan_speed: 1.0,
+            orbit_speed: std::f64::consts::PI,
+            zoom_speed: 2.0,
+        }
+    }
+}
+
+impl CameraControls {
+    /// The action for `input` held for `seconds` of real time, `None` if it does nothing, e.g. inside the deadzone.
+    /// Drags and pinches already are distances, so they do not depend on the time.
+    pub fn action(&self, input: CameraInput, seconds: f64) -> Option<CameraAction> {
+        let action = match input {
+            CameraInput::PanStick { x, y } => {
+                let speed = self.pan_speed * seconds;
+                CameraAction::Pan { x: self.stick.apply(x) * speed, y: self.stick.apply(y) * speed }
+            }
+            CameraInput::OrbitStick { x, y } => {
+                let speed = self.orbit_speed * seconds;
+                CameraAction::Orbit { yaw: self.stick.apply(x) * speed, pitch: self.stick.apply(y) * speed }
+            }
+            CameraInput::ZoomTrigger(pressure) => CameraAction::Zoom(0.5f64.powf(self.trigger.apply(pressure) * self.zoom_speed * seconds)),
+            // The content follows the finger, so the view moves the other way
+            CameraInput::Drag { x, y } => CameraAction::Pan { x: -x, y: -y },
+            CameraInput::Pinch(ratio) if ratio > 0.0 => CameraAction::Zoom(1.0 / ratio),
+            CameraInput::Pinch(_) => return None,
+            CameraInput::Cycle(step) => CameraAction::Cycle(step),
+        };
+        match action {
+            CameraAction::Pan { x: 0.0, y: 0.0 } | CameraAction::Orbit { yaw: 0.0, pitch: 0.0 } | CameraAction::Zoom(1.0) | CameraAction::Cycle(0) => None,
+            action => Some(action),
+        }
+    }
+}
+
+/// The sibling `step` places after `id` in the order of the system file, wrapping around. The root has no siblings
+/// and steps to its first child instead.
+pub fn cycle_body(store: &SystemStore, id: ObjectId, step: i32) -> Option<ObjectId> {
+    let siblings = match store.parent(id) {
+        Some(parent) => store.children(parent),
+        None => return store.children(id).first().copied(),
+    };
+    let index = siblings.iter().position(|sibling| *sibling == id)? as i64;
+    siblings.get((index + step as i64).rem_euclid(siblings.len() as i64) as usize).copied()
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_inputs_become_actions() {
+        let controls = CameraControls::default();
+        assert_eq!(controls.action(CameraInput::PanStick { x: 0.1, y: -0.1 }, 1.0), None);
+        assert_eq!(controls.action(CameraInput::PanStick { x: 1.0, y: 0.0 }, 0.5), Some(CameraAction::Pan { x: 0.5, y: 0.0 }));
+        let Some(CameraAction::Pan { x, .. }) = controls.action(CameraInput::PanStick { x: -0.575, y: 0.0 }, 1.0) else { panic!() };
+        assert!((x + 0.25).abs() < 1e-12);
+        assert_eq!(AxisCurve { deadzone: 1.0, ..AxisCurve::default() }.apply(1.0), 0.0);
+        assert_eq!(controls.action(CameraInput::ZoomTrigger(1.0), 0.5), Some(CameraAction::Zoom(0.5)));
+        assert_eq!(controls.action(CameraInput::Pinch(2.0), 0.0), Some(CameraAction::Zoom(0.5)));
+        assert_eq!(controls.action(CameraInput::Drag { x: 0.1, y: 0.0 }, 0.0), Some(CameraAction::Pan { x: -0.1, y: -0.0 }));
+
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let (sol, earth, luna) = (store.root_id(), store.find("Earth").unwrap(), store.find("Luna").unwrap());
+        assert_eq!(cycle_body(&store, sol, 1), store.children(sol).first().copied());
+        assert_eq!(cycle_body(&store, luna, -1), Some(luna));
+        let planets = store.children(sol);
+        assert_eq!(cycle_body(&store, earth, planets.len() as i32), Some(earth));
+    }
+}
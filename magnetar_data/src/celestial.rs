@@ -1,9 +1,18 @@
 use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
+use std::fmt;
 use std::time::Duration;
-use serde::Deserialize;
-pub(crate) use crate::orbital::OrbitalParameters;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, Serializer};
+use crate::orbital::OrbitalParameters;
+use crate::surface::Rotation;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 /// Enum for all Object types
 pub enum ObjectType {
     /// Denotes a given object is a star
@@ -16,7 +25,19 @@ pub enum ObjectType {
     IceGiant
 }
 
-#[derive(Debug)]
+impl fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ObjectType::Star => "Star",
+            ObjectType::Rocky => "Rocky",
+            ObjectType::Jovian => "Jovian",
+            ObjectType::IceGiant => "Ice Giant",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 /// Represents a given Celestial Object such as a Star, Planet or Asteroid
 /// All of these are basically handled the same way
 pub struct Object {
@@ -26,6 +47,14 @@ pub struct Object {
     pub radius: f64,
     pub orbital_params: OrbitalParameters,
     pub atmosphere: HashMap<String, f64>,
+    /// Height in km over which the atmospheric pressure drops by a factor of e, estimated from the
+    /// composition if not given
+    pub scale_height: Option<f64>,
+    /// Rotation of the object, objects without one keep their prime meridian on the x axis
+    pub rotation: Option<Rotation>,
+    /// Icon drawn instead of the one for the object's type when it is too small to draw as a disc, see
+    /// [`IconSet`](crate::icon::IconSet)
+    pub icon: Option<String>,
 
     pub children: Vec<Object>,
 }
@@ -35,9 +64,236 @@ impl Object {
     /// 
     /// * `time_step` - How much time to step forward 
     pub fn step_forward(&mut self, time_step: Duration) {
-        self.orbital_params.step_forward(time_step);
+        self.step_by(time_step.as_secs_f64());
+    }
+
+    /// Compares two object trees, allowing floating point values to differ by a relative `epsilon`,
+    /// angles are compared like in [`OrbitalParameters::approx_eq`]
+    ///
+    /// * `other` - The object to compare to
+    /// * `epsilon` - Allowed relative difference, e.g. `1e-9`
+    pub fn approx_eq(&self, other: &Object, epsilon: f64) -> bool {
+        self.name == other.name
+            && self.object_type == other.object_type
+            && approx_eq(self.mass, other.mass, epsilon)
+            && approx_eq(self.radius, other.radius, epsilon)
+            && self.orbital_params.approx_eq(&other.orbital_params, epsilon)
+            && self.atmosphere.len() == other.atmosphere.len()
+            && self.atmosphere.iter().all(|(gas, pressure)| {
+                other.atmosphere.get(gas).is_some_and(|other_pressure| approx_eq(*pressure, *other_pressure, epsilon))
+            })
+            && match (self.scale_height, other.scale_height) {
+                (Some(a), Some(b)) => approx_eq(a, b, epsilon),
+                (a, b) => a == b,
+            }
+            && match (self.rotation, other.rotation) {
+                (Some(a), Some(b)) => {
+                    let angle_difference = (a.prime_meridian - b.prime_meridian).rem_euclid(360.0);
+                    approx_eq(a.period, b.period, epsilon) && angle_difference.min(360.0 - angle_difference) <= epsilon * 360.0
+                }
+                (a, b) => a == b,
+            }
+            && self.icon == other.icon
+            && self.children.len() == other.children.len()
+            && self.children.iter().zip(&other.children).all(|(child, other_child)| child.approx_eq(other_child, epsilon))
+    }
+
+    /// Iterates depth first over this object and all of its descendants, parents before their children
+    pub fn iter(&self) -> TreeIter<'_> {
+        TreeIter { stack: vec![TreeItem { object: self, parent: None, depth: 0 }] }
+    }
+
+    /// Finds an object by name in this object's subtree, including the object itself
+    ///
+    /// * `name` - Name of the object to look for
+    pub fn find(&self, name: &str) -> Option<&Object> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(name))
+    }
+
+    /// Finds an object by name in this object's subtree for editing, see [`Object::find`]
+    ///
+    /// * `name` - Name of the object to look for
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Object> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|child| child.find_mut(name))
+    }
+
+    /// Finds the parent of the object with the given name in this object's subtree
+    ///
+    /// * `name` - Name of the child to look for
+    pub fn find_parent(&self, name: &str) -> Option<&Object> {
+        if self.children.iter().any(|child| child.name == name) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find_parent(name))
+    }
+
+    /// Step by a signed amount of seconds for a given object and propagates to any children,
+    /// negative values step backwards in time. The object is treated as the root of the system,
+    /// so only its children move.
+    ///
+    /// * `seconds` - How many seconds to step
+    pub fn step_by(&mut self, seconds: f64) {
+        self.step_around(seconds, 0.0);
+    }
+
+    fn step_around(&mut self, seconds: f64, parent_mass: f64) {
+        self.orbital_params.step_by(seconds, parent_mass);
+        if let Some(rotation) = &mut self.rotation {
+            rotation.step_by(seconds);
+        }
         for child in self.children.iter_mut() {
-            child.step_forward(time_step);
+            child.step_around(seconds, self.mass);
         }
     }
+
+    /// Analytically calculates the state of the object and all of its children `seconds` from now,
+    /// leaving `self` untouched. The object is treated as the root of the system.
+    ///
+    /// * `seconds` - Signed offset in seconds from the current state
+    pub fn state_at(&self, seconds: f64) -> Object {
+        self.state_around(seconds, 0.0)
+    }
+
+    fn state_around(&self, seconds: f64, parent_mass: f64) -> Object {
+        Object {
+            name: self.name.clone(),
+            object_type: self.object_type.clone(),
+            mass: self.mass,
+            radius: self.radius,
+            orbital_params: self.orbital_params.state_at(seconds, parent_mass),
+            atmosphere: self.atmosphere.clone(),
+            scale_height: self.scale_height,
+            rotation: self.rotation.map(|rotation| Rotation { prime_meridian: rotation.prime_meridian_at(seconds), ..rotation }),
+            icon: self.icon.clone(),
+            children: self.children.iter().map(|child| child.state_around(seconds, self.mass)).collect(),
+        }
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}, {:e} kg, {} km radius)", self.name, self.object_type, self.mass, self.radius)
+    }
+}
+
+/// Definition of an object in the same layout the YAML loader reads, the name is the key it is stored under
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct ObjectDefinition<'a> {
+    #[serde(rename = "type")]
+    object_type: &'a ObjectType,
+    mass: f64,
+    radius: f64,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    orbital_params: Option<&'a OrbitalParameters>,
+    // Sorted for a stable output
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    atmosphere: BTreeMap<&'a str, f64>,
+    #[serde(rename = "scale-height", skip_serializing_if = "Option::is_none")]
+    scale_height: Option<f64>,
+    #[serde(rename = "rotation-period", skip_serializing_if = "Option::is_none")]
+    rotation_period: Option<f64>,
+    #[serde(rename = "prime-meridian", skip_serializing_if = "Option::is_none")]
+    prime_meridian: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<&'a str>,
+    #[serde(rename = "parentTo", skip_serializing_if = "<[_]>::is_empty")]
+    children: &'a [Object],
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Object {
+    /// Serializes as a single entry map of name to definition, matching the format of the system files
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let definition = ObjectDefinition {
+            object_type: &self.object_type,
+            mass: self.mass,
+            radius: self.radius,
+            // Objects without an orbit have no orbital keys in the system files
+            orbital_params: Some(&self.orbital_params).filter(|orbit| orbit.semi_major_axis != 0.0),
+            atmosphere: self.atmosphere.iter().map(|(gas, pressure)| (gas.as_str(), *pressure)).collect(),
+            scale_height: self.scale_height,
+            rotation_period: self.rotation.map(|rotation| rotation.period),
+            prime_meridian: self.rotation.map(|rotation| rotation.prime_meridian).filter(|angle| *angle != 0.0),
+            icon: self.icon.as_deref(),
+            children: &self.children,
+        };
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(&self.name, &definition)?;
+        map.end()
+    }
+}
+
+/// Whether two values differ by at most `epsilon` relative to the larger one
+pub(crate) fn approx_eq(a: f64, b: f64, epsilon: f64) -> bool {
+    (a - b).abs() <= epsilon * a.abs().max(b.abs()).max(1.0)
+}
+
+#[derive(Debug, Clone, Copy)]
+/// An object visited while iterating over a tree, together with its position in the tree
+pub struct TreeItem<'a> {
+    pub object: &'a Object,
+    /// The object this one orbits, `None` for the root of the iteration
+    pub parent: Option<&'a Object>,
+    /// Distance from the root of the iteration, 0 for the root itself
+    pub depth: usize,
+}
+
+/// Depth first iterator over an object tree, created by [`Object::iter`]
+pub struct TreeIter<'a> {
+    stack: Vec<TreeItem<'a>>,
+}
+
+impl<'a> Iterator for TreeIter<'a> {
+    type Item = TreeItem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.stack.pop()?;
+        // Push in reverse so children are visited in their original order
+        self.stack.extend(item.object.children.iter().rev().map(|child| TreeItem {
+            object: child,
+            parent: Some(item.object),
+            depth: item.depth + 1,
+        }));
+        Some(item)
+    }
+}
+
+impl<'a> IntoIterator for &'a Object {
+    type Item = TreeItem<'a>;
+    type IntoIter = TreeIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_iter_order() {
+        let system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let visited: Vec<(&str, Option<&str>, usize)> = system.iter()
+            .map(|item| (item.object.name.as_str(), item.parent.map(|parent| parent.name.as_str()), item.depth))
+            .collect();
+        assert_eq!(visited, vec![("Sol", None, 0), ("Earth", Some("Sol"), 1), ("Luna", Some("Earth"), 2)]);
+    }
+
+    #[cfg(feature = "reflect")]
+    #[test]
+    fn test_reflect_paths() {
+        use bevy_reflect::GetPath;
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        *system.path_mut::<f64>("children[0].orbital_params.eccentricity").unwrap() = 0.2;
+        assert_eq!(system.find("Earth").unwrap().orbital_params.eccentricity, 0.2);
+        assert_eq!(*system.path::<f64>("children[0].children[0].mass").unwrap(), system.find("Luna").unwrap().mass);
+    }
 }
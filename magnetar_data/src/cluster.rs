@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use crate::store::{ObjectId, SystemStore};
+use crate::vector::DVec3;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Bodies drawn so close together that they are shown as a single badge
+pub struct Cluster {
+    /// Members in the order they were given, the first one seeded the cluster
+    pub members: Vec<ObjectId>,
+    /// Mean position of all members
+    pub center: DVec3,
+    /// Largest distance of a member from the center, e.g. to zoom in until the members separate
+    pub extent: f64,
+}
+
+impl Cluster {
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Whether the cluster stands for more than one body and should be drawn as a badge with a count
+    pub fn is_group(&self) -> bool {
+        self.members.len() > 1
+    }
+}
+
+/// Groups points which lie within `radius` of the first point of a group, in whatever space they are given in.
+/// Meant to run every frame on projected positions with the radius of a badge, points are bucketed into a grid
+/// of the radius so this stays linear in the number of points. Earlier points seed clusters first, so pass
+/// important bodies first to keep them as the seeds.
+pub fn cluster(points: &[(ObjectId, DVec3)], radius: f64) -> Vec<Cluster> {
+    let cell = |position: DVec3| ((position.x / radius).floor() as i64, (position.y / radius).floor() as i64);
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (index, (_, position)) in points.iter().enumerate() {
+        grid.entry(cell(*position)).or_default().push(index);
+    }
+
+    let mut assigned = vec![false; points.len()];
+    let mut clusters = Vec::new();
+    for (seed, &(_, seed_position)) in points.iter().enumerate() {
+        if assigned[seed] {
+            continue;
+        }
+        let (x, y) = cell(seed_position);
+        let mut members: Vec<usize> = (x - 1..=x + 1)
+            .flat_map(|x| (y - 1..=y + 1).map(move |y| (x, y)))
+            .filter_map(|key| grid.get(&key))
+            .flatten()
+            .copied()
+            .filter(|&index| !assigned[index] && points[index].1.distance(seed_position) <= radius)
+            .collect();
+        members.sort_unstable();
+        for &index in &members {
+            assigned[index] = true;
+        }
+        let center = members.iter().fold(DVec3::default(), |sum, &index| sum + points[index].1) / members.len() as f64;
+        let extent = members.iter().map(|&index| points[index].1.distance(center)).fold(0.0, f64::max);
+        clusters.push(Cluster { members: members.into_iter().map(|index| points[index].0).collect(), center, extent });
+    }
+    clusters
+}
+
+/// Clusters all bodies of a system by their positions relative to the root, seeded by the heaviest bodies,
+/// so a planet stays the face of the cluster formed with its moons
+///
+/// * `radius` - Distance in km below which bodies are merged, e.g. the size of a badge in km at the current zoom
+pub fn cluster_bodies(store: &SystemStore, radius: f64) -> Vec<Cluster> {
+    let mut points: Vec<(ObjectId, DVec3)> = store.ids()
+        .filter_map(|id| Some((id, store.absolute_position(id)?)))
+        .collect();
+    let mass = |id: ObjectId| store.get(id).map_or(0.0, |object| object.mass);
+    points.sort_by(|a, b| mass(b.0).total_cmp(&mass(a.0)));
+    cluster(&points, radius)
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_cluster_moons_with_planet() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let (sol, earth, luna) = (store.root_id(), store.find("Earth").unwrap(), store.find("Luna").unwrap());
+        // Zoomed out to the whole orbit of Earth, Luna merges into Earth's badge
+        let clusters = cluster_bodies(&store, 1.0e6);
+        assert_eq!(clusters.iter().map(|cluster| cluster.members.clone()).collect::<Vec<_>>(), vec![vec![sol], vec![earth, luna]]);
+        assert!(clusters[1].is_group() && clusters[1].extent < 4.0e5);
+        // Zoomed in far enough every body is drawn on its own
+        assert_eq!(cluster_bodies(&store, 1.0e4).len(), 3);
+    }
+}
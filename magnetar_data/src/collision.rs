@@ -0,0 +1,111 @@
+use std::collections::BTreeSet;
+use crate::store::{ObjectId, SystemStore};
+use crate::vector::DVec3;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Two objects touching each other
+pub struct Collision {
+    pub a: ObjectId,
+    pub b: ObjectId,
+    /// Distance between both centres in km, below the sum of both radii
+    pub distance: f64,
+    /// Velocity of `b` relative to `a` in km/s
+    pub relative_velocity: DVec3,
+}
+
+impl Collision {
+    /// Speed of the impact in km/s
+    pub fn impact_speed(&self) -> f64 {
+        self.relative_velocity.length()
+    }
+}
+
+/// All pairs of objects currently touching, checking siblings against each other and children against their parent.
+/// Objects further apart in the tree stay far apart as long as their ancestors do.
+pub fn detect_collisions(store: &SystemStore) -> Vec<Collision> {
+    let mut collisions = Vec::new();
+    for parent in store.ids() {
+        let children = store.children(parent);
+        let parent_mass = store.get(parent).map_or(0.0, |object| object.mass);
+        for (index, &a) in children.iter().enumerate() {
+            if let Some(collision) = touching(store, parent, a, parent_mass, true) {
+                collisions.push(collision);
+            }
+            for &b in &children[index + 1..] {
+                if let Some(collision) = touching(store, a, b, parent_mass, false) {
+                    collisions.push(collision);
+                }
+            }
+        }
+    }
+    collisions
+}
+
+/// Collision between `a` and `b` if they touch, `b` orbits `a` if `nested` and both orbit the same parent otherwise
+fn touching(store: &SystemStore, a: ObjectId, b: ObjectId, parent_mass: f64, nested: bool) -> Option<Collision> {
+    let (object_a, object_b) = (store.get(a)?, store.get(b)?);
+    let orbit_b = &object_b.orbital_params;
+    let (offset, relative_velocity) = if nested {
+        (orbit_b.position(), orbit_b.velocity(parent_mass))
+    } else {
+        let orbit_a = &object_a.orbital_params;
+        (orbit_b.position() - orbit_a.position(), orbit_b.velocity(parent_mass) - orbit_a.velocity(parent_mass))
+    };
+    let distance = offset.length();
+    (distance < object_a.radius + object_b.radius).then_some(Collision { a, b, distance, relative_velocity })
+}
+
+#[derive(Debug, Clone, Default)]
+/// Runs [`detect_collisions`] on every coarse tick and only reports pairs which were not touching on the previous one
+pub struct CollisionDetector {
+    touching: BTreeSet<(ObjectId, ObjectId)>,
+}
+
+impl CollisionDetector {
+    pub fn new() -> CollisionDetector {
+        CollisionDetector::default()
+    }
+
+    /// Collisions which started since the last check
+    pub fn check(&mut self, store: &SystemStore) -> Vec<Collision> {
+        let collisions = detect_collisions(store);
+        let touching: BTreeSet<_> = collisions.iter().map(|collision| (collision.a, collision.b)).collect();
+        let new = collisions.into_iter().filter(|collision| !self.touching.contains(&(collision.a, collision.b))).collect::<Vec<_>>();
+        for collision in &new {
+            tracing::info!(a = collision.a.0, b = collision.b.0, speed = collision.impact_speed(), "collision");
+        }
+        self.touching = touching;
+        new
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_collisions() {
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        assert!(detect_collisions(&SystemStore::new(system.clone())).is_empty());
+
+        let earth = &mut system.children[0];
+        let mut impactor = earth.children[0].clone();
+        impactor.name = "Impactor".to_string();
+        impactor.orbital_params.mean_anomaly += 0.1;
+        earth.children.push(impactor);
+        let store = SystemStore::new(system);
+        let mut detector = CollisionDetector::new();
+        let collisions = detector.check(&store);
+        assert_eq!(collisions.len(), 1);
+        assert_eq!((collisions[0].a, collisions[0].b), (store.find("Luna").unwrap(), store.find("Impactor").unwrap()));
+        assert!(collisions[0].impact_speed() < 0.1);
+        assert!(detector.check(&store).is_empty());
+
+        let mut store = store;
+        store.orbit_mut(store.find("Luna").unwrap()).unwrap().semi_major_axis = 7000.0;
+        let collisions = detector.check(&store);
+        assert_eq!(collisions.len(), 1);
+        assert_eq!((collisions[0].a, collisions[0].b), (store.find("Earth").unwrap(), store.find("Luna").unwrap()));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::celestial::ObjectType;
+use crate::stellar::{main_sequence_lifetime, stellar_state};
+use crate::store::{ObjectId, SystemStore};
+
+/// Colours of low to high values, a dark blue to yellow ramp which stays readable for colour blind users
+const GRADIENT: [[f32; 3]; 5] = [
+    [0.27, 0.00, 0.33],
+    [0.23, 0.32, 0.55],
+    [0.13, 0.57, 0.55],
+    [0.37, 0.79, 0.38],
+    [0.99, 0.91, 0.14],
+];
+
+/// Seconds in a Julian year
+const YEAR: f64 = 365.25 * 86400.0;
+/// Shortest and longest period in seconds shown on the period gradient, one hour to a thousand years
+const PERIOD_RANGE: (f64, f64) = (3600.0, 1000.0 * YEAR);
+
+#[derive(Debug, Clone, PartialEq)]
+/// One entry of the legend of a [`ColorMap`]
+pub struct LegendEntry {
+    pub label: String,
+    /// Linear RGB
+    pub color: [f32; 3],
+}
+
+/// Maps objects to the colour of their orbit line. Implemented by the built in [`ColorMode`]s, renderers accept
+/// any implementation so games can add their own, e.g. colouring by owner.
+pub trait ColorMap {
+    /// Linear RGB colour of the orbit of `id`, `None` to use the renderer's default
+    fn color(&self, store: &SystemStore, id: ObjectId) -> Option<[f32; 3]>;
+
+    /// Colours to explain in a legend, in the order to show them
+    fn legend(&self) -> Vec<LegendEntry>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Built in ways to colour orbits
+pub enum ColorMode {
+    #[default]
+    ObjectType,
+    /// From circular to parabolic
+    Eccentricity,
+    /// On a logarithmic scale from an hour to a thousand years
+    Period,
+    /// See [`habitability`]
+    Habitability,
+}
+
+impl ColorMode {
+    pub const ALL: [ColorMode; 4] = [ColorMode::ObjectType, ColorMode::Eccentricity, ColorMode::Period, ColorMode::Habitability];
+
+    /// The mode after this one, wrapping around, for cycling through the modes with a single key
+    pub fn next(self) -> ColorMode {
+        let index = ColorMode::ALL.iter().position(|mode| *mode == self).unwrap_or_default();
+        ColorMode::ALL[(index + 1) % ColorMode::ALL.len()]
+    }
+}
+
+impl ColorMap for ColorMode {
+    fn color(&self, store: &SystemStore, id: ObjectId) -> Option<[f32; 3]> {
+        let object = store.get(id)?;
+        match self {
+            ColorMode::ObjectType => Some(type_color(&object.object_type)),
+            _ if store.parent(id).is_none() => None,
+            ColorMode::Eccentricity => Some(gradient(object.orbital_params.eccentricity)),
+            ColorMode::Period => {
+                Some(gradient(period_value(object.orbital_params.period(store.parent_mass(id)))))
+            }
+            ColorMode::Habitability => habitability(store, id).map(gradient),
+        }
+    }
+
+    fn legend(&self) -> Vec<LegendEntry> {
+        let stops = |stops: [(&str, f64); 3]| stops.iter()
+            .map(|(label, value)| LegendEntry { label: label.to_string(), color: gradient(*value) })
+            .collect();
+        match self {
+            ColorMode::ObjectType => [ObjectType::Star, ObjectType::Rocky, ObjectType::Jovian, ObjectType::IceGiant]
+                .iter()
+                .map(|object_type| LegendEntry { label: object_type.to_string(), color: type_color(object_type) })
+                .collect(),
+            ColorMode::Eccentricity => stops([("e 0", 0.0), ("e 0.5", 0.5), ("e 1", 1.0)]),
+            ColorMode::Period => stops([("1 h", 0.0), ("1 yr", period_value(YEAR)), ("1000 yr", 1.0)]),
+            ColorMode::Habitability => stops([("0", 0.0), ("0.5", 0.5), ("1", 1.0)]),
+        }
+    }
+}
+
+fn type_color(object_type: &ObjectType) -> [f32; 3] {
+    match object_type {
+        ObjectType::Star => [1.0, 0.85, 0.3],
+        ObjectType::Rocky => [0.7, 0.55, 0.45],
+        ObjectType::Jovian => [0.9, 0.6, 0.35],
+        ObjectType::IceGiant => [0.4, 0.75, 0.95],
+    }
+}
+
+/// Position of a period in seconds on the logarithmic period gradient, 0 at the start of [`PERIOD_RANGE`] and 1 at its end
+fn period_value(period: f64) -> f64 {
+    (period / PERIOD_RANGE.0).ln() / (PERIOD_RANGE.1 / PERIOD_RANGE.0).ln()
+}
+
+/// Colour of `value` between 0 and 1 on [`GRADIENT`], values outside are clamped
+fn gradient(value: f64) -> [f32; 3] {
+    let position = value.clamp(0.0, 1.0) as f32 * (GRADIENT.len() - 1) as f32;
+    let index = (position as usize).min(GRADIENT.len() - 2);
+    let fraction = position - index as f32;
+    let (low, high) = (GRADIENT[index], GRADIENT[index + 1]);
+    [0, 1, 2].map(|channel| low[channel] + (high[channel] - low[channel]) * fraction)
+}
+
+/// How suitable the distance of an object from its star is for liquid water, 1 inside the habitable zone and
+/// falling with the ratio of the distance to the nearest edge outside of it. Moons use the orbit of their planet
+/// and stars are assumed to be halfway through their main sequence. `None` for stars and objects without one.
+pub fn habitability(store: &SystemStore, id: ObjectId) -> Option<f64> {
+    if store.get(id)?.object_type == ObjectType::Star {
+        return None;
+    }
+    let mut body = id;
+    let mut star = store.parent(id)?;
+    while store.get(star)?.object_type != ObjectType::Star {
+        body = star;
+        star = store.parent(star)?;
+    }
+    let star_mass = store.get(star)?.mass;
+    let zone = stellar_state(star_mass, main_sequence_lifetime(star_mass) / 2.0).habitable_zone();
+    let distance = store.get(body)?.orbital_params.semi_major_axis;
+    Some(if distance < zone.start {
+        distance / zone.start
+    } else if distance > zone.end {
+        zone.end / distance
+    } else {
+        1.0
+    })
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_color_modes() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let (sol, earth, luna) = (store.find("Sol").unwrap(), store.find("Earth").unwrap(), store.find("Luna").unwrap());
+        assert_eq!(habitability(&store, earth), Some(1.0));
+        assert_eq!(habitability(&store, luna), Some(1.0));
+        assert_eq!(habitability(&store, sol), None);
+
+        assert_eq!(ColorMode::Habitability.color(&store, earth), Some(GRADIENT[4]));
+        assert_eq!(ColorMode::Eccentricity.color(&store, sol), None);
+        assert_eq!(ColorMode::ObjectType.color(&store, sol), ColorMode::ObjectType.legend().first().map(|entry| entry.color));
+        // Luna's month is shorter than Earth's year, so it is further down the gradient
+        let [_, luna_green, _] = ColorMode::Period.color(&store, luna).unwrap();
+        let [_, earth_green, _] = ColorMode::Period.color(&store, earth).unwrap();
+        assert!(luna_green < earth_green);
+        // Earth's year is a year long, so its orbit has the colour of the legend's year
+        let [red, green, blue] = ColorMode::Period.legend()[1].color;
+        let [earth_red, _, earth_blue] = ColorMode::Period.color(&store, earth).unwrap();
+        assert!((red - earth_red).abs() < 1e-3 && (green - earth_green).abs() < 1e-3 && (blue - earth_blue).abs() < 1e-3);
+        assert_eq!(ColorMode::Habitability.next(), ColorMode::ObjectType);
+    }
+}
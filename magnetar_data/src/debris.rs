@@ -0,0 +1,169 @@
+use std::f64::consts::PI;
+use crate::impact::SplitMix64;
+use crate::orbital::OrbitalParameters;
+use crate::store::{ObjectId, SystemStore};
+use crate::vector::DVec3;
+
+#[derive(Debug, Clone, PartialEq)]
+/// How the debris of a destroyed object spreads
+pub struct DebrisParameters {
+    /// Number of tracked sample particles, each standing in for an equal share of the fragments
+    pub particles: usize,
+    /// Number of fragments large enough to be a hazard
+    pub fragments: f64,
+    /// Largest speed in km/s a particle is pushed away with
+    pub speed_spread: f64,
+    /// Seconds until the field has thinned out completely
+    pub lifetime: f64,
+    /// Seed for the random kicks, the same seed always gives the same field
+    pub seed: u64,
+}
+
+impl Default for DebrisParameters {
+    fn default() -> DebrisParameters {
+        DebrisParameters { particles: 64, fragments: 1000.0, speed_spread: 0.01, lifetime: 30.0 * 86400.0, seed: 0 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Cloud of fragments around the orbit of a destroyed object. Particles on slightly different orbits drift apart
+/// along the orbit until the cloud turns into a thin ring, while the number of fragments drops linearly to zero
+/// over the lifetime as they decay or disperse.
+pub struct DebrisField {
+    /// The object the debris orbits
+    pub parent: ObjectId,
+    /// Orbits of the sample particles around the parent
+    pub particles: Vec<OrbitalParameters>,
+    /// Number of hazardous fragments when the field was created
+    pub fragments: f64,
+    /// Seconds since the field was created
+    pub age: f64,
+    /// Seconds until the field is gone
+    pub lifetime: f64,
+}
+
+impl DebrisField {
+    /// Debris of the object `id` scattered around its current orbit, `None` for unknown objects and the root
+    pub fn from_object(store: &SystemStore, id: ObjectId, parameters: &DebrisParameters) -> Option<DebrisField> {
+        let parent = store.parent(id)?;
+        let parent_mass = store.parent_mass(id);
+        let orbit = &store.get(id)?.orbital_params;
+        let (position, velocity) = (orbit.position(), orbit.velocity(parent_mass));
+        let mut random = SplitMix64(parameters.seed);
+        let particles = (0..parameters.particles.max(1))
+            .map(|_| {
+                let direction = 2.0 * PI * random.next_f64();
+                let kick = DVec3::new(direction.cos(), direction.sin(), 0.0) * parameters.speed_spread * random.next_f64();
+                OrbitalParameters::from_state(position, velocity + kick, parent_mass).unwrap_or_else(|_| orbit.clone())
+            })
+            .collect();
+        Some(DebrisField { parent, particles, fragments: parameters.fragments, age: 0.0, lifetime: parameters.lifetime })
+    }
+
+    /// Moves all particles along their orbits and ages the field by a signed amount of seconds
+    ///
+    /// * `parent_mass` - Mass of the orbited object in kg
+    pub fn step_by(&mut self, seconds: f64, parent_mass: f64) {
+        for particle in &mut self.particles {
+            particle.step_by(seconds, parent_mass);
+        }
+        self.age += seconds;
+    }
+
+    /// Fraction of the initial fragments still left, from 1 when created down to 0 at the end of its lifetime
+    pub fn remaining(&self) -> f64 {
+        if self.lifetime <= 0.0 { 0.0 } else { (1.0 - self.age / self.lifetime).clamp(0.0, 1.0) }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining() == 0.0
+    }
+
+    /// Positions of the sample particles relative to the parent in km, e.g. to render the field as points
+    pub fn particle_positions(&self) -> impl Iterator<Item = DVec3> + '_ {
+        self.particles.iter().map(OrbitalParameters::position)
+    }
+
+    /// Expected number of fragments within `radius` km of `position`, relative to the parent in km
+    pub fn hazard(&self, position: DVec3, radius: f64) -> f64 {
+        let per_particle = self.fragments * self.remaining() / self.particles.len().max(1) as f64;
+        self.particle_positions().filter(|particle| particle.distance(position) <= radius).count() as f64 * per_particle
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// All debris fields of a system, stepped together with it and dropped once expired
+pub struct DebrisTracker {
+    fields: Vec<DebrisField>,
+}
+
+impl DebrisTracker {
+    pub fn new() -> DebrisTracker {
+        DebrisTracker::default()
+    }
+
+    /// Starts tracking the debris of the destroyed object `id`, call before removing it from the system
+    pub fn spawn(&mut self, store: &SystemStore, id: ObjectId, parameters: &DebrisParameters) {
+        if let Some(field) = DebrisField::from_object(store, id, parameters) {
+            tracing::info!(object = id.0, fragments = field.fragments, "debris field spawned");
+            self.fields.push(field);
+        }
+    }
+
+    pub fn fields(&self) -> &[DebrisField] {
+        &self.fields
+    }
+
+    /// Steps all fields by a signed amount of seconds and drops the expired ones
+    pub fn step_by(&mut self, store: &SystemStore, seconds: f64) {
+        for field in &mut self.fields {
+            let parent_mass = store.get(field.parent).map_or(0.0, |parent| parent.mass);
+            field.step_by(seconds, parent_mass);
+        }
+        self.fields.retain(|field| !field.is_expired());
+    }
+
+    /// Expected number of fragments within `radius` km of the object `id`, summed over all fields around its parent
+    pub fn hazard_at(&self, store: &SystemStore, id: ObjectId, radius: f64) -> f64 {
+        let (Some(parent), Some(object)) = (store.parent(id), store.get(id)) else {
+            return 0.0;
+        };
+        let position = object.orbital_params.position();
+        self.fields.iter()
+            .filter(|field| field.parent == parent)
+            .map(|field| field.hazard(position, radius))
+            .sum()
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_debris_spreads_and_expires() {
+        let mut store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let luna = store.find("Luna").unwrap();
+        let mut tracker = DebrisTracker::new();
+        tracker.spawn(&store, luna, &DebrisParameters::default());
+        assert_eq!(tracker.fields().len(), 1);
+        // Whole degree periapsis longitudes of the derived orbits put the particles a few dozen km off
+        assert!((tracker.hazard_at(&store, luna, 1000.0) - 1000.0).abs() < 1e-9);
+
+        let spread = |field: &DebrisField| {
+            let positions: Vec<DVec3> = field.particle_positions().collect();
+            positions.iter().map(|a| positions.iter().map(|b| a.distance(*b)).fold(0.0, f64::max)).fold(0.0, f64::max)
+        };
+        let initial = spread(&tracker.fields()[0]);
+        store.step_by(10.0 * 86400.0);
+        tracker.step_by(&store, 10.0 * 86400.0);
+        let field = &tracker.fields()[0];
+        assert!(spread(field) > 100.0 * initial);
+        assert!((field.remaining() - 2.0 / 3.0).abs() < 1e-9);
+        assert!(tracker.hazard_at(&store, luna, 1000.0) < 1000.0);
+
+        tracker.step_by(&store, 20.0 * 86400.0);
+        assert!(tracker.fields().is_empty());
+    }
+}
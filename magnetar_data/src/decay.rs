@@ -0,0 +1,116 @@
+use std::f64::consts::PI;
+use crate::aerobraking::Atmosphere;
+use crate::celestial::Object;
+use crate::orbital::GRAVITATIONAL_CONSTANT;
+use crate::store::{ObjectId, SystemStore};
+
+/// Drag coefficient assumed for all objects, typical for satellites in free molecular flow
+pub const DRAG_COEFFICIENT: f64 = 2.2;
+
+#[derive(Debug, Clone, PartialEq)]
+/// An object whose orbit decayed into the surface of its parent
+pub struct Deorbit {
+    pub id: ObjectId,
+    pub parent: ObjectId,
+    /// Seconds into the decay step at which the periapsis reached the surface
+    pub time: f64,
+}
+
+/// Mass over drag area of a spherical object in kg/m²
+pub fn ballistic_coefficient(object: &Object) -> f64 {
+    object.mass / (DRAG_COEFFICIENT * PI * (object.radius * 1000.0).powi(2))
+}
+
+/// Rate of change of the semi-major axis in km/s from drag in the atmosphere of `parent`, 0 if the periapsis
+/// lies above the atmosphere. Uses the circular orbit drag law with the density averaged over one orbit,
+/// on eccentric orbits only a short arc around the periapsis passes through dense air.
+pub fn decay_rate(object: &Object, parent: &Object) -> f64 {
+    let Some(atmosphere) = Atmosphere::of(parent) else {
+        return 0.0;
+    };
+    let orbit = &object.orbital_params;
+    let altitude = orbit.periapsis() - parent.radius;
+    let ballistic_coefficient = ballistic_coefficient(object);
+    if orbit.semi_major_axis <= 0.0 || altitude >= atmosphere.top() || ballistic_coefficient <= 0.0 {
+        return 0.0;
+    }
+    let arc_fraction = (atmosphere.scale_height / (2.0 * PI * orbit.semi_major_axis * orbit.eccentricity)).sqrt().min(1.0);
+    let density = atmosphere.density(altitude.max(0.0)) * arc_fraction;
+    let mu = GRAVITATIONAL_CONSTANT * parent.mass;
+    // kg m⁻³ / kg m⁻² is m⁻¹, times 1000 for km⁻¹
+    -density / ballistic_coefficient * 1000.0 * (mu * orbit.semi_major_axis).sqrt()
+}
+
+/// Lets the orbits of all objects with their periapsis inside an atmosphere decay over `seconds`.
+/// The apoapsis drops first until the orbit is circular, then both sink together. Returns every object whose
+/// periapsis reached the surface, their orbits are left there so gameplay can remove or merge them,
+/// e.g. with [`resolve_merge`](crate::impact::resolve_merge).
+///
+/// This is separate from orbital stepping and meant to run once per coarse tick.
+pub fn apply_decay(store: &mut SystemStore, seconds: f64) -> Vec<Deorbit> {
+    let mut deorbits = Vec::new();
+    let ids: Vec<ObjectId> = store.ids().collect();
+    for id in ids {
+        let Some(parent) = store.parent(id) else {
+            continue;
+        };
+        let (object, parent_object) = (store.get(id).unwrap().clone(), store.get(parent).unwrap().clone());
+        if decay_rate(&object, &parent_object) == 0.0 {
+            continue;
+        }
+        let scale_height = Atmosphere::of(&parent_object).map_or(1.0, |atmosphere| atmosphere.scale_height);
+        let mut object = object;
+        let mut elapsed = 0.0;
+        while elapsed < seconds {
+            let rate = decay_rate(&object, &parent_object);
+            if rate == 0.0 {
+                break;
+            }
+            // Small enough steps for the density to change by about 1% at most
+            let step = (seconds - elapsed).min(0.01 * scale_height / rate.abs());
+            let orbit = &mut object.orbital_params;
+            let periapsis = orbit.periapsis();
+            orbit.semi_major_axis = (orbit.semi_major_axis + rate * step).max(parent_object.radius);
+            orbit.eccentricity = (1.0 - periapsis / orbit.semi_major_axis).max(0.0);
+            elapsed += step;
+            if orbit.periapsis() <= parent_object.radius {
+                tracing::info!(object = %object.name, parent = %parent_object.name, "orbit decayed into the surface");
+                deorbits.push(Deorbit { id, parent, time: elapsed });
+                break;
+            }
+        }
+        *store.orbit_mut(id).unwrap() = object.orbital_params;
+    }
+    deorbits
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::orbital::OrbitalParameters;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_low_orbit_decays() {
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let mut station = system.children[0].children[0].clone();
+        (station.name, station.mass, station.radius) = ("Station".to_string(), 400_000.0, 0.05);
+        station.orbital_params = OrbitalParameters { semi_major_axis: 6371.0 + 115.0, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 0.0 };
+        let mut high = station.clone();
+        high.name = "High".to_string();
+        high.orbital_params.semi_major_axis = 6371.0 + 300.0;
+        system.children[0].children.extend([station, high]);
+        let mut store = SystemStore::new(system);
+        let (earth, station, high) = (store.find("Earth").unwrap(), store.find("Station").unwrap(), store.find("High").unwrap());
+
+        assert!(apply_decay(&mut store, 1.0).is_empty());
+        let after_second = store.get(station).unwrap().orbital_params.semi_major_axis;
+        assert!(after_second < 6371.0 + 115.0);
+
+        let deorbits = apply_decay(&mut store, 86400.0);
+        assert_eq!(deorbits.len(), 1);
+        assert_eq!((deorbits[0].id, deorbits[0].parent), (station, earth));
+        assert_eq!(store.get(high).unwrap().orbital_params.semi_major_axis, 6371.0 + 300.0);
+        assert_eq!(store.get(store.find("Luna").unwrap()).unwrap().orbital_params.semi_major_axis, 384_400.0);
+    }
+}
@@ -0,0 +1,165 @@
+#[cfg(feature = "csv")]
+use std::io::{self, Write};
+use crate::aerobraking::Atmosphere;
+use crate::celestial::ObjectType;
+use crate::orbital::{OrbitalParameters, GRAVITATIONAL_CONSTANT};
+use crate::store::{ObjectId, SystemStore};
+use crate::transfer::hohmann;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Delta-v in km/s needed to get from a low orbit around one body to a low orbit around another,
+/// for every pair of planets and moons in a system
+pub struct DeltaVMap {
+    bodies: Vec<ObjectId>,
+    /// Row major, departures in rows and arrivals in columns
+    delta_v: Vec<f64>,
+}
+
+impl DeltaVMap {
+    /// Estimates the delta-v between all bodies which are not stars using patched conics: Hohmann transfers
+    /// between circular orbits around the closest common ancestor, escape and capture burns at the low orbits
+    /// of both ends and at the orbits of all bodies in between. Aerobraking is not taken into account.
+    pub fn compute(store: &SystemStore) -> DeltaVMap {
+        let _span = tracing::debug_span!("delta_v_map").entered();
+        let bodies: Vec<ObjectId> = store.ids()
+            .filter(|id| store.get(*id).is_some_and(|object| object.object_type != ObjectType::Star))
+            .collect();
+        let delta_v = bodies.iter()
+            .flat_map(|&from| bodies.iter().map(move |&to| (from, to)))
+            .map(|(from, to)| delta_v_between(store, from, to))
+            .collect();
+        DeltaVMap { bodies, delta_v }
+    }
+
+    /// Bodies in the order of the rows and columns
+    pub fn bodies(&self) -> &[ObjectId] {
+        &self.bodies
+    }
+
+    /// Delta-v in km/s from a low orbit around `from` to one around `to`, `None` if either is not in the map
+    pub fn get(&self, from: ObjectId, to: ObjectId) -> Option<f64> {
+        let row = self.bodies.iter().position(|id| *id == from)?;
+        let column = self.bodies.iter().position(|id| *id == to)?;
+        Some(self.delta_v[row * self.bodies.len() + column])
+    }
+
+    /// Delta-v values of one departure body in the order of [`DeltaVMap::bodies`]
+    pub fn row(&self, from: ObjectId) -> Option<&[f64]> {
+        let row = self.bodies.iter().position(|id| *id == from)?;
+        Some(&self.delta_v[row * self.bodies.len()..(row + 1) * self.bodies.len()])
+    }
+
+    /// Writes the map as CSV with a header row of arrival names and the departure name in the first column
+    #[cfg(feature = "csv")]
+    pub fn write_csv(&self, store: &SystemStore, mut writer: impl Write) -> io::Result<()> {
+        let name = |id: ObjectId| csv_field(store.get(id).map_or("", |object| &object.name));
+        write!(writer, "from")?;
+        for &to in &self.bodies {
+            write!(writer, ",{}", name(to))?;
+        }
+        writeln!(writer)?;
+        for &from in &self.bodies {
+            write!(writer, "{}", name(from))?;
+            for value in self.row(from).unwrap() {
+                write!(writer, ",{}", value)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "csv")]
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Radius in km of a low parking orbit, just above the atmosphere or a tenth of the radius above the surface
+pub fn low_orbit_radius(store: &SystemStore, id: ObjectId) -> f64 {
+    store.get(id).map_or(0.0, |object| {
+        let atmosphere = Atmosphere::of(object).map_or(0.0, |atmosphere| atmosphere.top());
+        object.radius + atmosphere.max(0.1 * object.radius)
+    })
+}
+
+/// Delta-v in km/s from a low orbit around `from` to one around `to`
+pub fn delta_v_between(store: &SystemStore, from: ObjectId, to: ObjectId) -> f64 {
+    if from == to {
+        return 0.0;
+    }
+    let (ancestors_from, ancestors_to) = (ancestors(store, from), ancestors(store, to));
+    let Some(&common) = ancestors_from.iter().find(|id| ancestors_to.contains(id)) else {
+        return f64::INFINITY;
+    };
+    // Bodies passed on the way up to the common ancestor, starting at the end points
+    let chain_from = &ancestors_from[..ancestors_from.iter().position(|id| *id == common).unwrap()];
+    let chain_to = &ancestors_to[..ancestors_to.iter().position(|id| *id == common).unwrap()];
+    let transfer_radius = |chain: &[ObjectId]| chain.last().map_or(low_orbit_radius(store, common), |top| {
+        store.get(*top).map_or(0.0, |object| object.orbital_params.semi_major_axis)
+    });
+    let circular = |radius: f64| OrbitalParameters { semi_major_axis: radius, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 0.0 };
+    let common_mass = store.get(common).map_or(0.0, |object| object.mass);
+    let transfer = hohmann(&circular(transfer_radius(chain_from)), &circular(transfer_radius(chain_to)), common_mass);
+    escape(store, chain_from, transfer.departure_delta_v) + escape(store, chain_to, transfer.arrival_delta_v)
+}
+
+/// The object itself followed by all of its ancestors up to the root
+fn ancestors(store: &SystemStore, id: ObjectId) -> Vec<ObjectId> {
+    std::iter::successors(Some(id), |id| store.parent(*id)).collect()
+}
+
+/// Delta-v to climb from a low orbit around the first body of `chain` out of the sphere of influence of the last
+/// one with `excess` km/s left, one burn per body. Without a chain the excess is burnt directly. Capturing takes the same.
+fn escape(store: &SystemStore, chain: &[ObjectId], excess: f64) -> f64 {
+    if chain.is_empty() {
+        return excess;
+    }
+    chain.iter().enumerate()
+        .map(|(index, &body)| {
+            let mu = GRAVITATIONAL_CONSTANT * store.get(body).map_or(0.0, |object| object.mass);
+            let radius = match index {
+                0 => low_orbit_radius(store, body),
+                _ => store.get(chain[index - 1]).map_or(0.0, |object| object.orbital_params.semi_major_axis),
+            };
+            let excess = if index == chain.len() - 1 { excess } else { 0.0 };
+            (excess.powi(2) + 2.0 * mu / radius).sqrt() - (mu / radius).sqrt()
+        })
+        .sum()
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_earth_to_luna() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let (earth, luna) = (store.find("Earth").unwrap(), store.find("Luna").unwrap());
+        let map = DeltaVMap::compute(&store);
+        assert_eq!(map.bodies(), &[earth, luna]);
+        // About 3.1 km/s for the trans lunar injection and 0.8 km/s for the lunar orbit insertion
+        let delta_v = map.get(earth, luna).unwrap();
+        assert!((3.6..4.2).contains(&delta_v), "{}", delta_v);
+        assert_eq!(map.get(luna, earth), Some(delta_v));
+        assert_eq!(map.get(earth, earth), Some(0.0));
+        assert!(map.get(store.root_id(), earth).is_none());
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_write_csv() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let mut csv = Vec::new();
+        DeltaVMap::compute(&store).write_csv(&store, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "from,Earth,Luna");
+        assert!(lines[1].starts_with("Earth,0,"));
+    }
+}
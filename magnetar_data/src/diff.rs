@@ -0,0 +1,346 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::celestial::{Object, ObjectType};
+use crate::surface::Rotation;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A single value of an object that can be compared and merged, children are handled separately
+pub enum Field {
+    Type,
+    Mass,
+    Radius,
+    SemiMajorAxis,
+    Eccentricity,
+    LongitudeOfPeriapsis,
+    MeanAnomaly,
+    ScaleHeight,
+    Rotation,
+    Icon,
+    /// Partial pressure of one gas in the atmosphere
+    Gas(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Value of a [`Field`] of one object
+pub enum Value {
+    Type(ObjectType),
+    Number(f64),
+    Rotation(Rotation),
+    Text(String),
+    /// An optional field that is not set, e.g. a gas missing from the atmosphere
+    Unset,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A field with a different value in the old and the new object
+pub struct FieldChange {
+    pub field: Field,
+    pub old: Value,
+    pub new: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Difference of a child matched by name between two objects
+pub enum ChildDiff {
+    Added(Object),
+    Removed(Object),
+    Changed(ObjectDiff),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Structural difference between two object trees, created by [`diff`]
+pub struct ObjectDiff {
+    /// Name of the old object
+    pub name: String,
+    pub changes: Vec<FieldChange>,
+    /// Only children that differ, unchanged ones are left out
+    pub children: Vec<ChildDiff>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A field or child both sides changed in different ways, the merge keeps our side
+pub struct Conflict {
+    /// Names from the root down to the object, e.g. `Sol/Earth`
+    pub path: String,
+    /// The conflicting field, `None` if one side removed the object while the other changed it or
+    /// both added different objects with the same name
+    pub field: Option<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Result of a three-way [`merge`]
+pub struct Merge {
+    pub merged: Object,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Field {
+    /// All fields of the given objects, with the gases of all atmospheres in alphabetical order
+    pub fn all(objects: &[&Object]) -> Vec<Field> {
+        let mut fields = vec![
+            Field::Type, Field::Mass, Field::Radius, Field::SemiMajorAxis, Field::Eccentricity,
+            Field::LongitudeOfPeriapsis, Field::MeanAnomaly, Field::ScaleHeight, Field::Rotation, Field::Icon,
+        ];
+        let mut gases: Vec<&String> = objects.iter().flat_map(|object| object.atmosphere.keys()).collect();
+        gases.sort();
+        gases.dedup();
+        fields.extend(gases.into_iter().map(|gas| Field::Gas(gas.clone())));
+        fields
+    }
+
+    pub fn get(&self, object: &Object) -> Value {
+        let orbit = &object.orbital_params;
+        match self {
+            Field::Type => Value::Type(object.object_type.clone()),
+            Field::Mass => Value::Number(object.mass),
+            Field::Radius => Value::Number(object.radius),
+            Field::SemiMajorAxis => Value::Number(orbit.semi_major_axis),
+            Field::Eccentricity => Value::Number(orbit.eccentricity),
+            Field::LongitudeOfPeriapsis => Value::Number(orbit.longitude_of_periapsis as f64),
+            Field::MeanAnomaly => Value::Number(orbit.mean_anomaly),
+            Field::ScaleHeight => object.scale_height.map_or(Value::Unset, Value::Number),
+            Field::Rotation => object.rotation.map_or(Value::Unset, Value::Rotation),
+            Field::Icon => object.icon.clone().map_or(Value::Unset, Value::Text),
+            Field::Gas(gas) => object.atmosphere.get(gas).copied().map_or(Value::Unset, Value::Number),
+        }
+    }
+
+    /// Sets the field of `object`, values of the wrong kind are ignored
+    pub fn set(&self, object: &mut Object, value: Value) {
+        let orbit = &mut object.orbital_params;
+        match (self, value) {
+            (Field::Type, Value::Type(object_type)) => object.object_type = object_type,
+            (Field::Mass, Value::Number(value)) => object.mass = value,
+            (Field::Radius, Value::Number(value)) => object.radius = value,
+            (Field::SemiMajorAxis, Value::Number(value)) => orbit.semi_major_axis = value,
+            (Field::Eccentricity, Value::Number(value)) => orbit.eccentricity = value,
+            (Field::LongitudeOfPeriapsis, Value::Number(value)) => orbit.longitude_of_periapsis = value as u16,
+            (Field::MeanAnomaly, Value::Number(value)) => orbit.mean_anomaly = value,
+            (Field::ScaleHeight, Value::Number(value)) => object.scale_height = Some(value),
+            (Field::ScaleHeight, Value::Unset) => object.scale_height = None,
+            (Field::Rotation, Value::Rotation(rotation)) => object.rotation = Some(rotation),
+            (Field::Rotation, Value::Unset) => object.rotation = None,
+            (Field::Icon, Value::Text(icon)) => object.icon = Some(icon),
+            (Field::Icon, Value::Unset) => object.icon = None,
+            (Field::Gas(gas), Value::Number(value)) => {
+                object.atmosphere.insert(gas.clone(), value);
+            }
+            (Field::Gas(gas), Value::Unset) => {
+                object.atmosphere.remove(gas);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    /// Formats the field as its key in system files
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Type => f.write_str("type"),
+            Field::Mass => f.write_str("mass"),
+            Field::Radius => f.write_str("radius"),
+            Field::SemiMajorAxis => f.write_str("semi-major-axis"),
+            Field::Eccentricity => f.write_str("eccentricity"),
+            Field::LongitudeOfPeriapsis => f.write_str("longitude-of-periapsis"),
+            Field::MeanAnomaly => f.write_str("mean-anomaly"),
+            Field::ScaleHeight => f.write_str("scale-height"),
+            Field::Rotation => f.write_str("rotation"),
+            Field::Icon => f.write_str("icon"),
+            Field::Gas(gas) => write!(f, "atmosphere.{}", gas),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    /// Parses a field from its key in system files as written by `Display`, e.g. `atmosphere.N2`
+    fn from_str(key: &str) -> Result<Field, String> {
+        match key {
+            "type" => Ok(Field::Type),
+            "mass" => Ok(Field::Mass),
+            "radius" => Ok(Field::Radius),
+            "semi-major-axis" => Ok(Field::SemiMajorAxis),
+            "eccentricity" => Ok(Field::Eccentricity),
+            "longitude-of-periapsis" => Ok(Field::LongitudeOfPeriapsis),
+            "mean-anomaly" => Ok(Field::MeanAnomaly),
+            "scale-height" => Ok(Field::ScaleHeight),
+            "rotation" => Ok(Field::Rotation),
+            "icon" => Ok(Field::Icon),
+            _ => match key.strip_prefix("atmosphere.") {
+                Some(gas) if !gas.is_empty() => Ok(Field::Gas(gas.to_string())),
+                _ => Err(format!("Unknown field '{}'", key)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Type(object_type) => write!(f, "{}", object_type),
+            // Scientific notation for very large values such as masses
+            Value::Number(value) if value.abs() >= 1e12 => write!(f, "{:e}", value),
+            Value::Number(value) => write!(f, "{}", value),
+            Value::Rotation(rotation) => write!(f, "{} s at {}°", rotation.period, rotation.prime_meridian),
+            Value::Text(text) => f.write_str(text),
+            Value::Unset => f.write_str("unset"),
+        }
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{}: {} changed on both sides", self.path, field),
+            None => write!(f, "{}: changed on one side and removed or added differently on the other", self.path),
+        }
+    }
+}
+
+impl ObjectDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.children.is_empty()
+    }
+}
+
+/// Compares two object trees field by field, children are matched by name regardless of their order
+pub fn diff(old: &Object, new: &Object) -> ObjectDiff {
+    let changes = Field::all(&[old, new]).into_iter()
+        .filter_map(|field| {
+            let (old_value, new_value) = (field.get(old), field.get(new));
+            (old_value != new_value).then_some(FieldChange { field, old: old_value, new: new_value })
+        })
+        .collect();
+    let mut children = Vec::new();
+    for old_child in &old.children {
+        match new.children.iter().find(|child| child.name == old_child.name) {
+            Some(new_child) => {
+                let child_diff = diff(old_child, new_child);
+                if !child_diff.is_empty() {
+                    children.push(ChildDiff::Changed(child_diff));
+                }
+            }
+            None => children.push(ChildDiff::Removed(old_child.clone())),
+        }
+    }
+    for new_child in &new.children {
+        if !old.children.iter().any(|child| child.name == new_child.name) {
+            children.push(ChildDiff::Added(new_child.clone()));
+        }
+    }
+    ObjectDiff { name: old.name.clone(), changes, children }
+}
+
+/// Three-way merge of two edited versions of the `base` tree, children are matched by name.
+/// Changes made on only one side are taken, where both sides changed the same field or child differently
+/// ours is kept and a conflict reported. The result does not depend on anything but the three trees.
+pub fn merge(base: &Object, ours: &Object, theirs: &Object) -> Merge {
+    let mut conflicts = Vec::new();
+    let merged = merge_objects(base, ours, theirs, &base.name, &mut conflicts);
+    Merge { merged, conflicts }
+}
+
+fn merge_objects(base: &Object, ours: &Object, theirs: &Object, path: &str, conflicts: &mut Vec<Conflict>) -> Object {
+    let mut merged = ours.clone();
+    merged.children.clear();
+    if ours.name == base.name {
+        merged.name = theirs.name.clone();
+    }
+    for field in Field::all(&[base, ours, theirs]) {
+        let (base_value, our_value, their_value) = (field.get(base), field.get(ours), field.get(theirs));
+        if our_value == base_value {
+            field.set(&mut merged, their_value);
+        } else if their_value != base_value && their_value != our_value {
+            conflicts.push(Conflict { path: path.to_string(), field: Some(field) });
+        }
+    }
+
+    // Keep the order of ours, then append children only they added
+    let names = ours.children.iter().chain(&theirs.children).chain(&base.children).map(|child| child.name.as_str());
+    let mut seen: Vec<&str> = Vec::new();
+    for name in names {
+        if seen.contains(&name) {
+            continue;
+        }
+        seen.push(name);
+        let find = |object: &'_ Object| object.children.iter().find(|child| child.name == name).cloned();
+        let child_path = format!("{}/{}", path, name);
+        let conflict = || Conflict { path: child_path.clone(), field: None };
+        match (find(base), find(ours), find(theirs)) {
+            (Some(base_child), Some(our_child), Some(their_child)) => {
+                merged.children.push(merge_objects(&base_child, &our_child, &their_child, &child_path, conflicts));
+            }
+            (Some(base_child), Some(our_child), None) => {
+                if our_child != base_child {
+                    conflicts.push(conflict());
+                    merged.children.push(our_child);
+                }
+            }
+            (Some(base_child), None, Some(their_child)) => {
+                if their_child != base_child {
+                    conflicts.push(conflict());
+                }
+            }
+            (None, Some(our_child), Some(their_child)) => {
+                if our_child != their_child {
+                    conflicts.push(conflict());
+                }
+                merged.children.push(our_child);
+            }
+            (None, Some(child), None) | (None, None, Some(child)) => merged.children.push(child),
+            (_, None, None) => {}
+        }
+    }
+    merged
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    fn system() -> Object {
+        load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap()
+    }
+
+    #[test]
+    fn test_diff_fields_and_children() {
+        let old = system();
+        let mut new = old.clone();
+        new.children[0].mass *= 2.0;
+        new.children[0].atmosphere.remove("O2");
+        new.children[0].children.clear();
+        let diff = diff(&old, &new);
+        assert!(diff.changes.is_empty());
+        let [ChildDiff::Changed(earth)] = diff.children.as_slice() else { panic!("{:?}", diff.children) };
+        assert_eq!(earth.changes.iter().map(|change| change.field.clone()).collect::<Vec<_>>(),
+                   vec![Field::Mass, Field::Gas("O2".to_string())]);
+        assert!(matches!(earth.children.as_slice(), [ChildDiff::Removed(luna)] if luna.name == "Luna"));
+        for field in Field::all(&[&old.children[0]]) {
+            assert_eq!(field.to_string().parse(), Ok(field));
+        }
+    }
+
+    #[test]
+    fn test_merge_combines_both_sides_and_reports_conflicts() {
+        let base = system();
+        let (mut ours, mut theirs) = (base.clone(), base.clone());
+        ours.children[0].radius = 6378.0;
+        ours.children[0].children[0].mass = 7.0e22;
+        theirs.children[0].scale_height = None;
+        theirs.children[0].children[0].mass = 8.0e22;
+        let mut phobos = theirs.children[0].children[0].clone();
+        phobos.name = "Phobos".to_string();
+        theirs.children[0].children.push(phobos);
+
+        let merge = merge(&base, &ours, &theirs);
+        let earth = &merge.merged.children[0];
+        assert_eq!((earth.radius, earth.scale_height), (6378.0, None));
+        assert_eq!(earth.children.iter().map(|child| child.name.as_str()).collect::<Vec<_>>(), vec!["Luna", "Phobos"]);
+        assert_eq!(earth.children[0].mass, 7.0e22);
+        assert_eq!(merge.conflicts, vec![Conflict { path: "Sol/Earth/Luna".to_string(), field: Some(Field::Mass) }]);
+        assert_eq!(super::merge(&base, &theirs, &ours).merged.children[0].children.len(), 2);
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use crate::store::{ObjectId, SystemStore};
+use crate::vector::DVec3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How much of the farther object is covered at the peak of an occultation
+pub enum OccultationKind {
+    /// The nearer object covers the farther one completely, e.g. a total solar eclipse
+    Total,
+    /// The nearer object lies completely inside the disk of the farther one, e.g. a transit of Venus
+    /// or an annular eclipse
+    Transit,
+    /// The disks only partially overlap
+    Partial,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// One object passing in front of another as seen from an observer
+pub struct Occultation {
+    /// The nearer object
+    pub front: ObjectId,
+    /// The farther, covered object
+    pub back: ObjectId,
+    pub kind: OccultationKind,
+    /// Time the disks start to overlap in seconds from the epoch
+    pub start: f64,
+    /// Time of the closest alignment in seconds from the epoch
+    pub peak: f64,
+    /// Time the disks stop overlapping in seconds from the epoch
+    pub end: f64,
+}
+
+/// Finds every eclipse, transit and occultation seen from `observer` between `start` and `end` seconds
+/// from the state of `store`, sampled every `resolution` seconds. Start and end of each event are refined
+/// by bisection and the peak by golden section search, events shorter than the resolution can be missed.
+///
+/// Orbits are planar, so every alignment of two bodies with the observer is seen edge on.
+pub fn find_occultations(store: &SystemStore, observer: ObjectId, start: f64, end: f64, resolution: f64) -> Vec<Occultation> {
+    let _span = tracing::debug_span!("find_occultations", start, end, resolution).entered();
+    let pairs: Vec<(ObjectId, ObjectId)> = store.ids()
+        .flat_map(|a| store.ids().map(move |b| (a, b)))
+        .filter(|(a, b)| a != b && *a != observer && *b != observer)
+        .collect();
+    let radii: Vec<f64> = store.ids().map(|id| store.get(id).map_or(0.0, |object| object.radius)).collect();
+
+    let mut occultations = Vec::new();
+    // Events still in progress at the previous sample by pair, with their start and the best sampled peak so far
+    let mut open: HashMap<(ObjectId, ObjectId), (f64, f64, f64)> = HashMap::new();
+    let samples = ((end - start) / resolution).ceil().max(0.0) as usize;
+    for sample in 0..=samples {
+        let time = (start + sample as f64 * resolution).min(end);
+        let positions = positions_at(store, time);
+        for &(front, back) in &pairs {
+            let overlap = disk_overlap(&positions, &radii, observer, front, back);
+            match (overlap, open.get_mut(&(front, back))) {
+                (Some((ratio, _)), Some(event)) => {
+                    if ratio < event.2 {
+                        (event.1, event.2) = (time, ratio);
+                    }
+                }
+                (Some((ratio, _)), None) => {
+                    let event_start = if sample == 0 {
+                        time
+                    } else {
+                        refine_edge(store, &radii, observer, front, back, time - resolution, time)
+                    };
+                    open.insert((front, back), (event_start, time, ratio));
+                }
+                (None, Some(_)) => {
+                    let (event_start, peak, _) = open.remove(&(front, back)).unwrap();
+                    let event_end = refine_edge(store, &radii, observer, front, back, time, time - resolution);
+                    occultations.push(refine_peak(store, &radii, observer, front, back, event_start, peak, event_end));
+                }
+                (None, None) => {}
+            }
+        }
+    }
+    for ((front, back), (event_start, peak, _)) in open {
+        occultations.push(refine_peak(store, &radii, observer, front, back, event_start, peak, end));
+    }
+    occultations.sort_by(|a, b| a.start.total_cmp(&b.start));
+    occultations
+}
+
+/// Absolute positions of all objects indexed by id, `seconds` after the state of `store`
+fn positions_at(store: &SystemStore, seconds: f64) -> Vec<DVec3> {
+    let mut store = store.clone();
+    store.step_by(seconds);
+    store.ids().map(|id| store.absolute_position(id).unwrap_or_default()).collect()
+}
+
+/// Whether the disks of `front` and `back` overlap as seen from `observer` with `front` being nearer.
+/// Returns the angular separation relative to the sum of both angular radii, below 1 while overlapping,
+/// and how the disks overlap.
+fn disk_overlap(positions: &[DVec3], radii: &[f64], observer: ObjectId, front: ObjectId, back: ObjectId)
+                -> Option<(f64, OccultationKind)> {
+    let eye = positions[observer.0 as usize];
+    let (to_front, to_back) = (positions[front.0 as usize] - eye, positions[back.0 as usize] - eye);
+    let (front_distance, back_distance) = (to_front.length(), to_back.length());
+    if front_distance >= back_distance || front_distance == 0.0 {
+        return None;
+    }
+    let front_radius = angular_radius(radii[front.0 as usize], front_distance);
+    let back_radius = angular_radius(radii[back.0 as usize], back_distance);
+    let separation = to_front.cross(to_back).length().atan2(to_front.dot(to_back));
+    let ratio = separation / (front_radius + back_radius);
+    if ratio >= 1.0 {
+        return None;
+    }
+    let kind = if separation + back_radius <= front_radius {
+        OccultationKind::Total
+    } else if separation + front_radius <= back_radius {
+        OccultationKind::Transit
+    } else {
+        OccultationKind::Partial
+    };
+    Some((ratio, kind))
+}
+
+/// Angular radius in radians of a sphere with `radius` seen from `distance`
+fn angular_radius(radius: f64, distance: f64) -> f64 {
+    (radius / distance).min(1.0).asin()
+}
+
+/// Bisects between a time without overlap and one with overlap for the moment the disks touch
+fn refine_edge(store: &SystemStore, radii: &[f64], observer: ObjectId, front: ObjectId, back: ObjectId,
+               mut outside: f64, mut inside: f64) -> f64 {
+    for _ in 0..40 {
+        let middle = (outside + inside) / 2.0;
+        if disk_overlap(&positions_at(store, middle), radii, observer, front, back).is_some() {
+            inside = middle;
+        } else {
+            outside = middle;
+        }
+    }
+    (outside + inside) / 2.0
+}
+
+/// Golden section search for the closest alignment between `start` and `end`, keeps the sampled `peak` if it is closer
+#[allow(clippy::too_many_arguments)]
+fn refine_peak(store: &SystemStore, radii: &[f64], observer: ObjectId, front: ObjectId, back: ObjectId,
+               start: f64, peak: f64, end: f64) -> Occultation {
+    let overlap = |time: f64| disk_overlap(&positions_at(store, time), radii, observer, front, back);
+    let ratio = |time: f64| overlap(time).map_or(f64::INFINITY, |(ratio, _)| ratio);
+    let inverse_golden_ratio = (5f64.sqrt() - 1.0) / 2.0;
+    // The separation has a single minimum while the disks overlap
+    let (mut low, mut high) = (start, end);
+    for _ in 0..60 {
+        let a = high - inverse_golden_ratio * (high - low);
+        let b = low + inverse_golden_ratio * (high - low);
+        if ratio(a) < ratio(b) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+    let refined = (low + high) / 2.0;
+    let (peak, kind) = match (overlap(refined), overlap(peak)) {
+        (Some((refined_ratio, kind)), Some((sampled_ratio, _))) if refined_ratio <= sampled_ratio => (refined, kind),
+        (_, Some((_, kind))) => (peak, kind),
+        (Some((_, kind)), None) => (refined, kind),
+        (None, None) => (peak, OccultationKind::Partial),
+    };
+    Occultation { front, back, kind, start, peak, end }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_solar_eclipse_every_synodic_month() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let (sol, earth, luna) = (store.root_id(), store.find("Earth").unwrap(), store.find("Luna").unwrap());
+        let occultations = find_occultations(&store, earth, 0.0, 60.0 * 86400.0, 600.0);
+        assert_eq!(occultations.len(), 2);
+        for occultation in &occultations {
+            assert_eq!((occultation.front, occultation.back), (luna, sol));
+            assert!(occultation.start < occultation.peak && occultation.peak < occultation.end);
+            // Luna crosses the disk of Sol in a few hours
+            let hours = (occultation.end - occultation.start) / 3600.0;
+            assert!((1.0..6.0).contains(&hours), "{}", hours);
+        }
+        let month = (occultations[1].peak - occultations[0].peak) / 86400.0;
+        assert!((month - 29.5).abs() < 1.0, "{}", month);
+    }
+}
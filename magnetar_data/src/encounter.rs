@@ -0,0 +1,115 @@
+use crate::nbody::{Body, Gravity, NBodyRegion};
+use crate::orbital::OrbitalParameters;
+use crate::stability::hill_radius;
+use crate::store::{ObjectId, SystemStore};
+use crate::vector::DVec3;
+
+/// Largest relative change of an acceleration within one step, see [`NBodyRegion::tolerance`]
+const TOLERANCE: f64 = 1e-3;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Siblings that came within the Hill sphere of one another. Instead of following their orbits on rails they are
+/// integrated together with their parent, so they can pull each other onto new orbits.
+pub struct Encounter {
+    pub parent: ObjectId,
+    pub members: Vec<ObjectId>,
+    /// The parent followed by the members, relative to where the parent was at the start
+    region: NBodyRegion,
+}
+
+impl Encounter {
+    /// Integrate the members and their parent by a signed amount of seconds
+    pub fn step_by(&mut self, seconds: f64) {
+        self.region.step_by(seconds);
+    }
+
+    /// Put the members on the orbits they ended up on. Members whose new orbit cannot be represented, e.g. because
+    /// they were flung out of their parent's gravity, keep the orbit they have in the store.
+    pub fn apply(&self, store: &mut SystemStore) {
+        let Some((parent, members)) = self.region.bodies.split_first() else {
+            return;
+        };
+        for (id, body) in self.members.iter().zip(members) {
+            match OrbitalParameters::from_state(body.position - parent.position, body.velocity - parent.velocity, parent.mass) {
+                Ok(orbit) => {
+                    if let Some(current) = store.orbit_mut(*id) {
+                        *current = orbit;
+                    }
+                }
+                Err(e) => tracing::warn!(object = ?store.get(*id).map(|object| &object.name), error = %e, "keeping the orbit on rails after an encounter"),
+            }
+        }
+    }
+}
+
+/// Every encounter in a system, at most one per parent with all of its children that are closer to a sibling than
+/// the larger Hill radius of the two
+pub fn encounters(store: &SystemStore) -> Vec<Encounter> {
+    store.ids().filter_map(|parent| encounter(store, parent)).collect()
+}
+
+fn encounter(store: &SystemStore, parent: ObjectId) -> Option<Encounter> {
+    let children = store.children(parent);
+    let (positions, hill): (Vec<DVec3>, Vec<f64>) = children.iter()
+        .map(|child| (store.get(*child).map_or(DVec3::ZERO, |object| object.orbital_params.position()),
+                      hill_radius(store, *child).unwrap_or(0.0)))
+        .unzip();
+    let mut close = vec![false; children.len()];
+    for i in 0..children.len() {
+        for j in i + 1..children.len() {
+            if positions[i].distance(positions[j]) < hill[i].max(hill[j]) {
+                (close[i], close[j]) = (true, true);
+            }
+        }
+    }
+    let members: Vec<ObjectId> = children.iter().zip(close).filter(|(_, close)| *close).map(|(child, _)| *child).collect();
+    if members.is_empty() {
+        return None;
+    }
+    let parent_mass = store.get(parent)?.mass;
+    let mut bodies = vec![Body { position: DVec3::ZERO, velocity: DVec3::ZERO, mass: parent_mass }];
+    bodies.extend(members.iter().filter_map(|member| store.get(*member)).map(|object| Body {
+        position: object.orbital_params.position(),
+        velocity: object.orbital_params.velocity(parent_mass),
+        mass: object.mass,
+    }));
+    Some(Encounter { parent, members, region: NBodyRegion::new(bodies, Gravity::Direct).with_tolerance(TOLERANCE) })
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_close_moons_pull_each_other() {
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let earth = &mut system.children[0];
+        let mut moon = earth.children[0].clone();
+        (moon.mass, moon.radius, moon.children) = (1e20, 30.0, Vec::new());
+        moon.orbital_params = OrbitalParameters { semi_major_axis: 100_000.0, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 0.0 };
+        // The Hill radius of each is about 1800 km, the first two are 870 km apart and the third far away
+        for (name, mean_anomaly) in [("A", 0.0), ("B", 0.5), ("C", 180.0)] {
+            let mut moon = moon.clone();
+            (moon.name, moon.orbital_params.mean_anomaly) = (name.to_string(), mean_anomaly);
+            earth.children.push(moon);
+        }
+        let mut store = SystemStore::new(system);
+        let (a, b) = (store.find("A").unwrap(), store.find("B").unwrap());
+
+        let mut found = encounters(&store);
+        let [encounter] = found.as_mut_slice() else { panic!("{:?}", found) };
+        assert_eq!((encounter.parent, encounter.members.clone()), (store.find("Earth").unwrap(), vec![a, b]));
+        encounter.step_by(86400.0);
+        let mut rails = store.clone();
+        rails.step_by(86400.0);
+        store.step_by(86400.0);
+        encounter.apply(&mut store);
+
+        let separation = |store: &SystemStore| store.position_in_frame(a, b).unwrap().length();
+        assert!(separation(&store) < separation(&rails), "{} {}", separation(&store), separation(&rails));
+        let c = store.find("C").unwrap();
+        assert_eq!(store.get(c).unwrap().orbital_params, rails.get(c).unwrap().orbital_params);
+        assert!(store.get(a).unwrap().orbital_params.semi_major_axis != 100_000.0);
+    }
+}
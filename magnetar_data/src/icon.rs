@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+#[cfg(all(feature = "fs", feature = "yaml"))]
+use std::path::Path;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::celestial::{Object, ObjectType};
+
+/// Radius in pixels below which a body is drawn as its icon instead of a disc
+pub const MIN_DISC_RADIUS: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Area of an icon in the image of an atlas, in pixels from the top left corner
+pub struct IconRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// An image with named icons in it, e.g. shipped by a mod pack.
+/// Icons named `star`, `rocky`, `jovian` and `ice-giant` are used for bodies of that type without their own icon.
+pub struct IconAtlas {
+    /// Path of the image, relative to the atlas file when loaded with [`IconAtlas::load`]
+    pub image: String,
+    pub icons: HashMap<String, IconRect>,
+}
+
+impl IconAtlas {
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_str(content: &str) -> Result<IconAtlas, String> {
+        serde_yaml::from_str(content).map_err(|e| format!("Invalid icon atlas: {}", e))
+    }
+
+    /// Loads an atlas from a YAML file and makes the path of its image relative to the working directory
+    #[cfg(all(feature = "fs", feature = "yaml"))]
+    pub fn load(path: &str) -> Result<IconAtlas, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        let mut atlas = IconAtlas::from_yaml_str(&content)?;
+        if let Some(directory) = Path::new(path).parent() {
+            atlas.image = directory.join(&atlas.image).to_string_lossy().into_owned();
+        }
+        Ok(atlas)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// The atlases in use, later ones override icons of the same name in earlier ones so mod packs can replace
+/// the base game's icons
+pub struct IconSet {
+    atlases: Vec<IconAtlas>,
+}
+
+impl IconSet {
+    pub fn new() -> IconSet {
+        IconSet::default()
+    }
+
+    pub fn push(&mut self, atlas: IconAtlas) {
+        self.atlases.push(atlas);
+    }
+
+    /// The icon with the given name and the atlas it is in
+    pub fn get(&self, name: &str) -> Option<(&IconAtlas, IconRect)> {
+        self.atlases.iter().rev().find_map(|atlas| atlas.icons.get(name).map(|rect| (atlas, *rect)))
+    }
+
+    /// The icon of an object, its own if it names one that exists and the one for its type otherwise
+    pub fn icon_for(&self, object: &Object) -> Option<(&IconAtlas, IconRect)> {
+        object.icon.as_deref()
+            .and_then(|name| self.get(name))
+            .or_else(|| self.get(type_icon(&object.object_type)))
+    }
+}
+
+/// Name of the icon used for bodies of a type without their own icon
+pub fn type_icon(object_type: &ObjectType) -> &'static str {
+    match object_type {
+        ObjectType::Star => "star",
+        ObjectType::Rocky => "rocky",
+        ObjectType::Jovian => "jovian",
+        ObjectType::IceGiant => "ice-giant",
+    }
+}
+
+/// Whether a body of `radius` km is too small to draw as a disc at a scale of `km_per_pixel`
+pub fn shows_icon(radius: f64, km_per_pixel: f64) -> bool {
+    radius / km_per_pixel < MIN_DISC_RADIUS
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_icon_overrides() {
+        let base = IconAtlas::from_yaml_str("
+image: base.png
+icons:
+  star: {x: 0, y: 0, width: 32, height: 32}
+  rocky: {x: 32, y: 0, width: 32, height: 32}
+").unwrap();
+        let mod_pack = IconAtlas::from_yaml_str("
+image: mod.png
+icons:
+  rocky: {x: 0, y: 0, width: 16, height: 16}
+  homeworld: {x: 16, y: 0, width: 16, height: 16}
+").unwrap();
+        let mut icons = IconSet::new();
+        icons.push(base);
+        icons.push(mod_pack);
+
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        system.find_mut("Earth").unwrap().icon = Some("homeworld".to_string());
+        let image_and_x = |name: &str| icons.icon_for(system.find(name).unwrap()).map(|(atlas, rect)| (atlas.image.clone(), rect.x));
+        assert_eq!(image_and_x("Sol"), Some(("base.png".to_string(), 0)));
+        assert_eq!(image_and_x("Earth"), Some(("mod.png".to_string(), 16)));
+        assert_eq!(image_and_x("Luna"), Some(("mod.png".to_string(), 0)));
+        assert!(shows_icon(1737.4, 1e6) && !shows_icon(6371.0, 100.0));
+    }
+}
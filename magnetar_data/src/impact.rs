@@ -0,0 +1,202 @@
+use std::f64::consts::PI;
+use crate::celestial::Object;
+use crate::collision::Collision;
+use crate::orbital::OrbitalParameters;
+use crate::store::{ObjectId, SystemStore};
+use crate::vector::DVec3;
+
+#[derive(Debug, Clone, PartialEq)]
+/// How a body breaks apart in [`fragment`]
+pub struct FragmentParameters {
+    /// Number of fragments, at least 1
+    pub count: usize,
+    /// Largest speed in km/s a fragment is pushed away with
+    pub speed_spread: f64,
+    /// Seed for the random directions and speeds, the same seed always gives the same fragments
+    pub seed: u64,
+}
+
+impl Default for FragmentParameters {
+    fn default() -> FragmentParameters {
+        FragmentParameters { count: 8, speed_spread: 0.05, seed: 0 }
+    }
+}
+
+/// Merges two objects orbiting the same parent into one, keeping the name, type and atmosphere of the heavier one.
+/// Mass and momentum are conserved, the volume is the sum of both. Children of both keep their orbits
+/// relative to the merged object.
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+pub fn merge(a: &Object, b: &Object, parent_mass: f64) -> Result<Object, String> {
+    let mass = a.mass + b.mass;
+    if mass <= 0.0 {
+        return Err(format!("Cannot merge {} and {} without any mass", a.name, b.name));
+    }
+    let (orbit_a, orbit_b) = (&a.orbital_params, &b.orbital_params);
+    let position = (orbit_a.position() * a.mass + orbit_b.position() * b.mass) / mass;
+    let velocity = (orbit_a.velocity(parent_mass) * a.mass + orbit_b.velocity(parent_mass) * b.mass) / mass;
+    let orbital_params = OrbitalParameters::from_state(position, velocity, parent_mass)
+        .map_err(|error| format!("Merging {} and {}: {}", a.name, b.name, error))?;
+    let mut merged = absorb(if a.mass >= b.mass { (a, b) } else { (b, a) });
+    merged.orbital_params = orbital_params;
+    Ok(merged)
+}
+
+/// `heavier` with the mass, volume and children of `lighter` added, keeping its own orbit
+fn absorb((heavier, lighter): (&Object, &Object)) -> Object {
+    let mut merged = heavier.clone();
+    merged.mass += lighter.mass;
+    merged.radius = (heavier.radius.powi(3) + lighter.radius.powi(3)).cbrt();
+    merged.children.extend(lighter.children.iter().cloned());
+    merged
+}
+
+/// Breaks `object` into [`FragmentParameters::count`] equally heavy fragments of the same density, spread
+/// around its position and pushed apart in random directions. The children of `object` go to the first fragment.
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+pub fn fragment(object: &Object, parent_mass: f64, parameters: &FragmentParameters) -> Vec<Object> {
+    let count = parameters.count.max(1);
+    let radius = object.radius / (count as f64).cbrt();
+    // Far enough apart for neighbouring fragments not to touch
+    let spread = object.radius * (1.0 + (count as f64).powf(2.0 / 3.0));
+    let (position, velocity) = (object.orbital_params.position(), object.orbital_params.velocity(parent_mass));
+    let mut random = SplitMix64(parameters.seed);
+
+    (0..count)
+        .map(|index| {
+            let angle = 2.0 * PI * index as f64 / count as f64;
+            let offset = if count == 1 { DVec3::ZERO } else { DVec3::new(angle.cos(), angle.sin(), 0.0) * spread };
+            let direction = 2.0 * PI * random.next_f64();
+            let kick = DVec3::new(direction.cos(), direction.sin(), 0.0) * parameters.speed_spread * random.next_f64();
+            // Fragments which would escape or turn retrograde stay on the unperturbed path instead
+            let orbital_params = OrbitalParameters::from_state(position + offset, velocity + kick, parent_mass)
+                .or_else(|_| OrbitalParameters::from_state(position + offset, velocity, parent_mass))
+                .unwrap_or_else(|_| object.orbital_params.clone());
+            Object {
+                name: format!("{} fragment {}", object.name, index + 1),
+                mass: object.mass / count as f64,
+                radius,
+                orbital_params,
+                atmosphere: Default::default(),
+                scale_height: None,
+                children: if index == 0 { object.children.clone() } else { Vec::new() },
+                ..object.clone()
+            }
+        })
+        .collect()
+}
+
+/// Resolves a collision by merging both objects. Siblings merge into a new orbit, a child hitting its parent
+/// is absorbed by it. Returns the new system, ids of the old one are no longer valid.
+pub fn resolve_merge(store: &SystemStore, collision: &Collision) -> Result<SystemStore, String> {
+    let (a, b) = (collision.a, collision.b);
+    let (path_a, path_b) = (store.path(a).ok_or("Unknown object")?.to_vec(), store.path(b).ok_or("Unknown object")?.to_vec());
+    let mut root = store.root().clone();
+    if store.parent(b) == Some(a) {
+        let lighter = detach(&mut root, &path_b);
+        let parent = object_at_mut(&mut root, &path_a);
+        *parent = absorb((parent, &lighter));
+    } else if store.parent(a) == store.parent(b) && store.parent(a).is_some() {
+        let parent_mass = store.parent_mass(a);
+        let merged = merge(store.get(a).unwrap(), store.get(b).unwrap(), parent_mass)?;
+        // Detach the later sibling first so the index of the earlier one stays valid
+        let (first, second) = if path_a < path_b { (&path_a, &path_b) } else { (&path_b, &path_a) };
+        detach(&mut root, second);
+        *object_at_mut(&mut root, first) = merged;
+    } else {
+        return Err("Only siblings or an object and its parent can merge".to_string());
+    }
+    Ok(SystemStore::new(root))
+}
+
+/// Replaces an object with its fragments around the same parent. Returns the new system, ids of the old one
+/// are no longer valid.
+pub fn resolve_fragment(store: &SystemStore, id: ObjectId, parameters: &FragmentParameters) -> Result<SystemStore, String> {
+    let path = store.path(id).ok_or("Unknown object")?;
+    let (&index, parent_path) = path.split_last().ok_or("The root of a system cannot fragment")?;
+    let fragments = fragment(store.get(id).unwrap(), store.parent_mass(id), parameters);
+    let mut root = store.root().clone();
+    object_at_mut(&mut root, parent_path).children.splice(index..=index, fragments);
+    Ok(SystemStore::new(root))
+}
+
+fn object_at_mut<'a>(root: &'a mut Object, path: &[usize]) -> &'a mut Object {
+    path.iter().fold(root, |object, &index| &mut object.children[index])
+}
+
+fn detach(root: &mut Object, path: &[usize]) -> Object {
+    let (&index, parent_path) = path.split_last().expect("the root cannot be detached");
+    object_at_mut(root, parent_path).children.remove(index)
+}
+
+/// Small deterministic random number generator, so fragments do not depend on an external crate
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::collision::detect_collisions;
+    use crate::yaml_parser::load_yaml_str;
+
+    fn system_with_impactor() -> SystemStore {
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let earth = &mut system.children[0];
+        let mut impactor = earth.children[0].clone();
+        (impactor.name, impactor.mass, impactor.radius) = ("Impactor".to_string(), 1e21, 500.0);
+        impactor.orbital_params.mean_anomaly += 0.1;
+        earth.children.push(impactor);
+        SystemStore::new(system)
+    }
+
+    #[test]
+    fn test_merge_conserves_mass_and_momentum() {
+        let store = system_with_impactor();
+        let collision = &detect_collisions(&store)[0];
+        let (luna, impactor) = (store.get(collision.a).unwrap(), store.get(collision.b).unwrap());
+        let parent_mass = store.parent_mass(collision.a);
+        let merged = merge(luna, impactor, parent_mass).unwrap();
+        assert_eq!(merged.name, "Luna");
+        assert_eq!(merged.mass, luna.mass + impactor.mass);
+        let momentum = luna.orbital_params.velocity(parent_mass) * luna.mass + impactor.orbital_params.velocity(parent_mass) * impactor.mass;
+        assert!((merged.orbital_params.velocity(parent_mass) * merged.mass).distance(momentum) < 1e-3 * momentum.length());
+
+        let resolved = resolve_merge(&store, collision).unwrap();
+        assert_eq!(resolved.len(), store.len() - 1);
+        assert!(resolved.find("Impactor").is_none());
+        assert!(detect_collisions(&resolved).is_empty());
+    }
+
+    #[test]
+    fn test_fragments_conserve_mass_and_do_not_touch() {
+        let store = system_with_impactor();
+        let luna = store.find("Luna").unwrap();
+        let parameters = FragmentParameters { count: 5, ..Default::default() };
+        let resolved = resolve_fragment(&store, luna, &parameters).unwrap();
+        assert_eq!(resolved.len(), store.len() + 4);
+        let earth = resolved.get(resolved.find("Earth").unwrap()).unwrap();
+        let fragments: Vec<_> = earth.children.iter().filter(|child| child.name.starts_with("Luna fragment")).collect();
+        assert_eq!(fragments.len(), 5);
+        let mass: f64 = fragments.iter().map(|fragment| fragment.mass).sum();
+        assert!((mass / store.get(luna).unwrap().mass - 1.0).abs() < 1e-12);
+        assert!(detect_collisions(&resolved).iter().all(|collision| {
+            [collision.a, collision.b].contains(&resolved.find("Impactor").unwrap())
+        }));
+        assert_eq!(fragment(store.get(luna).unwrap(), earth.mass, &parameters), fragments.into_iter().cloned().collect::<Vec<_>>());
+    }
+}
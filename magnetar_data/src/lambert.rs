@@ -0,0 +1,215 @@
+use std::f64::consts::PI;
+use crate::vector::DVec3;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Velocities at both ends of a transfer arc found by [`lambert`]
+pub struct LambertSolution {
+    /// Velocity at departure in km/s
+    pub departure_velocity: DVec3,
+    /// Velocity at arrival in km/s
+    pub arrival_velocity: DVec3,
+}
+
+/// Solves Lambert's problem: finds the prograde arc around a body with the gravitational parameter `mu` km³/s²
+/// leading from `r1` to `r2`, both in km relative to the body, in `tof` seconds with less than one revolution.
+/// Uses universal variables with bisection, see [`lambert_revolutions`] for longer arcs.
+pub fn lambert(r1: DVec3, r2: DVec3, tof: f64, mu: f64) -> Result<LambertSolution, String> {
+    let arc = Arc::new(r1, r2, tof, mu)?;
+    // The time of flight grows monotonically with psi up to a full revolution at 4π²
+    let mut low = -4.0 * PI;
+    while arc.time_of_flight(low).is_some_and(|time| time > tof) {
+        low *= 2.0;
+        if low < -1e6 {
+            return Err("No hyperbolic transfer is fast enough".to_string());
+        }
+    }
+    let psi = arc.bisect(low, 4.0 * PI.powi(2), true);
+    arc.solution(psi).ok_or(format!("No single revolution transfer takes {} s", tof))
+}
+
+/// Solves Lambert's problem for arcs making `revolutions` full revolutions before arriving at `r2`.
+/// There are two such arcs if `tof` is long enough, the one with the shorter period first, and none otherwise.
+/// Zero revolutions give the single solution of [`lambert`].
+pub fn lambert_revolutions(r1: DVec3, r2: DVec3, tof: f64, mu: f64, revolutions: u32) -> Result<Vec<LambertSolution>, String> {
+    if revolutions == 0 {
+        return lambert(r1, r2, tof, mu).map(|solution| vec![solution]);
+    }
+    let arc = Arc::new(r1, r2, tof, mu)?;
+    let (low, high) = ((2.0 * PI * revolutions as f64).powi(2), (2.0 * PI * (revolutions + 1) as f64).powi(2));
+    // The time of flight is infinite at both ends of the interval with a single minimum in between
+    let time = |psi: f64| arc.time_of_flight(psi).unwrap_or(f64::INFINITY);
+    let inverse_golden_ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut left, mut right) = (low, high);
+    for _ in 0..200 {
+        let a = right - inverse_golden_ratio * (right - left);
+        let b = left + inverse_golden_ratio * (right - left);
+        if time(a) < time(b) {
+            right = b;
+        } else {
+            left = a;
+        }
+    }
+    let minimum = (left + right) / 2.0;
+    if time(minimum) > tof {
+        return Ok(Vec::new());
+    }
+    // Larger psi means a smaller semi-major axis and a shorter period
+    let solutions = [arc.bisect(minimum, high, true), arc.bisect(low, minimum, false)]
+        .into_iter()
+        .filter_map(|psi| arc.solution(psi))
+        .collect();
+    Ok(solutions)
+}
+
+/// Geometry of a Lambert problem in universal variables
+struct Arc {
+    r1: DVec3,
+    r2: DVec3,
+    radius1: f64,
+    radius2: f64,
+    /// Constant depending only on the transfer angle
+    a: f64,
+    tof: f64,
+    mu: f64,
+}
+
+impl Arc {
+    fn new(r1: DVec3, r2: DVec3, tof: f64, mu: f64) -> Result<Arc, String> {
+        let (radius1, radius2) = (r1.length(), r2.length());
+        if radius1 == 0.0 || radius2 == 0.0 || tof <= 0.0 || mu <= 0.0 {
+            return Err("Lambert's problem needs non zero positions, a positive time of flight and a positive mu".to_string());
+        }
+        let cos_angle = (r1.dot(r2) / (radius1 * radius2)).clamp(-1.0, 1.0);
+        // Orbits are prograde, so the arc runs counter clockwise
+        let sin_angle = r1.cross(r2).z.signum() * (1.0 - cos_angle.powi(2)).sqrt();
+        if 1.0 - cos_angle < 1e-12 || (sin_angle.abs() < 1e-12 && cos_angle < 0.0) {
+            return Err("Transfers by 0° or 180° do not define a transfer plane".to_string());
+        }
+        let a = sin_angle * (radius1 * radius2 / (1.0 - cos_angle)).sqrt();
+        Ok(Arc { r1, r2, radius1, radius2, a, tof, mu })
+    }
+
+    fn y(&self, psi: f64) -> f64 {
+        self.radius1 + self.radius2 + self.a * (psi * stumpff_c3(psi) - 1.0) / stumpff_c2(psi).sqrt()
+    }
+
+    /// Time of flight in seconds along the arc with `psi`, `None` where no such arc exists
+    fn time_of_flight(&self, psi: f64) -> Option<f64> {
+        let y = self.y(psi);
+        if y < 0.0 {
+            return None;
+        }
+        let chi = (y / stumpff_c2(psi)).sqrt();
+        Some((chi.powi(3) * stumpff_c3(psi) + self.a * y.sqrt()) / self.mu.sqrt())
+    }
+
+    /// Bisects for the psi matching the time of flight between `low` and `high`, the time of flight has to be
+    /// rising over the interval if `rising` and falling otherwise
+    fn bisect(&self, mut low: f64, mut high: f64, rising: bool) -> f64 {
+        let mut psi = (low + high) / 2.0;
+        for _ in 0..200 {
+            psi = (low + high) / 2.0;
+            let too_long = self.time_of_flight(psi).is_some_and(|time| time > self.tof);
+            if too_long == rising {
+                high = psi;
+            } else {
+                low = psi;
+            }
+            if high - low < 1e-12 * high.abs().max(1.0) {
+                break;
+            }
+        }
+        psi
+    }
+
+    /// Velocities at both ends of the arc with `psi`, `None` if it does not take the wanted time of flight
+    fn solution(&self, psi: f64) -> Option<LambertSolution> {
+        let time = self.time_of_flight(psi)?;
+        if (time - self.tof).abs() > 1e-6 * self.tof {
+            return None;
+        }
+        let y = self.y(psi);
+        let f = 1.0 - y / self.radius1;
+        let g = self.a * (y / self.mu).sqrt();
+        let g_dot = 1.0 - y / self.radius2;
+        Some(LambertSolution {
+            departure_velocity: (self.r2 - self.r1 * f) / g,
+            arrival_velocity: (self.r2 * g_dot - self.r1) / g,
+        })
+    }
+}
+
+/// Stumpff function C(ψ)
+fn stumpff_c2(psi: f64) -> f64 {
+    if psi > 1e-8 {
+        (1.0 - psi.sqrt().cos()) / psi
+    } else if psi < -1e-8 {
+        ((-psi).sqrt().cosh() - 1.0) / -psi
+    } else {
+        0.5 - psi / 24.0
+    }
+}
+
+/// Stumpff function S(ψ)
+fn stumpff_c3(psi: f64) -> f64 {
+    if psi > 1e-8 {
+        let root = psi.sqrt();
+        (root - root.sin()) / root.powi(3)
+    } else if psi < -1e-8 {
+        let root = (-psi).sqrt();
+        (root.sinh() - root) / root.powi(3)
+    } else {
+        1.0 / 6.0 - psi / 120.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orbital::{OrbitalParameters, GRAVITATIONAL_CONSTANT};
+
+    const SOLAR_MASS: f64 = 1.988416e30;
+
+    #[test]
+    fn test_recovers_keplerian_orbit() {
+        let orbit = OrbitalParameters { semi_major_axis: 2e8, eccentricity: 0.3, longitude_of_periapsis: 40, mean_anomaly: 10.0 };
+        let tof = 0.4 * orbit.period(SOLAR_MASS);
+        let arrival = orbit.state_at(tof, SOLAR_MASS);
+        let solution = lambert(orbit.position(), arrival.position(), tof, GRAVITATIONAL_CONSTANT * SOLAR_MASS).unwrap();
+        assert!(solution.departure_velocity.distance(orbit.velocity(SOLAR_MASS)) < 1e-6);
+        assert!(solution.arrival_velocity.distance(arrival.velocity(SOLAR_MASS)) < 1e-6);
+    }
+
+    const EARTH_MU: f64 = 398_600.441_8;
+
+    #[test]
+    fn test_vallado_example_7_5() {
+        let r1 = DVec3::new(15_945.34, 0.0, 0.0);
+        let r2 = DVec3::new(12_214.838_99, 10_249.467_31, 0.0);
+        let solution = lambert(r1, r2, 76.0 * 60.0, EARTH_MU).unwrap();
+        assert!(solution.departure_velocity.distance(DVec3::new(2.058913, 2.915965, 0.0)) < 1e-5);
+        assert!(solution.arrival_velocity.distance(DVec3::new(-3.451565, 0.910315, 0.0)) < 1e-5);
+    }
+
+    #[test]
+    fn test_curtis_example_5_2() {
+        let r1 = DVec3::new(5000.0, 10_000.0, 2100.0);
+        let r2 = DVec3::new(-14_600.0, 2500.0, 7000.0);
+        let solution = lambert(r1, r2, 3600.0, 398_600.0).unwrap();
+        assert!(solution.departure_velocity.distance(DVec3::new(-5.9925, 1.9254, 3.2456)) < 1e-3);
+        assert!(solution.arrival_velocity.distance(DVec3::new(-3.3125, -4.1966, -0.38529)) < 1e-3);
+    }
+
+    #[test]
+    fn test_multiple_revolutions() {
+        let orbit = OrbitalParameters { semi_major_axis: 2e8, eccentricity: 0.2, longitude_of_periapsis: 0, mean_anomaly: 0.0 };
+        let tof = 1.3 * orbit.period(SOLAR_MASS);
+        let arrival = orbit.state_at(tof, SOLAR_MASS);
+        let mu = GRAVITATIONAL_CONSTANT * SOLAR_MASS;
+        let solutions = lambert_revolutions(orbit.position(), arrival.position(), tof, mu, 1).unwrap();
+        assert_eq!(solutions.len(), 2);
+        assert!(solutions.iter().any(|solution| solution.departure_velocity.distance(orbit.velocity(SOLAR_MASS)) < 1e-6));
+        assert!(lambert_revolutions(orbit.position(), arrival.position(), tof, mu, 2).unwrap().is_empty());
+        assert_eq!(lambert_revolutions(orbit.position(), arrival.position(), tof, mu, 0).unwrap().len(), 1);
+    }
+}
@@ -1,18 +1,46 @@
+pub mod aerobraking;
+pub mod appearance;
+pub mod approach;
+pub mod audio;
+pub mod camera;
 pub mod celestial;
-mod orbital;
+pub mod cluster;
+pub mod collision;
+pub mod coloring;
+pub mod debris;
+pub mod decay;
+pub mod delta_v;
+pub mod diff;
+pub mod eclipse;
+#[cfg(feature = "nbody")]
+pub mod encounter;
+pub mod icon;
+pub mod impact;
+pub mod lambert;
+#[cfg(feature = "nbody")]
+pub mod nbody;
+pub mod orbital;
+pub mod perturbation;
+pub mod picking;
+pub mod porkchop;
+pub mod prelude;
+pub mod query;
+pub mod radiation;
+pub mod roche;
+pub mod selection;
+pub mod shared;
+pub mod signal;
+pub mod snapshot;
+pub mod stability;
+pub mod stats;
+pub mod stellar;
+pub mod store;
+pub mod surface;
+#[cfg(feature = "csv")]
+pub mod telemetry;
+pub mod tidal;
+pub mod transfer;
+pub mod validation;
+pub mod vector;
+#[cfg(feature = "yaml")]
 pub mod yaml_parser;
-
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
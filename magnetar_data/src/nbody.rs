@@ -0,0 +1,319 @@
+use crate::orbital::GRAVITATIONAL_CONSTANT;
+use crate::vector::DVec3;
+
+/// Opening angle of the Barnes-Hut tree, keeps the error of the accelerations well below 1 %
+pub const DEFAULT_THETA: f64 = 0.5;
+
+/// Depth after which a quadtree node keeps all its bodies instead of splitting further, only reached by bodies
+/// at (nearly) the same position
+const MAX_DEPTH: usize = 48;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A point mass moving under the gravity of every other body in its region, unlike objects it has no parent
+pub struct Body {
+    /// Position in km
+    pub position: DVec3,
+    /// Velocity in km/s
+    pub velocity: DVec3,
+    /// Mass in kg
+    pub mass: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// How the gravity between the bodies of a region is summed up
+pub enum Gravity {
+    /// Every pair of bodies, exact but O(n²), the better choice below a few hundred bodies
+    Direct,
+    /// Barnes-Hut tree code, O(n log n). Groups of bodies are treated as one mass if they appear smaller than
+    /// `theta` radians from a body, so 0 is exact and larger values are faster but less accurate.
+    BarnesHut { theta: f64 },
+}
+
+/// Gravitational acceleration in km/s² of every body caused by all the others
+///
+/// * `softening` - Length in km added to every distance, keeps close encounters from producing huge kicks
+pub fn accelerations(bodies: &[Body], gravity: Gravity, softening: f64) -> Vec<DVec3> {
+    match gravity {
+        Gravity::Direct => {
+            let mut accelerations = vec![DVec3::ZERO; bodies.len()];
+            for (i, body) in bodies.iter().enumerate() {
+                for (j, other) in bodies.iter().enumerate().skip(i + 1) {
+                    let offset = other.position - body.position;
+                    let pull = offset / (offset.length_squared() + softening * softening).powf(1.5) * GRAVITATIONAL_CONSTANT;
+                    accelerations[i] += pull * other.mass;
+                    accelerations[j] -= pull * body.mass;
+                }
+            }
+            accelerations
+        }
+        Gravity::BarnesHut { theta } => {
+            let tree = QuadTree::build(bodies);
+            (0..bodies.len()).map(|index| tree.acceleration(bodies, index, theta, softening)).collect()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A group of bodies integrated together with one gravity method, e.g. a debris cloud or an asteroid belt.
+/// Regions do not attract each other.
+pub struct NBodyRegion {
+    pub bodies: Vec<Body>,
+    pub gravity: Gravity,
+    /// See [`accelerations`]
+    pub softening: f64,
+    /// Longest single step in seconds, longer steps are split
+    pub max_step: f64,
+    /// Largest relative change of a body's acceleration allowed within one step, steps are halved until they
+    /// stay below it and doubled again once they change far less. `None` for fixed steps of the maximum step.
+    pub tolerance: Option<f64>,
+    /// Shortest step in seconds adaptive stepping subdivides down to, the maximum step wins if it is shorter
+    pub min_step: f64,
+    /// Length of the next adaptive step in seconds, carried over so a step starts where the last one left off
+    next_step: f64,
+}
+
+impl NBodyRegion {
+    /// A region without softening, stepping at most a minute at a time with fixed steps
+    pub fn new(bodies: Vec<Body>, gravity: Gravity) -> NBodyRegion {
+        NBodyRegion { bodies, gravity, softening: 0.0, max_step: 60.0, tolerance: None, min_step: 1e-3, next_step: 60.0 }
+    }
+
+    /// Let steps adapt to how fast the accelerations change, see [`NBodyRegion::tolerance`]. Close flybys get
+    /// short steps while quiet stretches are crossed with the maximum step.
+    pub fn with_tolerance(mut self, tolerance: f64) -> NBodyRegion {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Step by a signed amount of seconds with the leapfrog (kick-drift-kick) integrator, which keeps the
+    /// energy bounded over long integrations as long as steps stay short compared to close encounters
+    pub fn step_by(&mut self, seconds: f64) {
+        let mut current = accelerations(&self.bodies, self.gravity, self.softening);
+        let Some(tolerance) = self.tolerance else {
+            let steps = (seconds.abs() / self.max_step).ceil().max(1.0);
+            for _ in 0..steps as u64 {
+                (self.bodies, current) = self.leapfrog(&current, seconds / steps);
+            }
+            return;
+        };
+
+        let mut remaining = seconds.abs();
+        while remaining > 0.0 {
+            let step = self.bounded_step().min(remaining);
+            let (bodies, next) = self.leapfrog(&current, step.copysign(seconds));
+            let change = current.iter()
+                .zip(&next)
+                .map(|(before, after)| before.distance(*after) / before.length().max(after.length()).max(f64::MIN_POSITIVE))
+                .fold(0.0, f64::max);
+            if change > tolerance && step > self.min_step {
+                self.next_step = step / 2.0;
+                continue;
+            }
+            (self.bodies, current) = (bodies, next);
+            remaining -= step;
+            if change < tolerance / 4.0 && step == self.bounded_step() {
+                self.next_step = step * 2.0;
+            }
+        }
+    }
+
+    /// The next adaptive step within the minimum and maximum step, without panicking on public fields set the
+    /// wrong way around like `clamp` would
+    fn bounded_step(&self) -> f64 {
+        self.next_step.max(self.min_step).min(self.max_step)
+    }
+
+    /// One kick-drift-kick step from the accelerations at the start, returns the bodies and accelerations after it
+    fn leapfrog(&self, current: &[DVec3], step: f64) -> (Vec<Body>, Vec<DVec3>) {
+        let mut bodies = self.bodies.clone();
+        for (body, acceleration) in bodies.iter_mut().zip(current) {
+            body.velocity += *acceleration * (step / 2.0);
+            body.position += body.velocity * step;
+        }
+        let next = accelerations(&bodies, self.gravity, self.softening);
+        for (body, acceleration) in bodies.iter_mut().zip(&next) {
+            body.velocity += *acceleration * (step / 2.0);
+        }
+        (bodies, next)
+    }
+
+    /// Total kinetic and potential energy in kg·km²/s², constant in an exact integration
+    pub fn energy(&self) -> f64 {
+        let mut energy = 0.0;
+        for (i, body) in self.bodies.iter().enumerate() {
+            energy += body.mass * body.velocity.length_squared() / 2.0;
+            for other in &self.bodies[i + 1..] {
+                let distance = ((body.position - other.position).length_squared() + self.softening * self.softening).sqrt();
+                energy -= GRAVITATIONAL_CONSTANT * body.mass * other.mass / distance;
+            }
+        }
+        energy
+    }
+}
+
+/// Node of a [`QuadTree`], covering a square of the orbital plane
+struct Node {
+    center: DVec3,
+    half_size: f64,
+    mass: f64,
+    center_of_mass: DVec3,
+    /// Index of the first of the four children, which are stored next to each other
+    children: Option<usize>,
+    /// Bodies of a leaf
+    bodies: Vec<usize>,
+}
+
+/// Barnes-Hut tree over the x/y plane, which all orbits lie in
+struct QuadTree {
+    /// Nodes with every child stored after its parent
+    nodes: Vec<Node>,
+}
+
+impl QuadTree {
+    fn build(bodies: &[Body]) -> QuadTree {
+        let bounds = |axis: fn(&DVec3) -> f64| {
+            bodies.iter().map(|body| axis(&body.position)).fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| (min.min(value), max.max(value)))
+        };
+        let ((min_x, max_x), (min_y, max_y)) = (bounds(|position| position.x), bounds(|position| position.y));
+        let half_size = ((max_x - min_x).max(max_y - min_y) / 2.0).max(f64::MIN_POSITIVE);
+        let center = DVec3::new((min_x + max_x) / 2.0, (min_y + max_y) / 2.0, 0.0);
+        let mut tree = QuadTree { nodes: vec![Node::new(center, half_size)] };
+        for index in 0..bodies.len() {
+            tree.insert(0, index, bodies, 0);
+        }
+
+        // Children come after their parents, so going backwards sums up every child before its parent
+        for node in (0..tree.nodes.len()).rev() {
+            let (mass, weighted) = match tree.nodes[node].children {
+                Some(first) => tree.nodes[first..first + 4].iter().fold((0.0, DVec3::ZERO), |(mass, weighted), child| {
+                    (mass + child.mass, weighted + child.center_of_mass * child.mass)
+                }),
+                None => tree.nodes[node].bodies.iter().fold((0.0, DVec3::ZERO), |(mass, weighted), &body| {
+                    (mass + bodies[body].mass, weighted + bodies[body].position * bodies[body].mass)
+                }),
+            };
+            let node = &mut tree.nodes[node];
+            node.mass = mass;
+            node.center_of_mass = if mass > 0.0 { weighted / mass } else { node.center };
+        }
+        tree
+    }
+
+    fn insert(&mut self, node: usize, body: usize, bodies: &[Body], depth: usize) {
+        if let Some(first) = self.nodes[node].children {
+            let quadrant = self.nodes[node].quadrant(bodies[body].position);
+            return self.insert(first + quadrant, body, bodies, depth + 1);
+        }
+        self.nodes[node].bodies.push(body);
+        if self.nodes[node].bodies.len() > 1 && depth < MAX_DEPTH {
+            let (center, half_size) = (self.nodes[node].center, self.nodes[node].half_size / 2.0);
+            let first = self.nodes.len();
+            for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+                self.nodes.push(Node::new(center + DVec3::new(x * half_size, y * half_size, 0.0), half_size));
+            }
+            self.nodes[node].children = Some(first);
+            for body in std::mem::take(&mut self.nodes[node].bodies) {
+                self.insert(node, body, bodies, depth);
+            }
+        }
+    }
+
+    fn acceleration(&self, bodies: &[Body], index: usize, theta: f64, softening: f64) -> DVec3 {
+        let position = bodies[index].position;
+        let pull = |mass: f64, at: DVec3| {
+            let offset = at - position;
+            offset / (offset.length_squared() + softening * softening).powf(1.5) * (GRAVITATIONAL_CONSTANT * mass)
+        };
+        let mut acceleration = DVec3::ZERO;
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if node.mass == 0.0 {
+                continue;
+            }
+            match node.children {
+                Some(first) if 2.0 * node.half_size >= theta * node.center_of_mass.distance(position) => {
+                    stack.extend(first..first + 4);
+                }
+                Some(_) => acceleration += pull(node.mass, node.center_of_mass),
+                None => {
+                    for &other in node.bodies.iter().filter(|&&other| other != index) {
+                        acceleration += pull(bodies[other].mass, bodies[other].position);
+                    }
+                }
+            }
+        }
+        acceleration
+    }
+}
+
+impl Node {
+    fn new(center: DVec3, half_size: f64) -> Node {
+        Node { center, half_size, mass: 0.0, center_of_mass: center, children: None, bodies: Vec::new() }
+    }
+
+    fn quadrant(&self, position: DVec3) -> usize {
+        usize::from(position.x >= self.center.x) + 2 * usize::from(position.y >= self.center.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::impact::SplitMix64;
+
+    fn cloud(count: usize) -> Vec<Body> {
+        let mut random = SplitMix64(42);
+        (0..count)
+            .map(|_| Body {
+                position: DVec3::new(random.next_f64() * 1.0e4, random.next_f64() * 1.0e4, 0.0),
+                velocity: DVec3::ZERO,
+                mass: 1.0e12 * (1.0 + random.next_f64()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_adaptive_steps_keep_energy_through_flybys() {
+        // A satellite on an orbit with e = 0.9, passing its planet at 19.5 km/s every 7.8 hours
+        let (mass, apoapsis) = (5.972e24, 38_000.0);
+        let speed = (GRAVITATIONAL_CONSTANT * mass * 0.1 / apoapsis).sqrt();
+        let bodies = vec![
+            Body { position: DVec3::ZERO, velocity: DVec3::ZERO, mass },
+            Body { position: DVec3::new(apoapsis, 0.0, 0.0), velocity: DVec3::new(0.0, speed, 0.0), mass: 1000.0 },
+        ];
+        let drift = |mut region: NBodyRegion| {
+            region.max_step = 600.0;
+            let start = region.energy();
+            for _ in 0..20 {
+                region.step_by(28_150.0);
+            }
+            ((region.energy() - start) / start).abs()
+        };
+        let adaptive = drift(NBodyRegion::new(bodies.clone(), Gravity::Direct).with_tolerance(0.001));
+        let fixed = drift(NBodyRegion::new(bodies.clone(), Gravity::Direct));
+        assert!(adaptive < 1e-4, "{}", adaptive);
+        // Fixed steps of 10 minutes jump past the planet during the flyby
+        assert!(fixed > 0.1, "{}", fixed);
+
+        // A minimum step above the maximum one steps at the maximum
+        let mut region = NBodyRegion::new(bodies, Gravity::Direct).with_tolerance(0.001);
+        (region.min_step, region.max_step) = (120.0, 60.0);
+        region.step_by(600.0);
+        assert!(region.energy().is_finite());
+    }
+
+    #[test]
+    fn test_barnes_hut_matches_direct() {
+        let bodies = cloud(500);
+        let direct = accelerations(&bodies, Gravity::Direct, 0.0);
+        // Relative to the total, the pull on bodies in the middle of the cloud nearly cancels out
+        let error = |theta: f64| {
+            let approximate = accelerations(&bodies, Gravity::BarnesHut { theta }, 0.0);
+            approximate.iter().zip(&direct).map(|(approximate, exact)| approximate.distance(*exact)).sum::<f64>()
+                / direct.iter().map(|exact| exact.length()).sum::<f64>()
+        };
+        assert!(error(0.0) < 1e-9, "{}", error(0.0));
+        assert!(error(DEFAULT_THETA) < 0.01, "{}", error(DEFAULT_THETA));
+    }
+}
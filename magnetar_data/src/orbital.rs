@@ -1,8 +1,17 @@
+use std::fmt;
 use std::time::Duration;
 use std::f64::consts::PI;
-use serde::Deserialize;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::celestial::approx_eq;
+use crate::vector::DVec3;
 
-#[derive(Debug, Deserialize)]
+/// Gravitational constant in km³ kg⁻¹ s⁻²
+pub const GRAVITATIONAL_CONSTANT: f64 = 6.67430e-20;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "kebab-case"))]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
 /// Represents an Objects Orbital Parameters
 pub struct OrbitalParameters {
     /// Semi Major Axis in km
@@ -16,14 +25,267 @@ pub struct OrbitalParameters {
 }
 
 impl OrbitalParameters {
+    /// Compares two orbits, allowing floating point values to differ by a relative `epsilon`,
+    /// the mean anomaly is compared in absolute degrees and wraps around at 360
+    pub fn approx_eq(&self, other: &OrbitalParameters, epsilon: f64) -> bool {
+        let anomaly_difference = (self.mean_anomaly - other.mean_anomaly).rem_euclid(360.0);
+        approx_eq(self.semi_major_axis, other.semi_major_axis, epsilon)
+            && approx_eq(self.eccentricity, other.eccentricity, epsilon)
+            && self.longitude_of_periapsis == other.longitude_of_periapsis
+            && anomaly_difference.min(360.0 - anomaly_difference) <= epsilon * 360.0
+    }
+
+    /// Mean motion of the orbit in degrees per second
+    ///
+    /// * `parent_mass` - Mass of the orbited object in kg
+    pub fn mean_motion(&self, parent_mass: f64) -> f64 {
+        if self.semi_major_axis == 0.0 {
+            return 0.0;
+        }
+        (GRAVITATIONAL_CONSTANT * parent_mass / self.semi_major_axis.powi(3)).sqrt().to_degrees()
+    }
+
+    /// Time for one full orbit in seconds, infinite for objects which do not orbit anything
+    ///
+    /// * `parent_mass` - Mass of the orbited object in kg
+    pub fn period(&self, parent_mass: f64) -> f64 {
+        360.0 / self.mean_motion(parent_mass)
+    }
+
     /// Calculates the Objects next position in Orbit and moves it there.
-    pub fn step_forward(&mut self, time_step: Duration) {
+    ///
+    /// * `parent_mass` - Mass of the orbited object in kg
+    pub fn step_forward(&mut self, time_step: Duration, parent_mass: f64) {
+        self.step_by(time_step.as_secs_f64(), parent_mass);
+    }
+
+    /// Moves the Object along its Orbit by a signed amount of seconds, negative values step backwards.
+    ///
+    /// * `parent_mass` - Mass of the orbited object in kg
+    pub fn step_by(&mut self, seconds: f64, parent_mass: f64) {
+        self.mean_anomaly = self.mean_anomaly_at(seconds, parent_mass);
+    }
+
+    /// Analytically calculates the Orbital Parameters `seconds` from now without modifying `self`.
+    ///
+    /// As the state is derived directly from the current one, repeated calls with different offsets
+    /// do not accumulate any error, regardless of direction.
+    ///
+    /// * `parent_mass` - Mass of the orbited object in kg
+    pub fn state_at(&self, seconds: f64, parent_mass: f64) -> OrbitalParameters {
+        OrbitalParameters {
+            mean_anomaly: self.mean_anomaly_at(seconds, parent_mass),
+            ..self.clone()
+        }
+    }
+
+    /// Solves Kepler's equation for the eccentric anomaly in degrees using Newton's method
+    pub fn eccentric_anomaly(&self) -> f64 {
+        let mean_anomaly = self.mean_anomaly.to_radians();
+        let mut eccentric_anomaly = if self.eccentricity > 0.8 { PI } else { mean_anomaly };
+        for _ in 0..50 {
+            let delta = (eccentric_anomaly - self.eccentricity * eccentric_anomaly.sin() - mean_anomaly)
+                / (1.0 - self.eccentricity * eccentric_anomaly.cos());
+            eccentric_anomaly -= delta;
+            if delta.abs() < 1e-12 {
+                break;
+            }
+        }
+        eccentric_anomaly.to_degrees().rem_euclid(360.0)
+    }
+
+    /// Angle between the periapsis and the current position of the object in degrees
+    pub fn true_anomaly(&self) -> f64 {
+        let half_eccentric_anomaly = self.eccentric_anomaly().to_radians() / 2.0;
+        let true_anomaly = 2.0 * ((1.0 + self.eccentricity).sqrt() * half_eccentric_anomaly.sin())
+            .atan2((1.0 - self.eccentricity).sqrt() * half_eccentric_anomaly.cos());
+        true_anomaly.to_degrees().rem_euclid(360.0)
+    }
+
+    /// Current distance to the parent in km
+    pub fn radius(&self) -> f64 {
+        self.semi_major_axis * (1.0 - self.eccentricity * self.eccentric_anomaly().to_radians().cos())
+    }
+
+    /// Closest distance to the parent in km
+    pub fn periapsis(&self) -> f64 {
+        self.semi_major_axis * (1.0 - self.eccentricity)
+    }
+
+    /// Farthest distance from the parent in km
+    pub fn apoapsis(&self) -> f64 {
+        self.semi_major_axis * (1.0 + self.eccentricity)
+    }
+
+    /// Position of the periapsis relative to the parent in km, in the same frame as `position`
+    pub fn periapsis_position(&self) -> DVec3 {
+        let (sin, cos) = (self.longitude_of_periapsis as f64).to_radians().sin_cos();
+        DVec3::new(cos, sin, 0.0) * self.periapsis()
+    }
+
+    /// Position of the apoapsis relative to the parent in km, in the same frame as `position`
+    pub fn apoapsis_position(&self) -> DVec3 {
+        let (sin, cos) = (self.longitude_of_periapsis as f64).to_radians().sin_cos();
+        DVec3::new(cos, sin, 0.0) * -self.apoapsis()
+    }
+
+    /// Angle between longitude 0 and the current position of the object in degrees
+    pub fn true_longitude(&self) -> f64 {
+        (self.longitude_of_periapsis as f64 + self.true_anomaly()).rem_euclid(360.0)
+    }
+
+    /// Current position relative to the parent in km, in the parents orbital plane with the x axis
+    /// pointing towards longitude 0
+    pub fn position(&self) -> DVec3 {
+        let longitude = self.true_longitude().to_radians();
+        let radius = self.radius();
+        DVec3::new(radius * longitude.cos(), radius * longitude.sin(), 0.0)
+    }
+
+    /// Current velocity relative to the parent in km/s, in the same frame as `position`
+    ///
+    /// * `parent_mass` - Mass of the orbited object in kg
+    pub fn velocity(&self, parent_mass: f64) -> DVec3 {
         if self.semi_major_axis == 0.0 {
-            return;
+            return DVec3::ZERO;
         }
-        let time_seconds = time_step.as_secs_f64();
-        let mean_motion = (360.0) / (self.semi_major_axis.powf(1.5)); // Replace 2π with 360
-        self.mean_anomaly += mean_motion * time_seconds;
-        self.mean_anomaly = self.mean_anomaly % 360.0; // Keep within 0 to 360 degrees
-    } 
+        let semi_latus_rectum = self.semi_major_axis * (1.0 - self.eccentricity.powi(2));
+        let speed = (GRAVITATIONAL_CONSTANT * parent_mass / semi_latus_rectum).sqrt();
+        let true_anomaly = self.true_anomaly().to_radians();
+        // Velocity in the perifocal frame, x pointing towards the periapsis
+        let (x, y) = (-speed * true_anomaly.sin(), speed * (self.eccentricity + true_anomaly.cos()));
+        let (sin, cos) = (self.longitude_of_periapsis as f64).to_radians().sin_cos();
+        DVec3::new(x * cos - y * sin, x * sin + y * cos, 0.0)
+    }
+
+    /// Points along the whole orbit relative to the parent in km, evenly spaced in eccentric anomaly
+    /// starting at the periapsis, e.g. for drawing orbit lines or hit testing them
+    pub fn orbit_points(&self, count: usize) -> Vec<DVec3> {
+        let semi_minor_axis = self.semi_major_axis * (1.0 - self.eccentricity.powi(2)).sqrt();
+        let (sin, cos) = (self.longitude_of_periapsis as f64).to_radians().sin_cos();
+        (0..count)
+            .map(|index| {
+                let eccentric_anomaly = 2.0 * PI * index as f64 / count as f64;
+                // Relative to the focus, x pointing towards the periapsis
+                let x = self.semi_major_axis * (eccentric_anomaly.cos() - self.eccentricity);
+                let y = semi_minor_axis * eccentric_anomaly.sin();
+                DVec3::new(x * cos - y * sin, x * sin + y * cos, 0.0)
+            })
+            .collect()
+    }
+
+    /// Elements of the orbit through `position` with `velocity` relative to the parent, the inverse of
+    /// [`OrbitalParameters::position`] and [`OrbitalParameters::velocity`]. The position is kept exactly,
+    /// the velocity only up to the longitude of periapsis being rounded to whole degrees.
+    /// Fails for unbound and retrograde orbits, which cannot be represented.
+    ///
+    /// * `parent_mass` - Mass of the orbited object in kg
+    pub fn from_state(position: DVec3, velocity: DVec3, parent_mass: f64) -> Result<OrbitalParameters, String> {
+        let mu = GRAVITATIONAL_CONSTANT * parent_mass;
+        let radius = position.length();
+        if radius == 0.0 || mu <= 0.0 {
+            return Err("Cannot derive an orbit without a distance to a parent with mass".to_string());
+        }
+        let energy = velocity.length_squared() / 2.0 - mu / radius;
+        if energy >= 0.0 {
+            return Err(format!("Orbit is unbound with a specific energy of {} km²/s²", energy));
+        }
+        if position.cross(velocity).z < 0.0 {
+            return Err("Orbit is retrograde".to_string());
+        }
+        let semi_major_axis = -mu / (2.0 * energy);
+        let eccentricity_vector = (position * (velocity.length_squared() - mu / radius) - velocity * position.dot(velocity)) / mu;
+        let eccentricity = eccentricity_vector.length();
+        let longitude_of_periapsis = if eccentricity < 1e-12 {
+            0
+        } else {
+            eccentricity_vector.y.atan2(eccentricity_vector.x).to_degrees().rem_euclid(360.0).round() as u16 % 360
+        };
+        let true_anomaly = position.y.atan2(position.x) - (longitude_of_periapsis as f64).to_radians();
+        let eccentric_anomaly = ((1.0 - eccentricity.powi(2)).sqrt() * true_anomaly.sin())
+            .atan2(eccentricity + true_anomaly.cos());
+        let mean_anomaly = (eccentric_anomaly - eccentricity * eccentric_anomaly.sin()).to_degrees().rem_euclid(360.0);
+        Ok(OrbitalParameters { semi_major_axis, eccentricity, longitude_of_periapsis, mean_anomaly })
+    }
+
+    fn mean_anomaly_at(&self, seconds: f64, parent_mass: f64) -> f64 {
+        if self.semi_major_axis == 0.0 {
+            return self.mean_anomaly;
+        }
+        // Keep within 0 to 360 degrees, rem_euclid also handles negative time steps
+        (self.mean_anomaly + self.mean_motion(parent_mass) * seconds).rem_euclid(360.0)
+    }
+}
+
+impl fmt::Display for OrbitalParameters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a = {} km, e = {}, ϖ = {}°, M = {:.3}°",
+               self.semi_major_axis, self.eccentricity, self.longitude_of_periapsis, self.mean_anomaly)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> OrbitalParameters {
+        OrbitalParameters {
+            semi_major_axis: 149597870.0,
+            eccentricity: 0.0167086,
+            longitude_of_periapsis: 102,
+            mean_anomaly: 358.617,
+        }
+    }
+
+    const SOLAR_MASS: f64 = 1.988416e30;
+
+    #[test]
+    fn test_step_backwards() {
+        let mut orbit = params();
+        orbit.step_by(-1.0e7, SOLAR_MASS);
+        assert!((0.0..360.0).contains(&orbit.mean_anomaly));
+        orbit.step_by(1.0e7, SOLAR_MASS);
+        assert!((orbit.mean_anomaly - 358.617).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_state_at_matches_stepping() {
+        let mut orbit = params();
+        let state = orbit.state_at(3.6e7, SOLAR_MASS);
+        orbit.step_forward(Duration::from_secs(36_000_000), SOLAR_MASS);
+        assert!((state.mean_anomaly - orbit.mean_anomaly).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_earth_period() {
+        let days = params().period(SOLAR_MASS) / 86400.0;
+        assert!((days - 365.25).abs() < 0.1, "{}", days);
+    }
+
+    #[test]
+    fn test_position_at_periapsis_and_apoapsis() {
+        let mut orbit = OrbitalParameters {
+            semi_major_axis: 1000.0,
+            eccentricity: 0.5,
+            longitude_of_periapsis: 90,
+            mean_anomaly: 0.0,
+        };
+        let position = orbit.position();
+        assert!(position.distance(DVec3::new(0.0, 500.0, 0.0)) < 1e-9);
+        assert!(position.distance(orbit.periapsis_position()) < 1e-9);
+        orbit.mean_anomaly = 180.0;
+        assert!((orbit.true_anomaly() - 180.0).abs() < 1e-9);
+        assert!((orbit.radius() - orbit.apoapsis()).abs() < 1e-9);
+        assert!(orbit.position().distance(orbit.apoapsis_position()) < 1e-9);
+    }
+
+    #[test]
+    fn test_from_state_round_trip() {
+        let orbit = OrbitalParameters { longitude_of_periapsis: 102, mean_anomaly: 120.0, eccentricity: 0.3, ..params() };
+        let derived = OrbitalParameters::from_state(orbit.position(), orbit.velocity(SOLAR_MASS), SOLAR_MASS).unwrap();
+        assert!(derived.approx_eq(&orbit, 1e-9), "{}", derived);
+        let escape = orbit.velocity(SOLAR_MASS) * 2.0;
+        assert!(OrbitalParameters::from_state(orbit.position(), escape, SOLAR_MASS).is_err());
+        assert!(OrbitalParameters::from_state(orbit.position(), -orbit.velocity(SOLAR_MASS), SOLAR_MASS).is_err());
+    }
 }
@@ -0,0 +1,115 @@
+use crate::celestial::Object;
+use crate::impact::SplitMix64;
+use crate::store::{ObjectId, SystemStore};
+use crate::vector::DVec3;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Largest random change applied to each orbital element by [`perturb`], noise is uniform within the bounds
+pub struct PerturbationBounds {
+    /// Relative change of the semi-major axis, e.g. `0.001` for ±0.1 %
+    pub semi_major_axis: f64,
+    /// Absolute change of the eccentricity, results are kept between 0 and 0.99
+    pub eccentricity: f64,
+    /// Change of the longitude of periapsis in whole degrees
+    pub longitude_of_periapsis: u16,
+    /// Change of the mean anomaly in degrees
+    pub mean_anomaly: f64,
+    /// Relative change of the mass
+    pub mass: f64,
+}
+
+impl Default for PerturbationBounds {
+    fn default() -> PerturbationBounds {
+        PerturbationBounds { semi_major_axis: 0.001, eccentricity: 0.001, longitude_of_periapsis: 0, mean_anomaly: 0.1, mass: 0.0 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Spread of the position of one body over a set of perturbed copies of a system
+pub struct Envelope {
+    pub id: ObjectId,
+    /// Sample times in seconds from the epoch
+    pub times: Vec<f64>,
+    /// Unperturbed position relative to the root at each sample time
+    pub nominal: Vec<DVec3>,
+    /// Largest distance in km of a perturbed position from the nominal one at each sample time
+    pub radius: Vec<f64>,
+}
+
+/// Copy of `system` with random noise within `bounds` applied to every orbit, the root keeps its elements.
+/// The same seed always gives the same copy.
+pub fn perturb(system: &Object, bounds: &PerturbationBounds, seed: u64) -> Object {
+    let mut copy = system.clone();
+    let mut random = SplitMix64(seed);
+    for child in &mut copy.children {
+        perturb_tree(child, bounds, &mut random);
+    }
+    copy
+}
+
+fn perturb_tree(object: &mut Object, bounds: &PerturbationBounds, random: &mut SplitMix64) {
+    let mut noise = |bound: f64| (2.0 * random.next_f64() - 1.0) * bound;
+    let orbit = &mut object.orbital_params;
+    orbit.semi_major_axis *= 1.0 + noise(bounds.semi_major_axis);
+    orbit.eccentricity = (orbit.eccentricity + noise(bounds.eccentricity)).clamp(0.0, 0.99);
+    let longitude_shift = noise(bounds.longitude_of_periapsis as f64).round() as i32;
+    orbit.longitude_of_periapsis = (orbit.longitude_of_periapsis as i32 + longitude_shift).rem_euclid(360) as u16;
+    orbit.mean_anomaly = (orbit.mean_anomaly + noise(bounds.mean_anomaly)).rem_euclid(360.0);
+    object.mass *= 1.0 + noise(bounds.mass);
+    for child in &mut object.children {
+        perturb_tree(child, bounds, random);
+    }
+}
+
+/// Position envelopes of every body over `runs` perturbed copies of `system`, seeded with `seed`, `seed + 1`, ...
+///
+/// * `times` - Sample times in seconds from the epoch
+pub fn envelopes(system: &Object, bounds: &PerturbationBounds, runs: usize, seed: u64, times: &[f64]) -> Vec<Envelope> {
+    let _span = tracing::debug_span!("envelopes", runs, samples = times.len()).entered();
+    let positions = |system: &Object| -> Vec<Vec<DVec3>> {
+        times.iter()
+            .map(|time| {
+                let store = SystemStore::new(system.state_at(*time));
+                store.ids().map(|id| store.absolute_position(id).unwrap_or_default()).collect()
+            })
+            .collect()
+    };
+    let nominal = positions(system);
+    let mut radius = vec![vec![0.0; nominal.first().map_or(0, Vec::len)]; times.len()];
+    for run in 0..runs as u64 {
+        for (sample, perturbed) in positions(&perturb(system, bounds, seed.wrapping_add(run))).into_iter().enumerate() {
+            for (id, position) in perturbed.into_iter().enumerate() {
+                radius[sample][id] = f64::max(radius[sample][id], position.distance(nominal[sample][id]));
+            }
+        }
+    }
+    let bodies = radius.first().map_or(0, Vec::len);
+    (0..bodies)
+        .map(|id| Envelope {
+            id: ObjectId(id as u32),
+            times: times.to_vec(),
+            nominal: nominal.iter().map(|sample| sample[id]).collect(),
+            radius: radius.iter().map(|sample| sample[id]).collect(),
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_spread_grows_over_time() {
+        let system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        assert_eq!(perturb(&system, &PerturbationBounds::default(), 7), perturb(&system, &PerturbationBounds::default(), 7));
+        assert_ne!(perturb(&system, &PerturbationBounds::default(), 7), perturb(&system, &PerturbationBounds::default(), 8));
+
+        let times = [0.0, 365.25 * 86400.0, 10.0 * 365.25 * 86400.0];
+        let envelopes = envelopes(&system, &PerturbationBounds::default(), 16, 0, &times);
+        assert_eq!(envelopes[0].radius, vec![0.0; 3]);
+        let earth = &envelopes[1].radius;
+        // A 0.1 % larger orbit falls behind by about 0.15 % of the orbit every year
+        assert!(earth[0] > 0.0 && earth[1] > earth[0] && earth[2] > 5.0 * earth[1], "{:?}", earth);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::store::{ObjectId, SystemStore};
+use crate::vector::DVec3;
+
+/// Number of segments orbits are approximated with when hit testing them
+const ORBIT_SEGMENTS: usize = 1024;
+
+/// The object closest to `point` within `tolerance` km, positions are relative to the root.
+/// The radius of each object is added to the tolerance, so large bodies can be hit anywhere on their surface.
+pub fn pick_body(store: &SystemStore, point: DVec3, tolerance: f64) -> Option<ObjectId> {
+    store.ids()
+        .filter_map(|id| {
+            let distance = store.absolute_position(id)?.distance(point) - store.get(id)?.radius;
+            (distance <= tolerance).then_some((id, distance))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(id, _)| id)
+}
+
+/// The object whose orbit line passes closest to `point` within `tolerance` km, positions are relative to the root
+pub fn pick_orbit(store: &SystemStore, point: DVec3, tolerance: f64) -> Option<ObjectId> {
+    store.ids()
+        .filter_map(|id| {
+            let parent = store.absolute_position(store.parent(id)?)?;
+            let points = store.get(id)?.orbital_params.orbit_points(ORBIT_SEGMENTS);
+            let distance = points.iter()
+                .zip(points.iter().cycle().skip(1))
+                .map(|(start, end)| distance_to_segment(point, parent + *start, parent + *end))
+                .fold(f64::INFINITY, f64::min);
+            (distance <= tolerance).then_some((id, distance))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(id, _)| id)
+}
+
+/// Short single line description of an object for tooltips: name, type, distance from and period around its parent
+pub fn tooltip(store: &SystemStore, id: ObjectId) -> Option<String> {
+    let object = store.get(id)?;
+    let Some(parent) = store.parent(id).and_then(|parent| store.get(parent)) else {
+        return Some(format!("{} ({})", object.name, object.object_type));
+    };
+    let orbit = &object.orbital_params;
+    Some(format!("{} ({}), {:.0} km from {}, period {:.2} d",
+                 object.name, object.object_type, orbit.radius(), parent.name, orbit.period(parent.mass) / 86400.0))
+}
+
+fn distance_to_segment(point: DVec3, start: DVec3, end: DVec3) -> f64 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    let t = if length_squared == 0.0 { 0.0 } else { ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0) };
+    point.distance(start + segment * t)
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_pick() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let (earth, luna) = (store.find("Earth").unwrap(), store.find("Luna").unwrap());
+        let earth_position = store.absolute_position(earth).unwrap();
+        assert_eq!(pick_body(&store, earth_position + DVec3::new(1000.0, 0.0, 0.0), 100.0), Some(earth));
+        assert_eq!(pick_body(&store, DVec3::new(1.0e8, 0.0, 0.0), 1.0e4), None);
+
+        // Opposite of Earth on its orbit, far away from any body
+        let orbit = &store.get(earth).unwrap().orbital_params;
+        let opposite = orbit.state_at(orbit.period(store.parent_mass(earth)) / 2.0, store.parent_mass(earth)).position();
+        assert_eq!(pick_orbit(&store, opposite, 1.0e4), Some(earth));
+        assert_eq!(pick_orbit(&store, earth_position + store.get(luna).unwrap().orbital_params.position(), 100.0), Some(luna));
+
+        assert!(tooltip(&store, earth).unwrap().starts_with("Earth (Rocky), "));
+    }
+}
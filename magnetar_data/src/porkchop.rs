@@ -0,0 +1,101 @@
+use std::ops::Range;
+use crate::lambert::lambert;
+use crate::orbital::{OrbitalParameters, GRAVITATIONAL_CONSTANT};
+
+#[derive(Debug, Clone, PartialEq)]
+/// Delta-v of direct transfers between two objects around the same parent over a grid of
+/// departure and arrival times, the data behind a porkchop plot
+pub struct Porkchop {
+    /// Departure times in seconds from now, the columns of the plot
+    pub departures: Vec<f64>,
+    /// Arrival times in seconds from now, the rows of the plot
+    pub arrivals: Vec<f64>,
+    /// Departure major, infinite where the arrival is not after the departure or no transfer was found
+    delta_v: Vec<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A single transfer of a [`Porkchop`]
+pub struct PorkchopPoint {
+    /// Departure time in seconds from now
+    pub departure: f64,
+    /// Arrival time in seconds from now
+    pub arrival: f64,
+    /// Sum of the hyperbolic excess speeds at departure and arrival in km/s
+    pub delta_v: f64,
+}
+
+impl Porkchop {
+    /// Delta-v in km/s leaving at `departures[departure]` and arriving at `arrivals[arrival]`
+    pub fn get(&self, departure: usize, arrival: usize) -> f64 {
+        self.delta_v[departure * self.arrivals.len() + arrival]
+    }
+
+    /// Cheapest transfer on the grid
+    pub fn best(&self) -> Option<PorkchopPoint> {
+        let (index, delta_v) = self.delta_v.iter().enumerate()
+            .filter(|(_, delta_v)| delta_v.is_finite())
+            .min_by(|a, b| a.1.total_cmp(b.1))?;
+        Some(PorkchopPoint {
+            departure: self.departures[index / self.arrivals.len()],
+            arrival: self.arrivals[index % self.arrivals.len()],
+            delta_v: *delta_v,
+        })
+    }
+}
+
+/// Sweeps `columns` departure times over `departures` and `rows` arrival times over `arrivals`, both in seconds
+/// from now, and solves Lambert's problem for every pair. The delta-v of a transfer is the difference between
+/// the velocity of the transfer arc and the velocity of the objects at both ends, without escape or capture burns.
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+pub fn porkchop(origin: &OrbitalParameters, target: &OrbitalParameters, parent_mass: f64,
+                departures: Range<f64>, arrivals: Range<f64>, columns: usize, rows: usize) -> Porkchop {
+    let _span = tracing::debug_span!("porkchop", columns, rows).entered();
+    let mu = GRAVITATIONAL_CONSTANT * parent_mass;
+    let sweep = |range: &Range<f64>, count: usize| -> Vec<f64> {
+        (0..count).map(|index| range.start + (range.end - range.start) * index as f64 / (count.max(2) - 1) as f64).collect()
+    };
+    let (departure_times, arrival_times) = (sweep(&departures, columns), sweep(&arrivals, rows));
+
+    let mut delta_v = Vec::with_capacity(columns * rows);
+    for &departure in &departure_times {
+        let origin = origin.state_at(departure, parent_mass);
+        for &arrival in &arrival_times {
+            let target = target.state_at(arrival, parent_mass);
+            let transfer = lambert(origin.position(), target.position(), arrival - departure, mu);
+            delta_v.push(transfer.map_or(f64::INFINITY, |transfer| {
+                transfer.departure_velocity.distance(origin.velocity(parent_mass))
+                    + transfer.arrival_velocity.distance(target.velocity(parent_mass))
+            }));
+        }
+    }
+    Porkchop { departures: departure_times, arrivals: arrival_times, delta_v }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transfer::{hohmann, transfer_windows};
+
+    const SOLAR_MASS: f64 = 1.988416e30;
+    const DAY: f64 = 86400.0;
+
+    #[test]
+    fn test_best_transfer_is_close_to_hohmann() {
+        let earth = OrbitalParameters { semi_major_axis: 149597870.0, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 0.0 };
+        let mars = OrbitalParameters { semi_major_axis: 227939200.0, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 90.0 };
+        let window = transfer_windows(&earth, &mars, SOLAR_MASS, 0.0, 800.0 * DAY, DAY)[0];
+        let departures = window - 30.0 * DAY..window + 30.0 * DAY;
+        let arrivals = window + 150.0 * DAY..window + 350.0 * DAY;
+        let plot = porkchop(&earth, &mars, SOLAR_MASS, departures, arrivals, 61, 101);
+        assert!(plot.get(0, 0).is_finite());
+
+        let best = plot.best().unwrap();
+        let hohmann = hohmann(&earth, &mars, SOLAR_MASS);
+        // Exactly opposite positions are degenerate, so the best transfer is slightly shorter than 180°
+        assert!((best.delta_v - hohmann.total_delta_v()).abs() < 0.2, "{}", best.delta_v);
+        assert!((best.departure - window).abs() < 5.0 * DAY);
+        assert!(((best.arrival - best.departure) - hohmann.transfer_time).abs() < 20.0 * DAY);
+    }
+}
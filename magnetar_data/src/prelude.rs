@@ -0,0 +1,17 @@
+//! Commonly used types and functions, `use magnetar_data::prelude::*;` to import all of them
+
+pub use crate::celestial::{Object, ObjectType, TreeItem, TreeIter};
+pub use crate::orbital::OrbitalParameters;
+pub use crate::query::Query;
+pub use crate::selection::{GroupInfo, Selection};
+pub use crate::shared::SharedSystem;
+pub use crate::signal::light_delay_between;
+pub use crate::snapshot::{Snapshot, SnapshotBuffer};
+pub use crate::stats::SystemStats;
+pub use crate::store::{ObjectId, ObjectRef, SystemStore};
+pub use crate::validation::{validate, Issue, Severity};
+pub use crate::vector::DVec3;
+#[cfg(feature = "fs")]
+pub use crate::yaml_parser::{check_yaml, load_yaml};
+#[cfg(feature = "yaml")]
+pub use crate::yaml_parser::{check_yaml_str, load_yaml_reader, load_yaml_str, to_yaml};
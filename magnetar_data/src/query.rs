@@ -0,0 +1,341 @@
+use std::fmt;
+use crate::celestial::ObjectType;
+use crate::stellar::ASTRONOMICAL_UNIT;
+use crate::store::{ObjectId, SystemStore};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Comparison of a property with a value
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Numeric property of an object that can be compared in a [`Query`]
+pub enum Property {
+    /// Mass in kg
+    Mass,
+    /// Radius in km
+    Radius,
+    /// Semi-major axis in km
+    SemiMajorAxis,
+    Eccentricity,
+    /// Periapsis distance in km
+    Periapsis,
+    /// Apoapsis distance in km
+    Apoapsis,
+    /// Distance from the root in the tree, 0 for the root
+    Depth,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A parsed filter expression selecting objects of a system, e.g. `type=ROCKY and parent=Sol and sma<2AU`.
+///
+/// Comparisons are `<property> <operator> <value>` with the operators `=`, `!=`, `<`, `<=`, `>` and `>=`:
+///
+/// * `name`, `parent` and `ancestor` compare names, `"..."` quotes names containing spaces
+/// * `type` is one of `STAR`, `ROCKY`, `JOVIAN` or `ICE_GIANT`, case-insensitive
+/// * `atmosphere` matches objects with the given gas, `atmosphere=*` any atmosphere at all
+/// * `mass`, `radius`, `sma`, `eccentricity`, `periapsis`, `apoapsis` and `depth` are numbers,
+///   distances may carry the unit `km` or `AU`
+///
+/// Comparisons are combined with `and`, `or`, `not` and parentheses, `and` binds stronger than `or`.
+pub enum Query {
+    Name(Comparison, String),
+    Parent(Comparison, String),
+    /// Any object above in the tree has the name
+    Ancestor(Comparison, String),
+    Type(Comparison, ObjectType),
+    /// Has the gas in its atmosphere, `None` for any gas
+    Atmosphere(Comparison, Option<String>),
+    Number(Property, Comparison, f64),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+}
+
+impl Comparison {
+    fn holds<T: PartialOrd>(self, a: T, b: T) -> bool {
+        match self {
+            Comparison::Equal => a == b,
+            Comparison::NotEqual => a != b,
+            Comparison::Less => a < b,
+            Comparison::LessOrEqual => a <= b,
+            Comparison::Greater => a > b,
+            Comparison::GreaterOrEqual => a >= b,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+        })
+    }
+}
+
+impl Query {
+    /// Parses a filter expression, see [`Query`] for the syntax
+    pub fn parse(input: &str) -> Result<Query, String> {
+        let mut parser = Parser { tokens: tokenize(input)?, position: 0 };
+        let query = parser.or()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(query),
+            Some(token) => Err(format!("Unexpected '{}' in query", token)),
+        }
+    }
+
+    pub fn matches(&self, store: &SystemStore, id: ObjectId) -> bool {
+        let Some(object) = store.get(id) else {
+            return false;
+        };
+        let name_of = |id: ObjectId| store.get(id).map_or("", |object| object.name.as_str());
+        let equality = |comparison: &Comparison, found: bool| match comparison {
+            Comparison::NotEqual => !found,
+            _ => found,
+        };
+        match self {
+            Query::Name(comparison, name) => comparison.holds(object.name.as_str(), name.as_str()),
+            Query::Parent(comparison, name) => {
+                equality(comparison, store.parent(id).is_some_and(|parent| name_of(parent) == name))
+            }
+            Query::Ancestor(comparison, name) => {
+                let ancestors = std::iter::successors(store.parent(id), |ancestor| store.parent(*ancestor));
+                equality(comparison, ancestors.map(name_of).any(|ancestor| ancestor == name))
+            }
+            Query::Type(comparison, object_type) => equality(comparison, object.object_type == *object_type),
+            Query::Atmosphere(comparison, gas) => {
+                let found = match gas {
+                    Some(gas) => object.atmosphere.contains_key(gas),
+                    None => !object.atmosphere.is_empty(),
+                };
+                equality(comparison, found)
+            }
+            Query::Number(property, comparison, value) => {
+                let orbit = &object.orbital_params;
+                let actual = match property {
+                    Property::Mass => object.mass,
+                    Property::Radius => object.radius,
+                    Property::SemiMajorAxis => orbit.semi_major_axis,
+                    Property::Eccentricity => orbit.eccentricity,
+                    Property::Periapsis => orbit.periapsis(),
+                    Property::Apoapsis => orbit.apoapsis(),
+                    Property::Depth => std::iter::successors(store.parent(id), |parent| store.parent(*parent)).count() as f64,
+                };
+                comparison.holds(actual, *value)
+            }
+            Query::And(a, b) => a.matches(store, id) && b.matches(store, id),
+            Query::Or(a, b) => a.matches(store, id) || b.matches(store, id),
+            Query::Not(query) => !query.matches(store, id),
+        }
+    }
+
+    /// Ids of all matching objects in id order
+    pub fn select(&self, store: &SystemStore) -> Vec<ObjectId> {
+        store.ids().filter(|id| self.matches(store, *id)).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    /// Quoted text, never a keyword
+    Quoted(String),
+    Comparison(Comparison),
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => f.write_str(word),
+            Token::Quoted(text) => write!(f, "\"{}\"", text),
+            Token::Comparison(comparison) => write!(f, "{}", comparison),
+            Token::Open => f.write_str("("),
+            Token::Close => f.write_str(")"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '"' => {
+                chars.next();
+                let text: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                tokens.push(Token::Quoted(text));
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let or_equal = chars.next_if_eq(&'=').is_some();
+                tokens.push(Token::Comparison(match (c, or_equal) {
+                    ('=', _) => Comparison::Equal,
+                    ('!', true) => Comparison::NotEqual,
+                    ('<', false) => Comparison::Less,
+                    ('<', true) => Comparison::LessOrEqual,
+                    ('>', false) => Comparison::Greater,
+                    ('>', true) => Comparison::GreaterOrEqual,
+                    _ => return Err("Expected '=' after '!' in query".to_string()),
+                }));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"()\"=!<>".contains(*c)) {
+                    word.push(c);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the tokens of a query
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn next_is_keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.tokens.get(self.position), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Query, String> {
+        let mut query = self.and()?;
+        while self.next_is_keyword("or") {
+            query = Query::Or(Box::new(query), Box::new(self.and()?));
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<Query, String> {
+        let mut query = self.not()?;
+        while self.next_is_keyword("and") {
+            query = Query::And(Box::new(query), Box::new(self.not()?));
+        }
+        Ok(query)
+    }
+
+    fn not(&mut self) -> Result<Query, String> {
+        if self.next_is_keyword("not") {
+            return Ok(Query::Not(Box::new(self.not()?)));
+        }
+        if self.tokens.get(self.position) == Some(&Token::Open) {
+            self.position += 1;
+            let query = self.or()?;
+            return match self.next() {
+                Some(Token::Close) => Ok(query),
+                _ => Err("Missing ')' in query".to_string()),
+            };
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Query, String> {
+        let property = match self.next() {
+            Some(Token::Word(word)) => word.to_ascii_lowercase(),
+            Some(token) => return Err(format!("Expected a property instead of '{}' in query", token)),
+            None => return Err("Query ended early, expected a property".to_string()),
+        };
+        let comparison = match self.next() {
+            Some(Token::Comparison(comparison)) => comparison,
+            _ => return Err(format!("Expected a comparison after '{}' in query", property)),
+        };
+        let value = match self.next() {
+            Some(Token::Word(value) | Token::Quoted(value)) => value,
+            _ => return Err(format!("Expected a value after '{}{}' in query", property, comparison)),
+        };
+        let equality = || match comparison {
+            Comparison::Equal | Comparison::NotEqual => Ok(comparison),
+            _ => Err(format!("'{}' can only be compared with = or !=", property)),
+        };
+        let number = |property: Property| parse_number(&value).map(|value| Query::Number(property, comparison, value));
+        match property.as_str() {
+            "name" => Ok(Query::Name(comparison, value)),
+            "parent" => Ok(Query::Parent(equality()?, value)),
+            "ancestor" => Ok(Query::Ancestor(equality()?, value)),
+            "type" => Ok(Query::Type(equality()?, parse_object_type(&value)?)),
+            "atmosphere" => Ok(Query::Atmosphere(equality()?, (value != "*").then_some(value))),
+            "mass" => number(Property::Mass),
+            "radius" => number(Property::Radius),
+            "sma" | "semi-major-axis" => number(Property::SemiMajorAxis),
+            "eccentricity" | "ecc" => number(Property::Eccentricity),
+            "periapsis" => number(Property::Periapsis),
+            "apoapsis" => number(Property::Apoapsis),
+            "depth" => number(Property::Depth),
+            _ => Err(format!("Unknown property '{}' in query", property)),
+        }
+    }
+}
+
+fn parse_object_type(value: &str) -> Result<ObjectType, String> {
+    match value.to_ascii_uppercase().as_str() {
+        "STAR" => Ok(ObjectType::Star),
+        "ROCKY" => Ok(ObjectType::Rocky),
+        "JOVIAN" => Ok(ObjectType::Jovian),
+        "ICE_GIANT" => Ok(ObjectType::IceGiant),
+        _ => Err(format!("Unknown object type '{}' in query", value)),
+    }
+}
+
+/// Parses a number with an optional distance unit, kilometres if none is given
+fn parse_number(value: &str) -> Result<f64, String> {
+    let (number, scale) = if let Some(number) = value.strip_suffix("AU").or_else(|| value.strip_suffix("au")) {
+        (number, ASTRONOMICAL_UNIT)
+    } else {
+        (value.strip_suffix("km").unwrap_or(value), 1.0)
+    };
+    number.parse::<f64>().map(|number| number * scale).map_err(|_| format!("Invalid number '{}' in query", value))
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_select() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let names = |query: &str| -> Vec<String> {
+            Query::parse(query).unwrap().select(&store).into_iter().map(|id| store.get(id).unwrap().name.clone()).collect()
+        };
+        assert_eq!(names("type=ROCKY and parent=Sol and sma<2AU"), vec!["Earth"]);
+        assert_eq!(names("type=rocky and not (atmosphere=*)"), vec!["Luna"]);
+        assert_eq!(names("ancestor=Sol and (mass > 1e23 or name=\"Luna\")"), vec!["Earth", "Luna"]);
+        assert_eq!(names("depth>=1 and atmosphere!=N2"), vec!["Luna"]);
+        assert!(Query::parse("type<ROCKY").is_err());
+        assert!(Query::parse("sma<2 parsecs").is_err());
+        assert!(Query::parse("(mass>1").is_err());
+    }
+}
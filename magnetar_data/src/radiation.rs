@@ -0,0 +1,130 @@
+use std::f64::consts::PI;
+use crate::aerobraking::{molar_mass, AIR_MOLAR_MASS, GAS_CONSTANT};
+use crate::celestial::{Object, ObjectType};
+use crate::orbital::GRAVITATIONAL_CONSTANT;
+use crate::stellar::{main_sequence_lifetime, stellar_state, STEFAN_BOLTZMANN};
+use crate::store::{ObjectId, SystemStore};
+
+/// Share of the starlight every object reflects, about that of Earth
+pub const BOND_ALBEDO: f64 = 0.3;
+
+/// Temperature of the exosphere over the equilibrium temperature, the thin upper atmosphere is heated far beyond
+/// the surface by ultraviolet light. About 4 for Earth.
+pub const EXOSPHERE_HEATING: f64 = 4.0;
+
+/// Seconds a gas takes to thin out by a factor of e at a Jeans parameter of 0, when its molecules are as fast
+/// as the escape velocity. The whole column still has to rise to the exosphere first.
+pub const ESCAPE_TIME: f64 = 1e9;
+
+/// Partial pressure in atm below which a gas counts as lost and is removed
+pub const MIN_PRESSURE: f64 = 1e-6;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A gas that escaped from the atmosphere of an object
+pub struct Escape {
+    pub id: ObjectId,
+    pub gas: String,
+    /// Seconds into the escape step at which the partial pressure dropped below [`MIN_PRESSURE`]
+    pub time: f64,
+}
+
+/// Temperature in K of a black body with [`BOND_ALBEDO`] heated by its star, averaged over its surface. Moons use
+/// the orbit of their planet and stars are assumed to be halfway through their main sequence. `None` for stars and
+/// objects without one.
+pub fn equilibrium_temperature(store: &SystemStore, id: ObjectId) -> Option<f64> {
+    if store.get(id)?.object_type == ObjectType::Star {
+        return None;
+    }
+    let mut body = id;
+    let mut star = store.parent(id)?;
+    while store.get(star)?.object_type != ObjectType::Star {
+        body = star;
+        star = store.parent(star)?;
+    }
+    let star_mass = store.get(star)?.mass;
+    let luminosity = stellar_state(star_mass, main_sequence_lifetime(star_mass) / 2.0).luminosity;
+    // km to m
+    let distance = store.get(body)?.orbital_params.semi_major_axis * 1000.0;
+    Some((luminosity * (1.0 - BOND_ALBEDO) / (16.0 * PI * STEFAN_BOLTZMANN * distance.powi(2))).powf(0.25))
+}
+
+/// Jeans escape parameter of a gas with `molar_mass` kg/mol at `temperature` K on the surface of `object`, the
+/// squared ratio of the escape velocity to the most probable speed of the molecules. Gases escape within
+/// [`ESCAPE_TIME`] below about 1 and are kept for the age of the universe above about 50.
+pub fn jeans_parameter(object: &Object, molar_mass: f64, temperature: f64) -> f64 {
+    // km² s⁻² to m² s⁻²
+    let escape_velocity_squared = 2.0 * GRAVITATIONAL_CONSTANT * object.mass / object.radius * 1e6;
+    escape_velocity_squared * molar_mass / (2.0 * GAS_CONSTANT * temperature)
+}
+
+/// Lets the gases of every atmosphere heated by a star escape over `seconds` by Jeans escape from the exosphere,
+/// light gases on small, hot objects go first. Returns every gas whose partial pressure dropped below
+/// [`MIN_PRESSURE`], those are removed from their atmosphere.
+///
+/// Like [`apply_decay`](crate::decay::apply_decay) this is separate from orbital stepping.
+pub fn apply_escape(store: &mut SystemStore, seconds: f64) -> Vec<Escape> {
+    let mut escapes = Vec::new();
+    let ids: Vec<ObjectId> = store.ids().collect();
+    for id in ids {
+        let object = store.get(id).unwrap().clone();
+        if object.atmosphere.is_empty() || object.radius <= 0.0 {
+            continue;
+        }
+        let Some(temperature) = equilibrium_temperature(store, id).map(|temperature| temperature * EXOSPHERE_HEATING) else {
+            continue;
+        };
+        let mut gases: Vec<(&String, &f64)> = object.atmosphere.iter().collect();
+        gases.sort_by(|a, b| a.0.cmp(b.0));
+        let atmosphere = store.atmosphere_mut(id).unwrap();
+        for (gas, pressure) in gases {
+            let lambda = jeans_parameter(&object, molar_mass(gas).unwrap_or(AIR_MOLAR_MASS), temperature);
+            // Inverse of the e-folding time in s⁻¹
+            let rate = (1.0 + lambda) * (-lambda).exp() / ESCAPE_TIME;
+            let remaining = pressure * (-rate * seconds).exp();
+            if remaining >= MIN_PRESSURE {
+                atmosphere.insert(gas.clone(), remaining);
+                continue;
+            }
+            tracing::info!(object = %object.name, gas = %gas, "gas escaped to space");
+            atmosphere.remove(gas);
+            let time = ((pressure / MIN_PRESSURE).ln() / rate).max(0.0).min(seconds);
+            escapes.push(Escape { id, gas: gas.clone(), time });
+        }
+    }
+    escapes
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::orbital::OrbitalParameters;
+    use crate::stellar::ASTRONOMICAL_UNIT;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_hot_small_planet_loses_hydrogen() {
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let mut cinder = system.children[0].clone();
+        (cinder.name, cinder.mass, cinder.radius, cinder.children) = ("Cinder".to_string(), 6.4e23, 3390.0, Vec::new());
+        cinder.orbital_params = OrbitalParameters { semi_major_axis: 0.05 * ASTRONOMICAL_UNIT, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 0.0 };
+        cinder.atmosphere = [("H2".to_string(), 0.1), ("N2".to_string(), 0.5)].into();
+        system.children.push(cinder);
+        let mut store = SystemStore::new(system);
+        let (sol, earth, luna, cinder) = (store.root_id(), store.find("Earth").unwrap(), store.find("Luna").unwrap(), store.find("Cinder").unwrap());
+
+        assert_eq!(equilibrium_temperature(&store, sol), None);
+        let temperature = equilibrium_temperature(&store, earth).unwrap();
+        assert!((250.0..260.0).contains(&temperature), "{}", temperature);
+        assert_eq!(equilibrium_temperature(&store, luna), Some(temperature));
+
+        let air = store.get(earth).unwrap().atmosphere.clone();
+        // About a thousand years
+        let escapes = apply_escape(&mut store, 3e10);
+        let [escape] = escapes.as_slice() else { panic!("{:?}", escapes) };
+        assert_eq!((escape.id, escape.gas.as_str()), (cinder, "H2"));
+        assert!(escape.time > 1e10 && escape.time < 2e10, "{}", escape.time);
+        let nitrogen = store.get(cinder).unwrap().atmosphere["N2"];
+        assert!(nitrogen > 0.45 && nitrogen < 0.5, "{}", nitrogen);
+        assert_eq!(store.get(earth).unwrap().atmosphere, air);
+    }
+}
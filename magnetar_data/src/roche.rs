@@ -0,0 +1,65 @@
+use std::f64::consts::PI;
+use crate::celestial::Object;
+
+/// Factor of the Roche limit for a fluid body that deforms under the tides, the limit moons and rings form at
+const FLUID_FACTOR: f64 = 2.44;
+
+/// Factor of the Roche limit for a rigid sphere held together only by its own gravity
+const RIGID_FACTOR: f64 = 1.26;
+
+/// Mean density in kg/m³ of an object, 0 for objects without a volume
+pub fn density(object: &Object) -> f64 {
+    let volume = 4.0 / 3.0 * PI * (object.radius * 1000.0).powi(3);
+    if volume > 0.0 { object.mass / volume } else { 0.0 }
+}
+
+/// Distance in km from `parent` within which the tides tear apart `child`, e.g. about 18 400 km for Luna around Earth.
+/// Uses the fluid limit, inside of it bodies form rings instead of moons.
+pub fn roche_limit(parent: &Object, child: &Object) -> f64 {
+    roche_limit_for_density(parent, density(child))
+}
+
+/// Fluid Roche limit in km around `parent` for material with `density` kg/m³, e.g. to place ring particles
+/// inside and moons outside of it
+pub fn roche_limit_for_density(parent: &Object, density: f64) -> f64 {
+    limit(parent, density, FLUID_FACTOR)
+}
+
+/// Distance in km from `parent` within which the tides tear apart `child` if it were perfectly rigid,
+/// a lower bound of the Roche limit
+pub fn rigid_roche_limit(parent: &Object, child: &Object) -> f64 {
+    limit(parent, density(child), RIGID_FACTOR)
+}
+
+fn limit(parent: &Object, density: f64, factor: f64) -> f64 {
+    if density <= 0.0 {
+        return f64::INFINITY;
+    }
+    factor * parent.radius * (self::density(parent) / density).cbrt()
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_earth_moon() {
+        let system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let (earth, luna) = (system.find("Earth").unwrap(), system.find("Luna").unwrap());
+        assert!((roche_limit(earth, luna) - 18_381.0).abs() < 100.0, "{}", roche_limit(earth, luna));
+        assert!((rigid_roche_limit(earth, luna) - 9_492.0).abs() < 100.0, "{}", rigid_roche_limit(earth, luna));
+    }
+
+    #[test]
+    fn test_saturn_rings_inside_moons_outside() {
+        let system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let mut saturn = system.find("Earth").unwrap().clone();
+        (saturn.name, saturn.mass, saturn.radius) = ("Saturn".to_string(), 5.683e26, 58_232.0);
+        let mut mimas = system.find("Luna").unwrap().clone();
+        (mimas.name, mimas.mass, mimas.radius) = ("Mimas".to_string(), 3.75e19, 198.2);
+        // The outer edge of the A ring of porous ice particles and the orbit of Mimas
+        assert!(roche_limit_for_density(&saturn, 500.0) > 136_775.0);
+        assert!(roche_limit(&saturn, &mimas) < 185_539.0);
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeSet;
+use crate::store::{ObjectId, SystemStore};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// A set of selected objects of one [`SystemStore`], iterated in id order
+pub struct Selection {
+    ids: BTreeSet<ObjectId>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Combined information about all objects in a [`Selection`]
+pub struct GroupInfo {
+    pub count: usize,
+    /// Sum of the masses of all selected objects in kg
+    pub combined_mass: f64,
+    /// The object all selected objects orbit, `None` if they do not share one or the root is selected
+    pub shared_parent: Option<ObjectId>,
+}
+
+impl Selection {
+    pub fn new() -> Selection {
+        Selection::default()
+    }
+
+    /// Select only the given object
+    pub fn select(&mut self, id: ObjectId) {
+        self.ids.clear();
+        self.ids.insert(id);
+    }
+
+    /// Add or remove an object, returns whether it is selected afterwards
+    pub fn toggle(&mut self, id: ObjectId) -> bool {
+        if !self.ids.remove(&id) {
+            self.ids.insert(id);
+            return true;
+        }
+        false
+    }
+
+    pub fn insert(&mut self, id: ObjectId) {
+        self.ids.insert(id);
+    }
+
+    pub fn remove(&mut self, id: ObjectId) {
+        self.ids.remove(&id);
+    }
+
+    pub fn contains(&self, id: ObjectId) -> bool {
+        self.ids.contains(&id)
+    }
+
+    pub fn clear(&mut self) {
+        self.ids.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ObjectId> + '_ {
+        self.ids.iter().copied()
+    }
+
+    /// Combined information about the selected objects, ids not in `store` are skipped.
+    /// Returns `None` if none of the selected objects are in `store`.
+    pub fn group(&self, store: &SystemStore) -> Option<GroupInfo> {
+        let ids: Vec<ObjectId> = self.iter().filter(|id| store.get(*id).is_some()).collect();
+        let first = *ids.first()?;
+        let shared_parent = store.parent(first)
+            .filter(|parent| ids.iter().all(|id| store.parent(*id) == Some(*parent)));
+        Some(GroupInfo {
+            count: ids.len(),
+            combined_mass: ids.iter().filter_map(|id| store.get(*id)).map(|object| object.mass).sum(),
+            shared_parent,
+        })
+    }
+}
+
+impl FromIterator<ObjectId> for Selection {
+    fn from_iter<I: IntoIterator<Item = ObjectId>>(iter: I) -> Selection {
+        Selection { ids: iter.into_iter().collect() }
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_group_info() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let (earth, luna) = (store.find("Earth").unwrap(), store.find("Luna").unwrap());
+        let mut selection = Selection::new();
+        selection.select(luna);
+        assert_eq!(selection.group(&store).unwrap().shared_parent, Some(earth));
+
+        assert!(selection.toggle(earth));
+        let group = selection.group(&store).unwrap();
+        assert_eq!(group.count, 2);
+        assert_eq!(group.combined_mass, store.get(earth).unwrap().mass + store.get(luna).unwrap().mass);
+        assert_eq!(group.shared_parent, None);
+
+        assert!(!selection.toggle(earth));
+        selection.remove(luna);
+        assert!(selection.group(&store).is_none());
+    }
+}
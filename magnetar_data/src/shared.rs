@@ -0,0 +1,87 @@
+use std::sync::{Arc, PoisonError, RwLock};
+use crate::celestial::Object;
+use crate::store::SystemStore;
+
+#[derive(Debug, Clone)]
+/// Cheaply cloneable handle to a [`SystemStore`] shared between threads, e.g. a simulation thread stepping
+/// the system and a render thread reading it.
+///
+/// Updates run while holding the write lock, so readers only ever see the state before or after a whole
+/// step, never one in between. Snapshots are immutable copies of the store that stay valid while the
+/// simulation keeps stepping, an update only clones the store if a snapshot of it is still alive.
+pub struct SharedSystem {
+    store: Arc<RwLock<Arc<SystemStore>>>,
+}
+
+impl SharedSystem {
+    pub fn new(store: SystemStore) -> SharedSystem {
+        SharedSystem { store: Arc::new(RwLock::new(Arc::new(store))) }
+    }
+
+    /// The current state of the system, unaffected by later updates
+    pub fn snapshot(&self) -> Arc<SystemStore> {
+        Arc::clone(&self.store.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Run `f` against the current state, updates wait until it returns
+    pub fn read<R>(&self, f: impl FnOnce(&SystemStore) -> R) -> R {
+        f(&self.store.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Run `f` with exclusive access to the system, readers wait until it returns.
+    /// If `f` panics the system is left as `f` left it.
+    pub fn update<R>(&self, f: impl FnOnce(&mut SystemStore) -> R) -> R {
+        let mut guard = self.store.write().unwrap_or_else(PoisonError::into_inner);
+        f(Arc::make_mut(&mut guard))
+    }
+
+    /// Step the whole system by a signed amount of seconds as a single update
+    pub fn step_by(&self, seconds: f64) {
+        self.update(|store| store.step_by(seconds));
+    }
+}
+
+impl From<SystemStore> for SharedSystem {
+    fn from(store: SystemStore) -> SharedSystem {
+        SharedSystem::new(store)
+    }
+}
+
+impl From<Object> for SharedSystem {
+    fn from(system: Object) -> SharedSystem {
+        SharedSystem::new(SystemStore::new(system))
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use std::thread;
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_snapshot_is_not_torn() {
+        assert_send_sync::<SharedSystem>();
+        let shared = SharedSystem::from(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let epoch = shared.snapshot();
+
+        let stepper = {
+            let shared = shared.clone();
+            thread::spawn(move || (0..100).for_each(|_| shared.step_by(3600.0)))
+        };
+        // Earth and Luna are always stepped together, so every read matches the epoch after a whole number of steps
+        for _ in 0..100 {
+            shared.read(|store| {
+                assert!((0..=100).any(|step| epoch.root().state_at(step as f64 * 3600.0).approx_eq(store.root(), 1e-9)));
+            });
+        }
+        stepper.join().unwrap();
+
+        // The snapshot taken before stepping is left untouched
+        assert_eq!(epoch.root(), &load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let stepped = epoch.root().state_at(100.0 * 3600.0);
+        assert!(stepped.approx_eq(shared.snapshot().root(), 1e-9));
+    }
+}
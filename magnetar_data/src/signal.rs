@@ -0,0 +1,32 @@
+use crate::store::{ObjectId, SystemStore};
+
+/// Speed of light in km/s
+pub const SPEED_OF_LIGHT: f64 = 299_792.458;
+
+/// One way light travel time in seconds between two objects at their current positions,
+/// `None` if either id is not in the store
+pub fn light_delay_between(store: &SystemStore, a: ObjectId, b: ObjectId) -> Option<f64> {
+    Some(store.position_in_frame(a, b)?.length() / SPEED_OF_LIGHT)
+}
+
+/// Distance in km a signal travels within `seconds`, the radius of the delay contour for that time
+pub fn signal_range(seconds: f64) -> f64 {
+    seconds * SPEED_OF_LIGHT
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_earth_sun_delay() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let (sol, earth) = (store.root_id(), store.find("Earth").unwrap());
+        let delay = light_delay_between(&store, sol, earth).unwrap();
+        // About 8 minutes 20 seconds, depending on where Earth is on its orbit
+        assert!((490.0..510.0).contains(&delay), "{}", delay);
+        assert_eq!(light_delay_between(&store, earth, sol), Some(delay));
+        assert!((signal_range(delay) - store.absolute_position(earth).unwrap().length()).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::VecDeque;
+use crate::orbital::OrbitalParameters;
+use crate::surface::Rotation;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Orbital state of every object in a [`SystemStore`](crate::store::SystemStore) at one sim time.
+/// Only the orbits and rotations are stored, so snapshots are cheap enough to take every few steps.
+pub struct Snapshot {
+    /// Sim time the snapshot was taken at in seconds
+    pub sim_time: f64,
+    /// Orbits indexed by object id
+    pub(crate) orbits: Vec<OrbitalParameters>,
+    /// Rotations indexed by object id
+    pub(crate) rotations: Vec<Option<Rotation>>,
+}
+
+impl Snapshot {
+    /// A snapshot built from its parts, e.g. one received over the network. Fails if the number of orbits
+    /// and rotations differ.
+    pub fn new(sim_time: f64, orbits: Vec<OrbitalParameters>, rotations: Vec<Option<Rotation>>) -> Result<Snapshot, String> {
+        if orbits.len() != rotations.len() {
+            return Err(format!("Snapshot has {} orbits but {} rotations", orbits.len(), rotations.len()));
+        }
+        Ok(Snapshot { sim_time, orbits, rotations })
+    }
+
+    /// Orbits of all objects indexed by object id
+    pub fn orbits(&self) -> &[OrbitalParameters] {
+        &self.orbits
+    }
+
+    /// Rotations of all objects indexed by object id
+    pub fn rotations(&self) -> &[Option<Rotation>] {
+        &self.rotations
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Ring buffer of the most recent snapshots, the oldest one is dropped once it is full
+pub struct SnapshotBuffer {
+    snapshots: VecDeque<Snapshot>,
+    capacity: usize,
+}
+
+impl SnapshotBuffer {
+    pub fn new(capacity: usize) -> SnapshotBuffer {
+        SnapshotBuffer { snapshots: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Add a snapshot, dropping the oldest one if the buffer is full
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// The most recently pushed snapshot
+    pub fn latest(&self) -> Option<&Snapshot> {
+        self.snapshots.back()
+    }
+
+    /// The most recent snapshot taken at or before `sim_time`
+    pub fn at_or_before(&self, sim_time: f64) -> Option<&Snapshot> {
+        self.snapshots.iter().rev().find(|snapshot| snapshot.sim_time <= sim_time)
+    }
+
+    /// Drop every snapshot taken after `sim_time` and return the most recent remaining one, e.g. to rewind
+    /// by a day restore `rewind_to(sim_time - 86400.0)`. Returns `None` if no snapshot is old enough,
+    /// in which case the buffer is left untouched.
+    pub fn rewind_to(&mut self, sim_time: f64) -> Option<&Snapshot> {
+        let keep = self.snapshots.iter().rposition(|snapshot| snapshot.sim_time <= sim_time)? + 1;
+        self.snapshots.truncate(keep);
+        self.snapshots.back()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// All snapshots from oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = &Snapshot> {
+        self.snapshots.iter()
+    }
+}
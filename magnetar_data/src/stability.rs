@@ -0,0 +1,164 @@
+use std::ops::Range;
+use crate::aerobraking::Atmosphere;
+use crate::roche::roche_limit_for_density;
+use crate::store::{ObjectId, SystemStore};
+
+/// Fraction of the Hill radius within which prograde orbits stay bound over long times
+pub const HILL_STABILITY_FRACTION: f64 = 0.49;
+
+/// Half width in Hill radii of the zone kept clear around the orbit of an existing child, 2√3 after Gladman
+pub const HILL_SPACING: f64 = 3.46;
+
+/// Relative half width of the semi-major axis ranges excluded around low order mean motion resonances
+pub const RESONANCE_WIDTH: f64 = 0.01;
+
+/// Smallest ratio between the semi-major axes of neighbouring orbits for [`StabilityBand::slots`]
+pub const SLOT_SPACING: f64 = 1.5;
+
+/// Density in kg/m³ assumed for new moons when placing the inner edge at the Roche limit, between ice and rock
+pub const MOON_DENSITY: f64 = 2000.0;
+
+/// Period ratios of the mean motion resonances excluded around existing children, inside and outside of them
+const RESONANCES: [f64; 2] = [2.0, 1.5];
+
+#[derive(Debug, Clone, PartialEq)]
+/// Range of semi-major axes in km around an object in which new children have stable orbits
+pub struct StabilityBand {
+    /// Innermost stable orbit, above the surface, the atmosphere and the Roche limit
+    pub inner: f64,
+    /// Outermost stable orbit, a fraction of the Hill radius
+    pub outer: f64,
+    /// Ranges taken by existing children and their resonances, sorted by start
+    pub exclusions: Vec<Range<f64>>,
+    /// Semi-major axes of the existing children
+    occupied: Vec<f64>,
+}
+
+impl StabilityBand {
+    /// Whether a new child with this semi-major axis would have a stable orbit
+    pub fn is_stable(&self, semi_major_axis: f64) -> bool {
+        (self.inner..=self.outer).contains(&semi_major_axis)
+            && !self.exclusions.iter().any(|exclusion| exclusion.contains(&semi_major_axis))
+    }
+
+    /// The parts of the band outside of all exclusions, e.g. to shade them as safe zones
+    pub fn free_ranges(&self) -> Vec<Range<f64>> {
+        let mut ranges = Vec::new();
+        let mut start = self.inner;
+        for exclusion in &self.exclusions {
+            if exclusion.start > start {
+                ranges.push(start..exclusion.start.min(self.outer));
+            }
+            start = start.max(exclusion.end);
+            if start >= self.outer {
+                break;
+            }
+        }
+        if start < self.outer {
+            ranges.push(start..self.outer);
+        }
+        ranges
+    }
+
+    /// Semi-major axes for new children from the inside out, at least [`SLOT_SPACING`] apart
+    /// from each other and from the existing children. None if the band has no positive, finite inner edge to
+    /// start from, e.g. around a body without a radius.
+    pub fn slots(&self) -> Vec<f64> {
+        let mut slots: Vec<f64> = Vec::new();
+        if !(self.inner > 0.0 && self.inner.is_finite() && self.outer.is_finite()) {
+            return slots;
+        }
+        let mut semi_major_axis = self.inner;
+        while semi_major_axis <= self.outer {
+            let spaced = self.occupied.iter().chain(&slots)
+                .all(|other| (semi_major_axis / other).max(other / semi_major_axis) >= SLOT_SPACING);
+            if spaced && self.is_stable(semi_major_axis) {
+                slots.push(semi_major_axis);
+                semi_major_axis *= SLOT_SPACING;
+            } else {
+                semi_major_axis *= 1.01;
+            }
+        }
+        slots
+    }
+
+    /// How many more children fit into the band
+    pub fn capacity(&self) -> usize {
+        self.slots().len()
+    }
+}
+
+/// Hill radius in km of an object at its periapsis, within which its gravity dominates over its parent's
+pub fn hill_radius(store: &SystemStore, id: ObjectId) -> Option<f64> {
+    let object = store.get(id)?;
+    let parent_mass = store.parent_mass(id);
+    if parent_mass <= 0.0 {
+        return None;
+    }
+    Some(object.orbital_params.periapsis() * (object.mass / (3.0 * parent_mass)).cbrt())
+}
+
+/// Band of stable orbits for new children around an object, `None` for the root which has no Hill sphere
+pub fn stability_band(store: &SystemStore, id: ObjectId) -> Option<StabilityBand> {
+    let object = store.get(id)?;
+    let outer = HILL_STABILITY_FRACTION * hill_radius(store, id)?;
+    let atmosphere = Atmosphere::of(object).map_or(0.0, |atmosphere| atmosphere.top());
+    let inner = (object.radius + atmosphere).max(roche_limit_for_density(object, MOON_DENSITY));
+
+    let mut exclusions = Vec::new();
+    let mut occupied = Vec::new();
+    for &child in store.children(id) {
+        let semi_major_axis = store.get(child).map_or(0.0, |child| child.orbital_params.semi_major_axis);
+        let spacing = HILL_SPACING * hill_radius(store, child).unwrap_or(0.0);
+        exclusions.push(semi_major_axis - spacing..semi_major_axis + spacing);
+        for period_ratio in RESONANCES {
+            // Kepler's third law, semi-major axes scale with the period to the power of 2/3
+            for resonance in [semi_major_axis * period_ratio.powf(2.0 / 3.0), semi_major_axis / period_ratio.powf(2.0 / 3.0)] {
+                exclusions.push(resonance * (1.0 - RESONANCE_WIDTH)..resonance * (1.0 + RESONANCE_WIDTH));
+            }
+        }
+        occupied.push(semi_major_axis);
+    }
+    exclusions.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Some(StabilityBand { inner, outer, exclusions, occupied })
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_earth_band() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let earth = store.find("Earth").unwrap();
+        // Earth's Hill sphere reaches about 1.5 million km
+        let hill = hill_radius(&store, earth).unwrap();
+        assert!((hill - 1.47e6).abs() < 0.05e6, "{}", hill);
+        assert!(stability_band(&store, store.root_id()).is_none());
+
+        let band = stability_band(&store, earth).unwrap();
+        let luna = 384_400.0;
+        assert!(band.inner < luna && luna < band.outer);
+        assert!(!band.is_stable(luna));
+        // 3:2 resonance outside of Luna's orbit
+        assert!(!band.is_stable(luna * 1.5f64.powf(2.0 / 3.0)));
+        assert!(band.is_stable(150_000.0));
+
+        let slots = band.slots();
+        assert_eq!(slots.len(), band.capacity());
+        assert!(!slots.is_empty());
+        assert!(slots.iter().all(|slot| band.is_stable(*slot) && (slot / luna).max(luna / slot) >= SLOT_SPACING));
+        let free: f64 = band.free_ranges().iter().map(|range| range.end - range.start).sum();
+        assert!(free < band.outer - band.inner);
+    }
+
+    #[test]
+    fn test_band_of_point_mass_has_no_slots() {
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        system.find_mut("Luna").unwrap().radius = 0.0;
+        let store = SystemStore::new(system);
+        let band = stability_band(&store, store.find("Luna").unwrap()).unwrap();
+        assert_eq!((band.inner, band.capacity()), (0.0, 0));
+    }
+}
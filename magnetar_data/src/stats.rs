@@ -0,0 +1,105 @@
+use std::fmt;
+use crate::celestial::{Object, ObjectType};
+
+#[derive(Debug, Clone, PartialEq)]
+/// Named value picked out of a system, e.g. the innermost orbit or the largest moon
+pub struct Extreme {
+    pub name: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// Aggregate numbers describing a whole system, created by [`SystemStats::compute`]
+pub struct SystemStats {
+    /// Sum of the masses of all objects in kg
+    pub total_mass: f64,
+    pub stars: usize,
+    pub rocky: usize,
+    pub jovian: usize,
+    pub ice_giants: usize,
+    /// Objects orbiting something other than a star
+    pub moons: usize,
+    /// Objects with at least one gas in their atmosphere
+    pub with_atmosphere: usize,
+    /// Object directly orbiting the root with the smallest semi-major axis in km
+    pub innermost: Option<Extreme>,
+    /// Object directly orbiting the root with the largest semi-major axis in km
+    pub outermost: Option<Extreme>,
+    /// Heaviest moon with its mass in kg
+    pub largest_moon: Option<Extreme>,
+}
+
+impl SystemStats {
+    /// Statistics of the tree below and including `root`
+    pub fn compute(root: &Object) -> SystemStats {
+        let mut stats = SystemStats::default();
+        for item in root {
+            let object = item.object;
+            stats.total_mass += object.mass;
+            match object.object_type {
+                ObjectType::Star => stats.stars += 1,
+                ObjectType::Rocky => stats.rocky += 1,
+                ObjectType::Jovian => stats.jovian += 1,
+                ObjectType::IceGiant => stats.ice_giants += 1,
+            }
+            if !object.atmosphere.is_empty() {
+                stats.with_atmosphere += 1;
+            }
+            let extreme = |value: f64| Extreme { name: object.name.clone(), value };
+            let semi_major_axis = object.orbital_params.semi_major_axis;
+            if item.depth == 1 {
+                if stats.innermost.as_ref().is_none_or(|innermost| semi_major_axis < innermost.value) {
+                    stats.innermost = Some(extreme(semi_major_axis));
+                }
+                if stats.outermost.as_ref().is_none_or(|outermost| semi_major_axis > outermost.value) {
+                    stats.outermost = Some(extreme(semi_major_axis));
+                }
+            }
+            if item.parent.is_some_and(|parent| parent.object_type != ObjectType::Star) {
+                stats.moons += 1;
+                if stats.largest_moon.as_ref().is_none_or(|largest| object.mass > largest.value) {
+                    stats.largest_moon = Some(extreme(object.mass));
+                }
+            }
+        }
+        stats
+    }
+
+    /// Number of objects in the system
+    pub fn bodies(&self) -> usize {
+        self.stars + self.rocky + self.jovian + self.ice_giants
+    }
+}
+
+impl fmt::Display for SystemStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} bodies with a total mass of {:.4e} kg", self.bodies(), self.total_mass)?;
+        writeln!(f, "{} star(s), {} rocky, {} jovian, {} ice giant(s), {} moon(s)",
+                 self.stars, self.rocky, self.jovian, self.ice_giants, self.moons)?;
+        writeln!(f, "{} with an atmosphere", self.with_atmosphere)?;
+        if let (Some(innermost), Some(outermost)) = (&self.innermost, &self.outermost) {
+            writeln!(f, "Orbits from {} at {:.0} km to {} at {:.0} km",
+                     innermost.name, innermost.value, outermost.name, outermost.value)?;
+        }
+        if let Some(moon) = &self.largest_moon {
+            writeln!(f, "Largest moon is {} with {:.4e} kg", moon.name, moon.value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_stats_of_earth_moon_system() {
+        let stats = SystemStats::compute(&load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        assert_eq!((stats.bodies(), stats.stars, stats.rocky, stats.moons, stats.with_atmosphere), (3, 1, 2, 1, 1));
+        assert!((stats.total_mass - (1.988416e30 + 5.97237e24 + 7.342e22)).abs() < 1e18);
+        assert_eq!(stats.innermost, stats.outermost);
+        assert_eq!(stats.innermost.unwrap().name, "Earth");
+        assert_eq!(stats.largest_moon.unwrap().name, "Luna");
+    }
+}
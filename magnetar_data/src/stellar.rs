@@ -0,0 +1,212 @@
+use std::f64::consts::PI;
+use std::fmt;
+use std::ops::Range;
+use crate::celestial::ObjectType;
+use crate::store::{ObjectId, SystemStore};
+
+/// Mass of Sol in kg
+pub const SOLAR_MASS: f64 = 1.988416e30;
+
+/// Luminosity of Sol in W
+pub const SOLAR_LUMINOSITY: f64 = 3.828e26;
+
+/// Radius of Sol in km
+pub const SOLAR_RADIUS: f64 = 695_700.0;
+
+/// Astronomical unit in km
+pub const ASTRONOMICAL_UNIT: f64 = 149_597_870.7;
+
+/// Stefan-Boltzmann constant in W m⁻² K⁻⁴
+pub(crate) const STEFAN_BOLTZMANN: f64 = 5.670374e-8;
+
+/// Main sequence lifetime of Sol in years
+const SOLAR_LIFETIME: f64 = 1e10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// Harvard spectral class of a star by its effective temperature, from hottest to coolest
+pub enum SpectralClass {
+    O,
+    B,
+    A,
+    F,
+    G,
+    K,
+    M,
+}
+
+impl SpectralClass {
+    /// Spectral class of a star with an effective temperature in K
+    pub fn from_temperature(temperature: f64) -> SpectralClass {
+        match temperature {
+            t if t >= 30_000.0 => SpectralClass::O,
+            t if t >= 10_000.0 => SpectralClass::B,
+            t if t >= 7_500.0 => SpectralClass::A,
+            t if t >= 6_000.0 => SpectralClass::F,
+            t if t >= 5_200.0 => SpectralClass::G,
+            t if t >= 3_700.0 => SpectralClass::K,
+            _ => SpectralClass::M,
+        }
+    }
+}
+
+impl fmt::Display for SpectralClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// State of a main sequence star at a given age
+pub struct StellarState {
+    /// Age in years
+    pub age: f64,
+    /// Luminosity in W
+    pub luminosity: f64,
+    /// Radius in km
+    pub radius: f64,
+    /// Effective surface temperature in K
+    pub temperature: f64,
+    pub class: SpectralClass,
+}
+
+impl StellarState {
+    /// Distances in km from the star at which liquid water can exist on the surface of a planet
+    pub fn habitable_zone(&self) -> Range<f64> {
+        habitable_zone(self.luminosity)
+    }
+}
+
+/// Luminosity in W of a main sequence star with `mass` kg halfway through its lifetime
+fn main_sequence_luminosity(mass: f64) -> f64 {
+    let mass = mass / SOLAR_MASS;
+    let luminosity = if mass < 0.43 {
+        0.23 * mass.powf(2.3)
+    } else if mass < 2.0 {
+        mass.powi(4)
+    } else if mass < 55.0 {
+        1.4 * mass.powf(3.5)
+    } else {
+        32_000.0 * mass
+    };
+    luminosity * SOLAR_LUMINOSITY
+}
+
+/// Radius in km of a main sequence star with `mass` kg halfway through its lifetime
+fn main_sequence_radius(mass: f64) -> f64 {
+    let mass = mass / SOLAR_MASS;
+    SOLAR_RADIUS * if mass < 1.0 { mass.powf(0.8) } else { mass.powf(0.57) }
+}
+
+/// Time in years a star with `mass` kg spends on the main sequence, about 10 billion years for Sol
+pub fn main_sequence_lifetime(mass: f64) -> f64 {
+    if mass <= 0.0 {
+        return 0.0;
+    }
+    SOLAR_LIFETIME * (mass / SOLAR_MASS) / (main_sequence_luminosity(mass) / SOLAR_LUMINOSITY)
+}
+
+/// State of a main sequence star with `mass` kg at `age` years. Luminosity and radius grow linearly over
+/// the main sequence, calibrated so Sol starts at 70% of its current luminosity and 89% of its current radius.
+/// Later stages are not modelled, stars past the main sequence keep their final state.
+pub fn stellar_state(mass: f64, age: f64) -> StellarState {
+    let lifetime = main_sequence_lifetime(mass);
+    let progress = if lifetime > 0.0 { (age / lifetime).clamp(0.0, 1.0) } else { 0.0 };
+    let luminosity = main_sequence_luminosity(mass) * (0.7 + 0.65 * progress);
+    let radius = main_sequence_radius(mass) * (0.89 + 0.24 * progress);
+    let area = 4.0 * PI * (radius * 1000.0).powi(2);
+    let temperature = if area > 0.0 { (luminosity / (area * STEFAN_BOLTZMANN)).powf(0.25) } else { 0.0 };
+    StellarState { age, luminosity, radius, temperature, class: SpectralClass::from_temperature(temperature) }
+}
+
+/// Distances in km from a star with `luminosity` W at which liquid water can exist on the surface of a planet,
+/// using the runaway and maximum greenhouse limits
+pub fn habitable_zone(luminosity: f64) -> Range<f64> {
+    let luminosity = luminosity / SOLAR_LUMINOSITY;
+    (luminosity / 1.1).sqrt() * ASTRONOMICAL_UNIT..(luminosity / 0.53).sqrt() * ASTRONOMICAL_UNIT
+}
+
+#[derive(Debug, Clone)]
+/// Slow evolution of all stars in a system over megayears, advanced on its own coarse tick
+/// independent of orbital stepping. The radii of the stars in the store follow their age.
+pub struct StellarEvolution {
+    stars: Vec<(ObjectId, f64)>,
+    age: f64,
+    tick: f64,
+    pending: f64,
+}
+
+impl StellarEvolution {
+    /// Evolution of all stars in `store`, which all are `age` years old and get updated every `tick` years
+    pub fn new(store: &SystemStore, age: f64, tick: f64) -> StellarEvolution {
+        let stars = store.ids()
+            .filter_map(|id| store.get(id).filter(|object| object.object_type == ObjectType::Star).map(|star| (id, star.mass)))
+            .collect();
+        StellarEvolution { stars, age, tick, pending: 0.0 }
+    }
+
+    /// Age of the stars in years as of the last tick
+    pub fn age(&self) -> f64 {
+        self.age
+    }
+
+    /// Current state of a star, `None` if `id` is not a star
+    pub fn state(&self, id: ObjectId) -> Option<StellarState> {
+        self.stars.iter().find(|(star, _)| *star == id).map(|(_, mass)| stellar_state(*mass, self.age))
+    }
+
+    /// Let `years` pass and apply every whole tick to the radii in `store`, returns whether a tick happened
+    pub fn advance(&mut self, store: &mut SystemStore, years: f64) -> bool {
+        self.pending += years;
+        if self.pending < self.tick {
+            return false;
+        }
+        let ticks = (self.pending / self.tick).floor();
+        self.age += ticks * self.tick;
+        self.pending -= ticks * self.tick;
+        tracing::debug!(age = self.age, "stellar evolution tick");
+        self.apply(store);
+        true
+    }
+
+    /// Sets the radii of the stars in `store` to their current state
+    pub fn apply(&self, store: &mut SystemStore) {
+        for &(id, mass) in &self.stars {
+            if let Some(radius) = store.radius_mut(id) {
+                *radius = stellar_state(mass, self.age).radius;
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_present_day_sun() {
+        let sun = stellar_state(SOLAR_MASS, 4.6e9);
+        assert!((sun.luminosity / SOLAR_LUMINOSITY - 1.0).abs() < 0.01);
+        assert!((sun.radius / SOLAR_RADIUS - 1.0).abs() < 0.01);
+        assert!((sun.temperature - 5772.0).abs() < 50.0, "{}", sun.temperature);
+        assert_eq!(sun.class, SpectralClass::G);
+        assert!(sun.habitable_zone().contains(&ASTRONOMICAL_UNIT));
+        assert!(stellar_state(SOLAR_MASS, 0.0).luminosity < 0.75 * SOLAR_LUMINOSITY);
+        assert_eq!(stellar_state(0.2 * SOLAR_MASS, 1e9).class, SpectralClass::M);
+    }
+
+    #[test]
+    fn test_evolution_ticks() {
+        let mut store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let sol = store.root_id();
+        let mut evolution = StellarEvolution::new(&store, 4.6e9, 1e6);
+        assert!(!evolution.advance(&mut store, 5e5));
+        assert_eq!(store.root().radius, 695_700.0);
+        assert!(evolution.advance(&mut store, 1e9));
+        assert_eq!(evolution.age(), 5.6e9);
+        let state = evolution.state(sol).unwrap();
+        assert_eq!(store.root().radius, state.radius);
+        assert!(state.habitable_zone().start > stellar_state(SOLAR_MASS, 4.6e9).habitable_zone().start);
+        assert!(evolution.state(store.find("Earth").unwrap()).is_none());
+    }
+}
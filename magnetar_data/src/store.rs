@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::celestial::Object;
+use crate::orbital::OrbitalParameters;
+use crate::snapshot::Snapshot;
+use crate::vector::DVec3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+/// Lightweight handle to an object in a [`SystemStore`], cheap to copy into entities instead of the object itself.
+/// Ids are assigned depth first, so the root is always `ObjectId(0)`.
+pub struct ObjectId(pub u32);
+
+#[derive(Debug, Clone)]
+/// Owns a whole system tree once and hands out [`ObjectId`]s to reference single objects in it
+pub struct SystemStore {
+    root: Object,
+    /// Child indices leading from the root to each object, indexed by id
+    paths: Vec<Vec<usize>>,
+    parents: Vec<Option<ObjectId>>,
+    children: Vec<Vec<ObjectId>>,
+    by_name: HashMap<String, ObjectId>,
+}
+
+impl SystemStore {
+    /// Takes ownership of a system and indexes all of its objects
+    pub fn new(root: Object) -> SystemStore {
+        let mut paths: Vec<Vec<usize>> = Vec::new();
+        let mut parents = Vec::new();
+        let mut children: Vec<Vec<ObjectId>> = Vec::new();
+        let mut by_name = HashMap::new();
+        // Ids of the current object's ancestors, indexed by depth
+        let mut ancestors: Vec<ObjectId> = Vec::new();
+
+        for item in root.iter() {
+            let id = ObjectId(paths.len() as u32);
+            ancestors.truncate(item.depth);
+            let parent = ancestors.last().copied();
+            let path = match parent {
+                Some(parent) => {
+                    let siblings = &mut children[parent.0 as usize];
+                    let mut path = paths[parent.0 as usize].clone();
+                    path.push(siblings.len());
+                    siblings.push(id);
+                    path
+                }
+                None => Vec::new(),
+            };
+            paths.push(path);
+            parents.push(parent);
+            children.push(Vec::new());
+            // Keep the first object with a name if names repeat, lookups by id are always unambiguous
+            by_name.entry(item.object.name.clone()).or_insert(id);
+            ancestors.push(id);
+        }
+
+        SystemStore { root, paths, parents, children, by_name }
+    }
+
+    /// The root object of the system
+    pub fn root(&self) -> &Object {
+        &self.root
+    }
+
+    /// Id of the root object
+    pub fn root_id(&self) -> ObjectId {
+        ObjectId(0)
+    }
+
+    /// Number of objects in the system
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Whether the store contains no objects, never true as there always is a root
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// All ids in depth first order
+    pub fn ids(&self) -> impl Iterator<Item = ObjectId> {
+        (0..self.paths.len() as u32).map(ObjectId)
+    }
+
+    /// Looks up the object behind an id
+    pub fn get(&self, id: ObjectId) -> Option<&Object> {
+        let path = self.paths.get(id.0 as usize)?;
+        Some(path.iter().fold(&self.root, |object, &index| &object.children[index]))
+    }
+
+    /// Child indices leading from the root to the object behind an id
+    pub(crate) fn path(&self, id: ObjectId) -> Option<&[usize]> {
+        self.paths.get(id.0 as usize).map(Vec::as_slice)
+    }
+
+    /// Borrowed view of the object behind an id
+    pub fn get_ref(&self, id: ObjectId) -> Option<ObjectRef<'_>> {
+        self.get(id).map(|object| ObjectRef { store: self, id, object })
+    }
+
+    /// Mutable access to the orbit of an object, the tree structure itself can only change by building a new store
+    pub fn orbit_mut(&mut self, id: ObjectId) -> Option<&mut OrbitalParameters> {
+        Some(&mut self.object_mut(id)?.orbital_params)
+    }
+
+    /// Mutable access to the radius of an object, e.g. for stars growing over their lifetime
+    pub fn radius_mut(&mut self, id: ObjectId) -> Option<&mut f64> {
+        Some(&mut self.object_mut(id)?.radius)
+    }
+
+    /// Mutable access to the partial pressures of an object's atmosphere, e.g. for gases escaping to space
+    pub fn atmosphere_mut(&mut self, id: ObjectId) -> Option<&mut HashMap<String, f64>> {
+        Some(&mut self.object_mut(id)?.atmosphere)
+    }
+
+    /// Mutable access to a single object, kept private so the tree structure cannot change under the index
+    fn object_mut(&mut self, id: ObjectId) -> Option<&mut Object> {
+        let path = self.paths.get(id.0 as usize)?;
+        Some(path.iter().fold(&mut self.root, |object, &index| &mut object.children[index]))
+    }
+
+    /// Id of the first object with the given name
+    pub fn find(&self, name: &str) -> Option<ObjectId> {
+        self.by_name.get(name).copied()
+    }
+
+    /// Id of the object the given one orbits, `None` for the root
+    pub fn parent(&self, id: ObjectId) -> Option<ObjectId> {
+        self.parents.get(id.0 as usize).copied().flatten()
+    }
+
+    /// Ids of all objects directly orbiting the given one
+    pub fn children(&self, id: ObjectId) -> &[ObjectId] {
+        self.children.get(id.0 as usize).map_or(&[], Vec::as_slice)
+    }
+
+    /// Mass of the object the given one orbits in kg, 0 for the root
+    pub fn parent_mass(&self, id: ObjectId) -> f64 {
+        self.parent(id).and_then(|parent| self.get(parent)).map_or(0.0, |parent| parent.mass)
+    }
+
+    /// Position of an object relative to the root in km, summing up the positions of all of its ancestors
+    pub fn absolute_position(&self, id: ObjectId) -> Option<DVec3> {
+        let path = self.paths.get(id.0 as usize)?;
+        let mut object = &self.root;
+        let mut position = DVec3::ZERO;
+        for &index in path {
+            object = &object.children[index];
+            position += object.orbital_params.position();
+        }
+        Some(position)
+    }
+
+    /// Position of an object as seen from `frame` in km, e.g. Mars from Earth to show its apparent loops
+    pub fn position_in_frame(&self, id: ObjectId, frame: ObjectId) -> Option<DVec3> {
+        Some(self.absolute_position(id)? - self.absolute_position(frame)?)
+    }
+
+    /// Capture the orbits and rotations of all objects, `sim_time` is only recorded to find the snapshot again later
+    pub fn snapshot(&self, sim_time: f64) -> Snapshot {
+        let orbits = self.root.iter().map(|item| item.object.orbital_params.clone()).collect();
+        let rotations = self.root.iter().map(|item| item.object.rotation).collect();
+        Snapshot { sim_time, orbits, rotations }
+    }
+
+    /// Restore the orbits and rotations of all objects from a snapshot of this system
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), String> {
+        if snapshot.orbits.len() != self.len() {
+            return Err(format!("Snapshot has {} objects but the system has {}", snapshot.orbits.len(), self.len()));
+        }
+        for (index, (orbit, rotation)) in snapshot.orbits.iter().zip(&snapshot.rotations).enumerate() {
+            let object = self.object_mut(ObjectId(index as u32)).unwrap();
+            object.orbital_params = orbit.clone();
+            object.rotation = *rotation;
+        }
+        Ok(())
+    }
+
+    /// Step the whole system by a signed amount of seconds
+    pub fn step_by(&mut self, seconds: f64) {
+        self.root.step_by(seconds);
+    }
+
+    /// Step the system by a signed amount of seconds, except for everything orbiting the objects `frozen` is true
+    /// for. Frozen objects still move themselves, only their subsystems stay as they are until they are caught up
+    /// with [`SystemStore::step_children_by`], which gives the same result as stepping them all along.
+    pub fn step_by_except(&mut self, seconds: f64, frozen: impl Fn(ObjectId) -> bool) {
+        for id in self.ids() {
+            if !self.ancestors(id).any(&frozen) {
+                self.step_object(id, seconds);
+            }
+        }
+    }
+
+    /// Step everything orbiting an object, directly or not, by a signed amount of seconds
+    pub fn step_children_by(&mut self, id: ObjectId, seconds: f64) {
+        for descendant in self.ids() {
+            if self.ancestors(descendant).any(|ancestor| ancestor == id) {
+                self.step_object(descendant, seconds);
+            }
+        }
+    }
+
+    /// Parent, grandparent and so on up to the root
+    fn ancestors(&self, id: ObjectId) -> impl Iterator<Item = ObjectId> + '_ {
+        std::iter::successors(self.parent(id), |&parent| self.parent(parent))
+    }
+
+    fn step_object(&mut self, id: ObjectId, seconds: f64) {
+        let parent_mass = self.parent_mass(id);
+        let object = self.object_mut(id).unwrap();
+        object.orbital_params.step_by(seconds, parent_mass);
+        if let Some(rotation) = &mut object.rotation {
+            rotation.step_by(seconds);
+        }
+    }
+
+    /// Consumes the store and returns the system tree
+    pub fn into_root(self) -> Object {
+        self.root
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// An object in a [`SystemStore`] together with its id, allowing navigation to related objects
+pub struct ObjectRef<'a> {
+    store: &'a SystemStore,
+    id: ObjectId,
+    object: &'a Object,
+}
+
+impl<'a> ObjectRef<'a> {
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn object(&self) -> &'a Object {
+        self.object
+    }
+
+    /// The object this one orbits, `None` for the root
+    pub fn parent(&self) -> Option<ObjectRef<'a>> {
+        self.store.parent(self.id).and_then(|parent| self.store.get_ref(parent))
+    }
+
+    /// All objects directly orbiting this one
+    pub fn children(&self) -> impl Iterator<Item = ObjectRef<'a>> + 'a {
+        let store = self.store;
+        store.children(self.id).iter().filter_map(move |child| store.get_ref(*child))
+    }
+}
+
+impl std::ops::Deref for ObjectRef<'_> {
+    type Target = Object;
+
+    fn deref(&self) -> &Object {
+        self.object
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::snapshot::SnapshotBuffer;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_ids_follow_tree() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        assert_eq!(store.len(), 3);
+        let luna = store.find("Luna").unwrap();
+        let earth = store.parent(luna).unwrap();
+        assert_eq!(store.get(earth).unwrap().name, "Earth");
+        assert_eq!(store.children(earth), &[luna]);
+        assert_eq!(store.parent(store.root_id()), None);
+        let luna_ref = store.get_ref(luna).unwrap();
+        assert_eq!(luna_ref.parent().unwrap().parent().unwrap().name, "Sol");
+    }
+
+    #[test]
+    fn test_absolute_position() {
+        let store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let (earth, luna) = (store.find("Earth").unwrap(), store.find("Luna").unwrap());
+        let distance = store.absolute_position(luna).unwrap().distance(store.absolute_position(earth).unwrap());
+        assert!((distance - store.get(luna).unwrap().orbital_params.radius()).abs() < 1e-6);
+        assert_eq!(store.absolute_position(store.root_id()), Some(DVec3::ZERO));
+        assert_eq!(store.position_in_frame(earth, earth), Some(DVec3::ZERO));
+        let luna_from_earth = store.position_in_frame(luna, earth).unwrap();
+        assert!(luna_from_earth.distance(store.get(luna).unwrap().orbital_params.position()) < 1e-6);
+    }
+
+    #[test]
+    fn test_step_keeps_ids() {
+        let mut store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let luna = store.find("Luna").unwrap();
+        let before = store.get(luna).unwrap().orbital_params.mean_anomaly;
+        store.step_by(86400.0);
+        assert_ne!(store.get(luna).unwrap().orbital_params.mean_anomaly, before);
+        store.orbit_mut(luna).unwrap().mean_anomaly = 0.0;
+        assert_eq!(store.get(luna).unwrap().orbital_params.mean_anomaly, 0.0);
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let epoch = store.root().clone();
+        let mut buffer = SnapshotBuffer::new(3);
+        for day in 0..5 {
+            buffer.push(store.snapshot(day as f64 * 86400.0));
+            store.step_by(86400.0);
+        }
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.iter().next().unwrap().sim_time, 2.0 * 86400.0);
+        assert!(buffer.rewind_to(86400.0).is_none());
+
+        let snapshot = buffer.rewind_to(3.5 * 86400.0).unwrap().clone();
+        assert_eq!(buffer.len(), 2);
+        store.restore(&snapshot).unwrap();
+        assert!(store.root().approx_eq(&epoch.state_at(3.0 * 86400.0), 1e-9));
+    }
+}
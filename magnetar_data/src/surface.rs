@@ -0,0 +1,130 @@
+use std::fmt;
+use crate::celestial::Object;
+use crate::store::{ObjectId, SystemStore};
+use crate::vector::DVec3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+/// Rotation of an object around the axis perpendicular to its parent's orbital plane
+pub struct Rotation {
+    /// Sidereal rotation period in seconds, negative for retrograde rotation like Venus
+    pub period: f64,
+    /// Current angle in degrees between the x axis and the prime meridian, longitude 0 on the surface
+    pub prime_meridian: f64,
+}
+
+impl Rotation {
+    /// Angle of the prime meridian in degrees `seconds` from now
+    pub fn prime_meridian_at(&self, seconds: f64) -> f64 {
+        if self.period == 0.0 {
+            return self.prime_meridian;
+        }
+        (self.prime_meridian + 360.0 * seconds / self.period).rem_euclid(360.0)
+    }
+
+    /// Rotates by a signed amount of seconds
+    pub fn step_by(&mut self, seconds: f64) {
+        self.prime_meridian = self.prime_meridian_at(seconds);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Planetocentric coordinates on the surface of an object
+pub struct SurfacePoint {
+    /// Latitude in degrees, positive north of the orbital plane
+    pub latitude: f64,
+    /// East longitude in degrees from the prime meridian in (-180, 180]
+    pub longitude: f64,
+}
+
+impl SurfacePoint {
+    /// The point of `body`'s surface below `offset`, a position relative to its centre in km.
+    /// Objects without a rotation keep their prime meridian on the x axis.
+    pub fn below(body: &Object, offset: DVec3) -> SurfacePoint {
+        let prime_meridian = body.rotation.map_or(0.0, |rotation| rotation.prime_meridian);
+        let horizontal = offset.x.hypot(offset.y);
+        SurfacePoint {
+            latitude: offset.z.atan2(horizontal).to_degrees(),
+            longitude: 180.0 - (180.0 - (offset.y.atan2(offset.x).to_degrees() - prime_meridian)).rem_euclid(360.0),
+        }
+    }
+
+    /// Position relative to the centre of `body` in km, `altitude` km above this point at the body's
+    /// current rotation, e.g. to place a colony on the surface
+    pub fn position_on(&self, body: &Object, altitude: f64) -> DVec3 {
+        let prime_meridian = body.rotation.map_or(0.0, |rotation| rotation.prime_meridian);
+        let (latitude, longitude) = (self.latitude.to_radians(), (self.longitude + prime_meridian).to_radians());
+        DVec3::new(latitude.cos() * longitude.cos(), latitude.cos() * longitude.sin(), latitude.sin()) * (body.radius + altitude)
+    }
+}
+
+impl fmt::Display for SurfacePoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let north_south = if self.latitude < 0.0 { 'S' } else { 'N' };
+        let east_west = if self.longitude < 0.0 { 'W' } else { 'E' };
+        write!(f, "{:.3}°{} {:.3}°{}", self.latitude.abs(), north_south, self.longitude.abs(), east_west)
+    }
+}
+
+/// Point on the surface of `body` directly below `satellite`, `None` if either id is unknown
+pub fn sub_satellite_point(store: &SystemStore, satellite: ObjectId, body: ObjectId) -> Option<SurfacePoint> {
+    Some(SurfacePoint::below(store.get(body)?, store.position_in_frame(satellite, body)?))
+}
+
+/// Sub-satellite points of `satellite` on `body` every `interval` seconds from `start` to `end` seconds from now
+pub fn ground_track(store: &SystemStore, satellite: ObjectId, body: ObjectId, start: f64, end: f64, interval: f64)
+                    -> Vec<(f64, SurfacePoint)> {
+    if interval <= 0.0 {
+        return Vec::new();
+    }
+    let samples = ((end - start) / interval).floor().max(0.0) as usize;
+    (0..=samples)
+        .filter_map(|sample| {
+            let time = start + sample as f64 * interval;
+            let mut store = store.clone();
+            store.step_by(time);
+            sub_satellite_point(&store, satellite, body).map(|point| (time, point))
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::orbital::OrbitalParameters;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_geostationary_ground_track_stands_still() {
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let mut satellite = system.children[0].children[0].clone();
+        (satellite.name, satellite.mass, satellite.radius) = ("Geo".to_string(), 1000.0, 0.01);
+        satellite.orbital_params = OrbitalParameters { semi_major_axis: 42_164.17, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 0.0 };
+        system.children[0].children.push(satellite);
+        let store = SystemStore::new(system);
+        let (earth, geo) = (store.find("Earth").unwrap(), store.find("Geo").unwrap());
+
+        let track = ground_track(&store, geo, earth, 0.0, 86400.0, 3600.0);
+        assert_eq!(track.len(), 25);
+        for (_, point) in &track {
+            assert_eq!(point.latitude, 0.0);
+            assert!((point.longitude - track[0].1.longitude).abs() < 0.1, "{}", point);
+        }
+        // The sub-lunar point comes back about 12° further east every day, as Luna moves 13° along its orbit
+        let luna = store.find("Luna").unwrap();
+        let luna_track = ground_track(&store, luna, earth, 0.0, 86400.0, 86400.0);
+        let drift = (luna_track[1].1.longitude - luna_track[0].1.longitude).rem_euclid(360.0);
+        assert!((10.0..15.0).contains(&drift), "{}", drift);
+    }
+
+    #[test]
+    fn test_surface_position_round_trip() {
+        let system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let earth = &system.children[0];
+        let site = SurfacePoint { latitude: 48.2, longitude: 16.37 };
+        let position = site.position_on(earth, 0.0);
+        assert!((position.length() - earth.radius).abs() < 1e-9);
+        let back = SurfacePoint::below(earth, position);
+        assert!((back.latitude - site.latitude).abs() < 1e-9 && (back.longitude - site.longitude).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,82 @@
+use std::io::{self, Write};
+use crate::delta_v::csv_field;
+use crate::store::{ObjectId, SystemStore};
+
+/// Samples the positions and orbital elements of a few bodies into CSV while a simulation runs,
+/// one row per body and sample with positions relative to the parent in km
+pub struct TelemetryRecorder<W: Write> {
+    writer: W,
+    bodies: Vec<ObjectId>,
+    /// Sim time in seconds between two samples
+    cadence: f64,
+    last_sample: Option<f64>,
+    rows: usize,
+}
+
+impl<W: Write> TelemetryRecorder<W> {
+    /// Starts a recording by writing the header row
+    ///
+    /// * `bodies` - Bodies to sample, unknown ids are skipped when sampling
+    /// * `cadence` - Sim time in seconds between two samples, 0 samples on every call of [`TelemetryRecorder::record`]
+    pub fn new(mut writer: W, bodies: Vec<ObjectId>, cadence: f64) -> io::Result<TelemetryRecorder<W>> {
+        writeln!(writer, "sim_time,body,x,y,semi_major_axis,eccentricity,longitude_of_periapsis,mean_anomaly")?;
+        Ok(TelemetryRecorder { writer, bodies, cadence: cadence.abs(), last_sample: None, rows: 0 })
+    }
+
+    /// Samples all bodies if at least the cadence has passed since the last sample, in either direction.
+    /// Returns whether a sample was written.
+    pub fn record(&mut self, store: &SystemStore, sim_time: f64) -> io::Result<bool> {
+        if self.last_sample.is_some_and(|last| (sim_time - last).abs() < self.cadence) {
+            return Ok(false);
+        }
+        for &id in &self.bodies {
+            let Some(object) = store.get(id) else {
+                continue;
+            };
+            let orbit = &object.orbital_params;
+            let position = orbit.position();
+            writeln!(self.writer, "{},{},{},{},{},{},{},{}", sim_time, csv_field(&object.name), position.x, position.y,
+                     orbit.semi_major_axis, orbit.eccentricity, orbit.longitude_of_periapsis, orbit.mean_anomaly)?;
+            self.rows += 1;
+        }
+        self.last_sample = Some(sim_time);
+        Ok(true)
+    }
+
+    /// Number of rows written so far, without the header
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Stops the recording, flushing and returning the writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        tracing::info!(rows = self.rows, "telemetry recording finished");
+        Ok(self.writer)
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_records_at_cadence() {
+        let mut store = SystemStore::new(load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap());
+        let bodies = vec![store.find("Earth").unwrap(), store.find("Luna").unwrap()];
+        let mut recorder = TelemetryRecorder::new(Vec::new(), bodies, 3600.0).unwrap();
+        for minute in 0..=120 {
+            if minute > 0 {
+                store.step_by(60.0);
+            }
+            recorder.record(&store, minute as f64 * 60.0).unwrap();
+        }
+        assert_eq!(recorder.rows(), 6);
+        let csv = String::from_utf8(recorder.finish().unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert!(lines[3].starts_with("3600,Earth,"));
+        assert!(lines[6].starts_with("7200,Luna,"));
+    }
+}
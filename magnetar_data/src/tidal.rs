@@ -0,0 +1,100 @@
+use std::f64::consts::PI;
+use crate::celestial::Object;
+use crate::orbital::GRAVITATIONAL_CONSTANT;
+
+/// Ratio of the tidal Love number k2 to the tidal quality factor Q used for all bodies, Io's measured value
+pub const LOVE_NUMBER_RATIO: f64 = 0.015;
+
+/// Surface heat flux in W/m² from which a body is treated as volcanically active, Io's is about 2 W/m²
+pub const VOLCANIC_HEAT_FLUX: f64 = 1.0;
+
+/// Surface heat flux in W/m² from which a body may keep a liquid ocean under its surface, Enceladus' is about 0.03 W/m²
+pub const SUBSURFACE_OCEAN_HEAT_FLUX: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What the tidal heating of a body is enough for
+pub enum ThermalClass {
+    Inert,
+    /// Enough heat to keep water liquid under an ice shell, like Europa or Enceladus
+    SubsurfaceOcean,
+    /// Enough heat for active volcanism, like Io
+    Volcanic,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Thermal state of a body from the tides raised by its parent
+pub struct ThermalState {
+    /// Tidal heating power in W
+    pub tidal_heating: f64,
+    /// Tidal heating spread over the surface in W/m²
+    pub heat_flux: f64,
+    pub class: ThermalClass,
+}
+
+/// Tidal heating power in W of a synchronously rotating body on an eccentric orbit, using
+/// `21/2 · k2/Q · G · M² · R⁵ · n · e² / a⁶` with the same k2/Q for all bodies
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+pub fn tidal_heating(object: &Object, parent_mass: f64) -> f64 {
+    let orbit = &object.orbital_params;
+    if orbit.semi_major_axis == 0.0 {
+        return 0.0;
+    }
+    let mean_motion = orbit.mean_motion(parent_mass).to_radians();
+    let power = 10.5 * LOVE_NUMBER_RATIO * GRAVITATIONAL_CONSTANT * parent_mass.powi(2) * object.radius.powi(5)
+        * mean_motion * orbit.eccentricity.powi(2) / orbit.semi_major_axis.powi(6);
+    // kg km² s⁻³ to W
+    power * 1e6
+}
+
+/// Thermal state of a body from the tides raised by its parent
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+pub fn thermal_state(object: &Object, parent_mass: f64) -> ThermalState {
+    let tidal_heating = tidal_heating(object, parent_mass);
+    let surface_area = 4.0 * PI * (object.radius * 1000.0).powi(2);
+    let heat_flux = if surface_area > 0.0 { tidal_heating / surface_area } else { 0.0 };
+    let class = if heat_flux >= VOLCANIC_HEAT_FLUX {
+        ThermalClass::Volcanic
+    } else if heat_flux >= SUBSURFACE_OCEAN_HEAT_FLUX {
+        ThermalClass::SubsurfaceOcean
+    } else {
+        ThermalClass::Inert
+    };
+    ThermalState { tidal_heating, heat_flux, class }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+    use crate::celestial::ObjectType;
+    use crate::orbital::OrbitalParameters;
+
+    const JUPITER_MASS: f64 = 1.898e27;
+
+    fn moon(radius: f64, semi_major_axis: f64, eccentricity: f64) -> Object {
+        Object {
+            name: String::new(),
+            object_type: ObjectType::Rocky,
+            mass: 1e22,
+            radius,
+            orbital_params: OrbitalParameters { semi_major_axis, eccentricity, longitude_of_periapsis: 0, mean_anomaly: 0.0 },
+            atmosphere: HashMap::new(),
+            scale_height: None,
+            rotation: None,
+            icon: None,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_galilean_moons() {
+        let io = thermal_state(&moon(1821.6, 421700.0, 0.0041), JUPITER_MASS);
+        // Io radiates about 1e14 W
+        assert!((5e13..2e14).contains(&io.tidal_heating), "{}", io.tidal_heating);
+        assert_eq!(io.class, ThermalClass::Volcanic);
+        assert_eq!(thermal_state(&moon(1560.8, 671034.0, 0.009), JUPITER_MASS).class, ThermalClass::SubsurfaceOcean);
+        assert_eq!(thermal_state(&moon(1560.8, 671034.0, 0.0), JUPITER_MASS).class, ThermalClass::Inert);
+    }
+}
@@ -0,0 +1,130 @@
+use std::f64::consts::PI;
+use crate::orbital::{OrbitalParameters, GRAVITATIONAL_CONSTANT};
+
+#[derive(Debug, Clone, PartialEq)]
+/// A Hohmann transfer between the orbits of two objects around the same parent
+pub struct HohmannTransfer {
+    /// Delta-v needed to leave the origin orbit in km/s
+    pub departure_delta_v: f64,
+    /// Delta-v needed to circularise at the target orbit in km/s
+    pub arrival_delta_v: f64,
+    /// Time of flight in seconds
+    pub transfer_time: f64,
+    /// Angle in degrees the target has to lead the origin by at departure
+    pub phase_angle: f64,
+}
+
+impl HohmannTransfer {
+    /// Combined delta-v of both burns in km/s
+    pub fn total_delta_v(&self) -> f64 {
+        self.departure_delta_v + self.arrival_delta_v
+    }
+}
+
+/// Calculates a Hohmann transfer between two orbits around the same parent,
+/// both orbits are treated as circular with their semi-major axis as radius
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+pub fn hohmann(origin: &OrbitalParameters, target: &OrbitalParameters, parent_mass: f64) -> HohmannTransfer {
+    let mu = GRAVITATIONAL_CONSTANT * parent_mass;
+    let (r1, r2) = (origin.semi_major_axis, target.semi_major_axis);
+    let transfer_semi_major_axis = (r1 + r2) / 2.0;
+
+    let departure_delta_v = ((mu / r1).sqrt() * ((2.0 * r2 / (r1 + r2)).sqrt() - 1.0)).abs();
+    let arrival_delta_v = ((mu / r2).sqrt() * (1.0 - (2.0 * r1 / (r1 + r2)).sqrt())).abs();
+    let transfer_time = PI * (transfer_semi_major_axis.powi(3) / mu).sqrt();
+    // The target has to arrive at the point opposite of the departure after the transfer time
+    let phase_angle = (180.0 - target.mean_motion(parent_mass) * transfer_time).rem_euclid(360.0);
+
+    HohmannTransfer { departure_delta_v, arrival_delta_v, transfer_time, phase_angle }
+}
+
+/// Current angle in degrees the target leads the origin by, in [0, 360)
+pub fn phase_angle(origin: &OrbitalParameters, target: &OrbitalParameters) -> f64 {
+    (target.true_longitude() - origin.true_longitude()).rem_euclid(360.0)
+}
+
+/// Time in seconds until two objects around the same parent return to the same relative position
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+pub fn synodic_period(origin: &OrbitalParameters, target: &OrbitalParameters, parent_mass: f64) -> f64 {
+    360.0 / (origin.mean_motion(parent_mass) - target.mean_motion(parent_mass)).abs()
+}
+
+/// Finds all times in seconds between `start` and `end` at which the phase angle between origin and target
+/// passes `angle` degrees, sampled every `resolution` seconds and refined by bisection
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+pub fn phase_angle_crossings(origin: &OrbitalParameters, target: &OrbitalParameters, parent_mass: f64,
+                             angle: f64, start: f64, end: f64, resolution: f64) -> Vec<f64> {
+    // Signed difference to the wanted angle in (-180, 180]
+    let offset = |seconds: f64| {
+        let phase = phase_angle(&origin.state_at(seconds, parent_mass), &target.state_at(seconds, parent_mass));
+        180.0 - (180.0 - (phase - angle)).rem_euclid(360.0)
+    };
+
+    let mut crossings = Vec::new();
+    let mut previous = (start, offset(start));
+    let mut time = start;
+    while time < end {
+        time = (time + resolution).min(end);
+        let current = (time, offset(time));
+        // A sign change across the ±180° wrap is the opposite alignment, not a crossing
+        if previous.1.signum() != current.1.signum() && (previous.1 - current.1).abs() < 180.0 {
+            let (mut low, mut high) = (previous, current);
+            for _ in 0..60 {
+                let middle_time = (low.0 + high.0) / 2.0;
+                let middle = (middle_time, offset(middle_time));
+                if middle.1.signum() == low.1.signum() {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            crossings.push((low.0 + high.0) / 2.0);
+        }
+        previous = current;
+    }
+    crossings
+}
+
+/// Finds the departure times in seconds of Hohmann transfer windows between `start` and `end`
+///
+/// * `parent_mass` - Mass of the orbited object in kg
+pub fn transfer_windows(origin: &OrbitalParameters, target: &OrbitalParameters, parent_mass: f64,
+                        start: f64, end: f64, resolution: f64) -> Vec<f64> {
+    let transfer = hohmann(origin, target, parent_mass);
+    phase_angle_crossings(origin, target, parent_mass, transfer.phase_angle, start, end, resolution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOLAR_MASS: f64 = 1.988416e30;
+
+    fn circular(semi_major_axis: f64, mean_anomaly: f64) -> OrbitalParameters {
+        OrbitalParameters { semi_major_axis, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly }
+    }
+
+    #[test]
+    fn test_earth_mars_hohmann() {
+        let transfer = hohmann(&circular(149597870.0, 0.0), &circular(227939200.0, 0.0), SOLAR_MASS);
+        assert!((transfer.departure_delta_v - 2.94).abs() < 0.01);
+        assert!((transfer.arrival_delta_v - 2.65).abs() < 0.01);
+        assert!((transfer.transfer_time / 86400.0 - 259.0).abs() < 1.0);
+        assert!((transfer.phase_angle - 44.3).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_windows_repeat_with_synodic_period() {
+        let (earth, mars) = (circular(149597870.0, 0.0), circular(227939200.0, 90.0));
+        let synodic = synodic_period(&earth, &mars, SOLAR_MASS);
+        let windows = transfer_windows(&earth, &mars, SOLAR_MASS, 0.0, 3.0 * synodic, 86400.0);
+        assert_eq!(windows.len(), 3);
+        assert!((windows[1] - windows[0] - synodic).abs() < 60.0);
+        let transfer = hohmann(&earth, &mars, SOLAR_MASS);
+        let phase = phase_angle(&earth.state_at(windows[0], SOLAR_MASS), &mars.state_at(windows[0], SOLAR_MASS));
+        assert!((phase - transfer.phase_angle).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+use crate::aerobraking::Atmosphere;
+use crate::celestial::Object;
+use crate::roche::rigid_roche_limit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How severe a validation issue is
+pub enum Severity {
+    /// The data is unusable or physically impossible
+    Error,
+    /// The data is usable but likely not what the author intended
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A single problem found while validating a system
+pub struct Issue {
+    pub severity: Severity,
+    /// Slash separated path of the offending object from the root, e.g. "Sol/Earth/Luna"
+    pub path: String,
+    pub message: String,
+}
+
+impl Issue {
+    pub fn error(path: &str, message: impl Into<String>) -> Issue {
+        Issue { severity: Severity::Error, path: path.to_string(), message: message.into() }
+    }
+
+    pub fn warning(path: &str, message: impl Into<String>) -> Issue {
+        Issue { severity: Severity::Warning, path: path.to_string(), message: message.into() }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", severity, self.path, self.message)
+    }
+}
+
+/// Runs the physics validation pass over an object and all of its children
+pub fn validate(object: &Object) -> Vec<Issue> {
+    let _span = tracing::debug_span!("validate", system = %object.name).entered();
+    let mut issues = Vec::new();
+    validate_object(object, None, &object.name, &mut issues);
+    for issue in &issues {
+        match issue.severity {
+            Severity::Error => tracing::error!(path = %issue.path, "{}", issue.message),
+            Severity::Warning => tracing::warn!(path = %issue.path, "{}", issue.message),
+        }
+    }
+    issues
+}
+
+fn validate_object(object: &Object, parent: Option<&Object>, path: &str, issues: &mut Vec<Issue>) {
+    let orbit = &object.orbital_params;
+
+    if object.mass <= 0.0 {
+        issues.push(Issue::error(path, format!("mass must be positive, got {}", object.mass)));
+    }
+    if object.radius <= 0.0 {
+        issues.push(Issue::error(path, format!("radius must be positive, got {}", object.radius)));
+    }
+    if orbit.eccentricity < 0.0 {
+        issues.push(Issue::error(path, format!("eccentricity must not be negative, got {}", orbit.eccentricity)));
+    } else if orbit.eccentricity >= 1.0 {
+        issues.push(Issue::warning(path, format!("eccentricity {} is not a closed orbit, propagation assumes elliptical orbits", orbit.eccentricity)));
+    }
+    if !(0.0..360.0).contains(&orbit.mean_anomaly) {
+        issues.push(Issue::warning(path, format!("mean anomaly {} is outside of [0, 360)", orbit.mean_anomaly)));
+    }
+    for (gas, pressure) in &object.atmosphere {
+        if *pressure < 0.0 {
+            issues.push(Issue::error(path, format!("partial pressure of {} must not be negative, got {}", gas, pressure)));
+        }
+    }
+
+    if let Some(parent) = parent {
+        if orbit.semi_major_axis <= 0.0 {
+            issues.push(Issue::error(path, "orbiting object has no semi-major-axis"));
+        } else {
+            let periapsis = orbit.periapsis();
+            if periapsis <= parent.radius {
+                issues.push(Issue::error(path, format!("periapsis of {:.0} km lies inside of {} (radius {} km)", periapsis, parent.name, parent.radius)));
+            } else if let Some(atmosphere) = Atmosphere::of(parent).filter(|atmosphere| periapsis - parent.radius < atmosphere.top()) {
+                issues.push(Issue::warning(path, format!("periapsis at {:.0} km altitude lies inside the atmosphere of {} (up to {:.0} km), the orbit will decay",
+                                                         periapsis - parent.radius, parent.name, atmosphere.top())));
+            }
+            // Small moons hold together by their own strength well inside the fluid limit, only the rigid limit is a hard bound
+            let roche_limit = rigid_roche_limit(parent, object);
+            if periapsis > parent.radius && periapsis < roche_limit {
+                issues.push(Issue::warning(path, format!("periapsis of {:.0} km lies inside the rigid Roche limit of {} ({:.0} km), the tides would tear it apart",
+                                                         periapsis, parent.name, roche_limit)));
+            }
+        }
+        if object.mass > parent.mass {
+            issues.push(Issue::warning(path, format!("object is more massive than its parent {}", parent.name)));
+        }
+    }
+
+    for child in &object.children {
+        validate_object(child, Some(object), &format!("{}/{}", path, child.name), issues);
+    }
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod tests {
+    use super::*;
+    use crate::yaml_parser::load_yaml_str;
+
+    #[test]
+    fn test_valid_system_has_no_errors() {
+        let system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        assert!(validate(&system).iter().all(|issue| issue.severity != Severity::Error));
+    }
+
+    #[test]
+    fn test_orbit_inside_parent() {
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        system.children[0].children[0].orbital_params.semi_major_axis = 1000.0;
+        let issues = validate(&system);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].path, "Sol/Earth/Luna");
+    }
+
+    #[test]
+    fn test_orbit_inside_atmosphere() {
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        system.children[0].children[0].orbital_params.semi_major_axis = 6371.0 + 100.0;
+        system.children[0].children[0].orbital_params.eccentricity = 0.0;
+        let issues = validate(&system);
+        assert!(issues.iter().any(|issue| issue.message.contains("atmosphere")));
+        assert!(issues.iter().all(|issue| issue.severity == Severity::Warning));
+    }
+
+    #[test]
+    fn test_orbit_inside_roche_limit() {
+        let mut system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        system.children[0].children[0].orbital_params.semi_major_axis = 9_000.0;
+        system.children[0].children[0].orbital_params.eccentricity = 0.0;
+        let issues = validate(&system);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert!(issues[0].message.contains("Roche limit"));
+    }
+}
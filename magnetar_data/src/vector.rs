@@ -0,0 +1,134 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+/// Double precision 3D vector used for all positions and velocities, so the physics stays in f64 end to end.
+/// Orbits currently lie in the parents orbital plane, so `z` is always 0 for propagated values.
+///
+/// Mirrors the parts of glam's `DVec3` the physics needs, renderers should only downcast with [`DVec3::as_f32`].
+pub struct DVec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl DVec3 {
+    pub const ZERO: DVec3 = DVec3::new(0.0, 0.0, 0.0);
+
+    pub const fn new(x: f64, y: f64, z: f64) -> DVec3 {
+        DVec3 { x, y, z }
+    }
+
+    pub fn dot(self, other: DVec3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: DVec3) -> DVec3 {
+        DVec3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length_squared(self) -> f64 {
+        self.dot(self)
+    }
+
+    pub fn length(self) -> f64 {
+        self.length_squared().sqrt()
+    }
+
+    pub fn distance(self, other: DVec3) -> f64 {
+        (self - other).length()
+    }
+
+    /// Vector with the same direction and a length of 1, the zero vector stays zero
+    pub fn normalize_or_zero(self) -> DVec3 {
+        let length = self.length();
+        if length == 0.0 { DVec3::ZERO } else { self / length }
+    }
+
+    /// Lossy conversion for rendering, e.g. into a bevy `Vec3` with `Vec3::from_array`
+    pub fn as_f32(self) -> [f32; 3] {
+        [self.x as f32, self.y as f32, self.z as f32]
+    }
+
+    pub fn to_array(self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+}
+
+impl From<[f64; 3]> for DVec3 {
+    fn from([x, y, z]: [f64; 3]) -> DVec3 {
+        DVec3::new(x, y, z)
+    }
+}
+
+impl From<DVec3> for [f64; 3] {
+    fn from(vector: DVec3) -> [f64; 3] {
+        vector.to_array()
+    }
+}
+
+impl Add for DVec3 {
+    type Output = DVec3;
+
+    fn add(self, other: DVec3) -> DVec3 {
+        DVec3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl AddAssign for DVec3 {
+    fn add_assign(&mut self, other: DVec3) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for DVec3 {
+    type Output = DVec3;
+
+    fn sub(self, other: DVec3) -> DVec3 {
+        DVec3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl SubAssign for DVec3 {
+    fn sub_assign(&mut self, other: DVec3) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<f64> for DVec3 {
+    type Output = DVec3;
+
+    fn mul(self, factor: f64) -> DVec3 {
+        DVec3::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl Div<f64> for DVec3 {
+    type Output = DVec3;
+
+    fn div(self, divisor: f64) -> DVec3 {
+        DVec3::new(self.x / divisor, self.y / divisor, self.z / divisor)
+    }
+}
+
+impl Neg for DVec3 {
+    type Output = DVec3;
+
+    fn neg(self) -> DVec3 {
+        DVec3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl fmt::Display for DVec3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
@@ -1,12 +1,91 @@
 // yaml_parser.rs
 use serde_yaml;
+#[cfg(feature = "fs")]
 use std::fs;
 use std::collections::HashMap;
-use crate::celestial::{Object, ObjectType, OrbitalParameters};
+use std::io::Read;
+#[cfg(feature = "fs")]
+use std::time::Instant;
+use serde::de::{Deserialize, Deserializer, Error};
+use crate::celestial::{Object, ObjectType};
+use crate::orbital::OrbitalParameters;
+use crate::surface::Rotation;
+use crate::validation::Issue;
 
+/// Keys understood by the parser on an object definition
+const KNOWN_KEYS: [&str; 13] = [
+    "type", "mass", "radius", "semi-major-axis", "eccentricity", "longitude-of-periapsis", "mean-anomaly",
+    "atmosphere", "scale-height", "rotation-period", "prime-meridian", "icon", "parentTo",
+];
+/// Keys which all need to be present for an object to have an orbit
+const ORBIT_KEYS: [&str; 4] = ["semi-major-axis", "eccentricity", "longitude-of-periapsis", "mean-anomaly"];
+
+#[cfg(feature = "fs")]
 pub fn load_yaml(file_path: &str) -> Result<Object, String> {
-    let file_content = fs::read_to_string(file_path).map_err(|e| format!("Failed to read file: {}", e))?;
-    let parsed_yaml: HashMap<String, serde_yaml::Value> = serde_yaml::from_str(&file_content).map_err(|e| format!("Failed to parse YAML: {}", e))?;
+    let _span = tracing::info_span!("load_yaml", file_path).entered();
+    let start = Instant::now();
+    let object = load_yaml_str(&read_file(file_path)?)?;
+    tracing::debug!(objects = object.iter().count(), elapsed = ?start.elapsed(), "loaded system");
+    Ok(object)
+}
+
+/// Parses a system from the contents of a YAML file
+pub fn load_yaml_str(content: &str) -> Result<Object, String> {
+    let (name, value) = parse_root(content)?;
+    parse_object(name, value).inspect_err(|e| tracing::error!(error = %e, "failed to parse system"))
+}
+
+/// Parses a system from a reader over the contents of a YAML file
+pub fn load_yaml_reader(mut reader: impl Read) -> Result<Object, String> {
+    let mut content = String::new();
+    reader.read_to_string(&mut content).map_err(|e| format!("Failed to read YAML: {}", e))?;
+    load_yaml_str(&content)
+}
+
+/// Checks the schema of a YAML file without building the object tree, reporting every problem found
+/// instead of stopping at the first one. Only fails if the file cannot be read or has no root object.
+#[cfg(feature = "fs")]
+pub fn check_yaml(file_path: &str) -> Result<Vec<Issue>, String> {
+    let _span = tracing::info_span!("check_yaml", file_path).entered();
+    let start = Instant::now();
+    let issues = check_yaml_str(&read_file(file_path)?)?;
+    tracing::debug!(issues = issues.len(), elapsed = ?start.elapsed(), "checked schema");
+    Ok(issues)
+}
+
+/// Checks the schema of the contents of a YAML file, see `check_yaml`
+pub fn check_yaml_str(content: &str) -> Result<Vec<Issue>, String> {
+    let (name, value) = parse_root(content)?;
+    let mut issues = Vec::new();
+    check_object(&name, &value, &mut issues);
+    Ok(issues)
+}
+
+/// Serializes a system into the same format `load_yaml` reads, wrapped in a StarSystem definition
+pub fn to_yaml(system: &Object) -> Result<String, String> {
+    let wrapped = HashMap::from([("StarSystem", system)]);
+    serde_yaml::to_string(&wrapped).map_err(|e| format!("Failed to serialize YAML: {}", e))
+}
+
+impl<'de> Deserialize<'de> for Object {
+    /// Deserializes the single entry map of name to definition written by `Serialize`, from any format
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Object, D::Error> {
+        let mut entries = HashMap::<String, serde_yaml::Value>::deserialize(deserializer)?.into_iter();
+        match (entries.next(), entries.next()) {
+            (Some((name, value)), None) => parse_object(name, value).map_err(D::Error::custom),
+            _ => Err(D::Error::custom("Expected a single object")),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+fn read_file(file_path: &str) -> Result<String, String> {
+    fs::read_to_string(file_path).map_err(|e| format!("Failed to read file: {}", e))
+}
+
+/// Parses the contents of a YAML file and returns the name and definition of its root object
+fn parse_root(content: &str) -> Result<(String, serde_yaml::Value), String> {
+    let parsed_yaml: HashMap<String, serde_yaml::Value> = serde_yaml::from_str(content).map_err(|e| format!("Failed to parse YAML: {}", e))?;
 
     // Handle the special "StarSystem" case
     if let Some(star_system) = parsed_yaml.get("StarSystem") {
@@ -14,17 +93,108 @@ pub fn load_yaml(file_path: &str) -> Result<Object, String> {
             if let Some((system_name, system_value)) = system_mapping.iter().next() {
                 // StarSystem's first child element (e.g., "Sol")
                 let name = system_name.as_str().unwrap_or("Unnamed").to_owned();
-                return parse_object(name, system_value.clone());
+                return Ok((name, system_value.clone()));
             }
         }
         return Err("Malformed StarSystem definition".to_string());
     }
 
     // Default fallback: Parse as single object
-    if let Some((name, value)) = parsed_yaml.into_iter().next() {
-        parse_object(name, value)
-    } else {
-        Err("No valid object found in YAML".to_string())
+    parsed_yaml.into_iter().next().ok_or("No valid object found in YAML".to_string())
+}
+
+fn parse_object_type(obj_type: &str) -> Option<ObjectType> {
+    match obj_type {
+        "STAR" => Some(ObjectType::Star),
+        "ROCKY" => Some(ObjectType::Rocky),
+        "JOVIAN" => Some(ObjectType::Jovian),
+        "ICE_GIANT" => Some(ObjectType::IceGiant),
+        _ => None,
+    }
+}
+
+fn check_object(path: &str, value: &serde_yaml::Value, issues: &mut Vec<Issue>) {
+    let Some(mapping) = value.as_mapping() else {
+        issues.push(Issue::error(path, "object definition must be a mapping"));
+        return;
+    };
+
+    match value.get("type").map(|v| v.as_str()) {
+        None => issues.push(Issue::error(path, "missing object type")),
+        Some(None) => issues.push(Issue::error(path, "object type must be a string")),
+        Some(Some(obj_type)) if parse_object_type(obj_type).is_none() => {
+            issues.push(Issue::error(path, format!("invalid object type {}", obj_type)));
+        }
+        _ => {}
+    }
+    for key in ["mass", "radius"] {
+        match value.get(key) {
+            None => issues.push(Issue::error(path, format!("missing {}", key))),
+            Some(v) if v.as_f64().is_none() => issues.push(Issue::error(path, format!("{} must be a number", key))),
+            _ => {}
+        }
+    }
+
+    let missing_orbit_keys: Vec<&str> = ORBIT_KEYS.iter().copied().filter(|key| value.get(key).is_none()).collect();
+    if !missing_orbit_keys.is_empty() && missing_orbit_keys.len() < ORBIT_KEYS.len() {
+        issues.push(Issue::warning(path, format!("incomplete orbit, missing {}, the object will not move", missing_orbit_keys.join(", "))));
+    }
+    for key in ORBIT_KEYS {
+        if value.get(key).is_some_and(|v| v.as_f64().is_none()) {
+            issues.push(Issue::error(path, format!("{} must be a number", key)));
+        }
+    }
+
+    for key in mapping.keys() {
+        match key.as_str() {
+            Some(key) if KNOWN_KEYS.contains(&key) => {}
+            Some(key) => issues.push(Issue::warning(path, format!("unknown key {}", key))),
+            None => issues.push(Issue::warning(path, "non string key")),
+        }
+    }
+
+    if let Some(atmosphere) = value.get("atmosphere") {
+        match atmosphere.as_mapping() {
+            Some(gases) => {
+                for (gas, pressure) in gases {
+                    if pressure.as_f64().is_none() {
+                        issues.push(Issue::warning(path, format!("partial pressure of {} is not a number and will be ignored", gas.as_str().unwrap_or("?"))));
+                    }
+                }
+            }
+            None => issues.push(Issue::warning(path, "atmosphere must be a mapping of gas to partial pressure and will be ignored")),
+        }
+    }
+
+    if value.get("scale-height").is_some_and(|v| v.as_f64().is_none()) {
+        issues.push(Issue::error(path, "scale-height must be a number"));
+    }
+    for key in ["rotation-period", "prime-meridian"] {
+        if value.get(key).is_some_and(|v| v.as_f64().is_none()) {
+            issues.push(Issue::error(path, format!("{} must be a number", key)));
+        }
+    }
+    if value.get("prime-meridian").is_some() && value.get("rotation-period").is_none() {
+        issues.push(Issue::warning(path, "prime-meridian without a rotation-period will be ignored"));
+    }
+    if value.get("icon").is_some_and(|v| v.as_str().is_none()) {
+        issues.push(Issue::warning(path, "icon must be the name of an icon and will be ignored"));
+    }
+
+    if let Some(children) = value.get("parentTo") {
+        let Some(children) = children.as_sequence() else {
+            issues.push(Issue::error(path, "parentTo must be a list of objects"));
+            return;
+        };
+        for child in children {
+            match child.as_mapping().and_then(|map| map.iter().next()) {
+                Some((child_name, child_value)) => {
+                    let child_name = child_name.as_str().unwrap_or("Unnamed");
+                    check_object(&format!("{}/{}", path, child_name), child_value, issues);
+                }
+                None => issues.push(Issue::error(path, "child entries must be a mapping of name to definition")),
+            }
+        }
     }
 }
 
@@ -58,13 +228,23 @@ fn parse_object(name: String, value: serde_yaml::Value) -> Result<Object, String
                 .collect::<HashMap<String, f64>>() // Collect explicitly into HashMap<String, f64>
         })
         .unwrap_or_default(); // Fallback to empty HashMap if "atmosphere" is missing or invalid
+    let scale_height = value.get("scale-height").and_then(|v| v.as_f64());
+    let rotation = value.get("rotation-period").and_then(|v| v.as_f64()).map(|period| Rotation {
+        period,
+        prime_meridian: value.get("prime-meridian").and_then(|v| v.as_f64()).unwrap_or(0.0),
+    });
+    let icon = value.get("icon").and_then(|v| v.as_str()).map(str::to_string);
 
 
     let children = value.get("parentTo").and_then(|v| v.as_sequence()).map(|seq| {
         seq.iter()
             .filter_map(|child| child.as_mapping().and_then(|map| {
                 if let Some((child_name, child_value)) = map.iter().next() {
-                    child_name.as_str().map(|cn| parse_object(cn.to_string(), child_value.clone()).ok()).flatten()
+                    child_name.as_str().and_then(|cn| {
+                        parse_object(cn.to_string(), child_value.clone())
+                            .inspect_err(|e| tracing::warn!(parent = %name, child = cn, error = %e, "skipping malformed child"))
+                            .ok()
+                    })
                 } else {
                     None
                 }
@@ -74,13 +254,7 @@ fn parse_object(name: String, value: serde_yaml::Value) -> Result<Object, String
 
     Ok(Object {
         name,
-        object_type: match obj_type {
-            "STAR" => ObjectType::Star,
-            "ROCKY" => ObjectType::Rocky,
-            "JOVIAN" => ObjectType::Jovian,
-            "ICE_GIANT" => ObjectType::IceGiant,
-            _ => return Err("Invalid object type".to_string()),
-        },
+        object_type: parse_object_type(obj_type).ok_or("Invalid object type".to_string())?,
         mass,
         radius,
         orbital_params: orbital_params.unwrap_or(OrbitalParameters {
@@ -90,6 +264,9 @@ fn parse_object(name: String, value: serde_yaml::Value) -> Result<Object, String
             mean_anomaly: 0.0,
         }),
         atmosphere,
+        scale_height,
+        rotation,
+        icon,
         children: children.unwrap_or_default(),
     })
 }
@@ -98,15 +275,40 @@ fn parse_object(name: String, value: serde_yaml::Value) -> Result<Object, String
 mod tests {
     use super::*;
 
+    #[cfg(feature = "fs")]
     #[test]
     fn test_valid_yaml() {
         let result = load_yaml("test_data/valid.yaml");
         assert!(result.is_ok());
     }
 
+    #[cfg(feature = "fs")]
     #[test]
     fn test_invalid_yaml() {
         let result = load_yaml("test_data/invalid.yaml");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_load_from_reader() {
+        let from_str = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let from_reader = load_yaml_reader(include_bytes!("../test_data/valid.yaml").as_slice()).unwrap();
+        assert_eq!(from_str, from_reader);
+    }
+
+    #[test]
+    fn test_yaml_round_trip() {
+        let system = load_yaml_str(include_str!("../test_data/valid.yaml")).unwrap();
+        let reloaded = load_yaml_str(&to_yaml(&system).unwrap()).unwrap();
+        assert_eq!(system, reloaded);
+        assert!(system.approx_eq(&reloaded.state_at(1e-5), 1e-9));
+        let earth = system.find("Earth").unwrap();
+        assert_eq!(&serde_yaml::from_str::<Object>(&serde_yaml::to_string(earth).unwrap()).unwrap(), earth);
+    }
+
+    #[test]
+    fn test_check_invalid_yaml() {
+        let issues = check_yaml_str(include_str!("../test_data/invalid.yaml")).unwrap();
+        assert_eq!(issues, vec![Issue::error("Sol", "invalid object type NEUTRON_STAR")]);
+    }
 }
@@ -0,0 +1,72 @@
+use magnetar_data::orbital::GRAVITATIONAL_CONSTANT;
+use magnetar_data::prelude::OrbitalParameters;
+use proptest::prelude::*;
+
+fn orbit() -> impl Strategy<Value = OrbitalParameters> {
+    (1.0e3..1.0e9f64, 0.0..0.95f64, 0..360u16, 0.0..360.0f64).prop_map(
+        |(semi_major_axis, eccentricity, longitude_of_periapsis, mean_anomaly)| OrbitalParameters {
+            semi_major_axis,
+            eccentricity,
+            longitude_of_periapsis,
+            mean_anomaly,
+        },
+    )
+}
+
+fn parent_mass() -> impl Strategy<Value = f64> {
+    1.0e20..1.0e31f64
+}
+
+fn seconds() -> impl Strategy<Value = f64> {
+    -1.0e9..1.0e9f64
+}
+
+/// Smallest difference between two angles in degrees
+fn angle_difference(a: f64, b: f64) -> f64 {
+    let difference = (a - b).rem_euclid(360.0);
+    difference.min(360.0 - difference)
+}
+
+/// Specific orbital energy in km²/s²
+fn specific_energy(orbit: &OrbitalParameters, parent_mass: f64) -> f64 {
+    orbit.velocity(parent_mass).length_squared() / 2.0 - GRAVITATIONAL_CONSTANT * parent_mass / orbit.position().length()
+}
+
+proptest! {
+    #[test]
+    fn mean_anomaly_stays_in_range(orbit in orbit(), parent_mass in parent_mass(), seconds in seconds()) {
+        let anomaly = orbit.state_at(seconds, parent_mass).mean_anomaly;
+        prop_assert!((0.0..360.0).contains(&anomaly), "{}", anomaly);
+    }
+
+    #[test]
+    fn stepping_is_additive(orbit in orbit(), parent_mass in parent_mass(), t1 in seconds(), t2 in seconds()) {
+        let mut stepped = orbit.clone();
+        stepped.step_by(t1, parent_mass);
+        stepped.step_by(t2, parent_mass);
+        let direct = orbit.state_at(t1 + t2, parent_mass);
+        // Rounding grows with the number of degrees travelled
+        let travelled = orbit.mean_motion(parent_mass) * (t1.abs() + t2.abs());
+        let tolerance = 1e-9 * (1.0 + travelled);
+        prop_assert!(angle_difference(stepped.mean_anomaly, direct.mean_anomaly) <= tolerance);
+    }
+
+    #[test]
+    fn radius_stays_between_apsides(orbit in orbit(), parent_mass in parent_mass(), seconds in seconds()) {
+        let state = orbit.state_at(seconds, parent_mass);
+        let (periapsis, apoapsis) = (orbit.periapsis(), orbit.apoapsis());
+        let radius = state.radius();
+        prop_assert!(radius >= periapsis * (1.0 - 1e-12) && radius <= apoapsis * (1.0 + 1e-12),
+                     "{} not in [{}, {}]", radius, periapsis, apoapsis);
+        prop_assert!((state.position().length() - radius).abs() <= radius * 1e-12);
+    }
+
+    #[test]
+    fn energy_is_conserved(orbit in orbit(), parent_mass in parent_mass(), seconds in seconds()) {
+        let expected = -GRAVITATIONAL_CONSTANT * parent_mass / (2.0 * orbit.semi_major_axis);
+        let before = specific_energy(&orbit, parent_mass);
+        let after = specific_energy(&orbit.state_at(seconds, parent_mass), parent_mass);
+        prop_assert!((before - expected).abs() <= expected.abs() * 1e-9, "{} != {}", before, expected);
+        prop_assert!((after - before).abs() <= expected.abs() * 1e-9, "{} != {}", after, before);
+    }
+}
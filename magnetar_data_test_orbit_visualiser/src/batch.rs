@@ -0,0 +1,131 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use clap::ValueEnum;
+use serde::Serialize;
+use magnetar_data::prelude::Object;
+use crate::parse_signed_timestep;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+/// Machine readable output formats for batch mode
+pub enum OutputFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A sequence of equally sized steps, parsed from e.g. "365x1d"
+pub struct StepSpec {
+    pub count: usize,
+    /// Signed size of a single step in seconds
+    pub seconds: f64,
+}
+
+/// Parse a step sequence like "365x1d" or "10x-6h", a plain timestep like "1d" is a single step.
+pub fn parse_step_spec(input: &str) -> Option<StepSpec> {
+    let (count, step) = match input.split_once('x') {
+        Some((count, step)) => (count.parse().ok()?, step),
+        None => (1, input),
+    };
+    Some(StepSpec { count, seconds: parse_signed_timestep(step)? })
+}
+
+#[derive(Serialize)]
+/// State of a single body at one step
+struct BodyState {
+    /// Slash separated path from the root, e.g. "Sol/Earth/Luna"
+    path: String,
+    semi_major_axis: f64,
+    eccentricity: f64,
+    longitude_of_periapsis: u16,
+    mean_anomaly: f64,
+}
+
+#[derive(Serialize)]
+/// State of the whole system at one step
+struct StepRecord {
+    step: usize,
+    /// Offset from the epoch in seconds
+    sim_time: f64,
+    bodies: Vec<BodyState>,
+}
+
+/// Run all steps of `spec` against `epoch` and write every body's state per step to `out`,
+/// the initial state is written as step 0.
+pub fn run(epoch: &Object, spec: &StepSpec, format: OutputFormat, out: Option<&str>) -> io::Result<()> {
+    let mut writer: Box<dyn Write> = match out {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let _span = tracing::info_span!("batch", steps = spec.count, step_seconds = spec.seconds).entered();
+    let start = std::time::Instant::now();
+    let records: Vec<StepRecord> = (0..=spec.count)
+        .map(|step| {
+            // Avoid writing -0 for the initial state of backwards sequences
+            let sim_time = if step == 0 { 0.0 } else { step as f64 * spec.seconds };
+            StepRecord { step, sim_time, bodies: collect_states(&epoch.state_at(sim_time)) }
+        })
+        .collect();
+    tracing::debug!(elapsed = ?start.elapsed(), "propagated all steps");
+
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, &records).map_err(io::Error::other)?;
+            writeln!(writer)?;
+        }
+        OutputFormat::Csv => {
+            writeln!(writer, "step,sim_time,path,semi_major_axis,eccentricity,longitude_of_periapsis,mean_anomaly")?;
+            for record in &records {
+                for body in &record.bodies {
+                    writeln!(writer, "{},{},{},{},{},{},{}",
+                             record.step, record.sim_time, csv_field(&body.path), body.semi_major_axis,
+                             body.eccentricity, body.longitude_of_periapsis, body.mean_anomaly)?;
+                }
+            }
+        }
+    }
+    writer.flush()
+}
+
+/// Walk the tree depth first and collect the state of every body.
+fn collect_states(system: &Object) -> Vec<BodyState> {
+    // Names of the current object's ancestors, indexed by depth
+    let mut ancestors: Vec<&str> = Vec::new();
+    system.iter()
+        .map(|item| {
+            ancestors.truncate(item.depth);
+            ancestors.push(&item.object.name);
+            let orbit = &item.object.orbital_params;
+            BodyState {
+                path: ancestors.join("/"),
+                semi_major_axis: orbit.semi_major_axis,
+                eccentricity: orbit.eccentricity,
+                longitude_of_periapsis: orbit.longitude_of_periapsis,
+                mean_anomaly: orbit.mean_anomaly,
+            }
+        })
+        .collect()
+}
+
+/// Quote a CSV field if it contains a separator or quote.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_step_spec() {
+        assert_eq!(parse_step_spec("365x1d"), Some(StepSpec { count: 365, seconds: 86400.0 }));
+        assert_eq!(parse_step_spec("10x-6h"), Some(StepSpec { count: 10, seconds: -21600.0 }));
+        assert_eq!(parse_step_spec("2h"), Some(StepSpec { count: 1, seconds: 7200.0 }));
+        assert_eq!(parse_step_spec("ax1d"), None);
+        assert_eq!(parse_step_spec("10x"), None);
+    }
+}
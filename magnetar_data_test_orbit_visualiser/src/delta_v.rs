@@ -0,0 +1,32 @@
+use std::io::{self, Write};
+use magnetar_data::delta_v::DeltaVMap;
+use magnetar_data::prelude::{Object, SystemStore};
+use crate::ephemeris::TableFormat;
+
+/// Print the delta-v in km/s between low orbits around all planets and moons, departures in rows
+pub fn run(system: &Object, format: TableFormat) -> Result<(), String> {
+    let store = SystemStore::new(system.clone());
+    let map = DeltaVMap::compute(&store);
+    let mut out = io::stdout().lock();
+    let write_error = |e: io::Error| e.to_string();
+    match format {
+        TableFormat::Csv => map.write_csv(&store, &mut out).map_err(write_error),
+        TableFormat::Text => {
+            let name = |id| store.get(id).map_or("", |object| object.name.as_str());
+            let width = map.bodies().iter().map(|id| name(*id).chars().count()).max().unwrap_or(0).max(8);
+            write!(out, "{:width$}", "").map_err(write_error)?;
+            for &to in map.bodies() {
+                write!(out, " {:>width$}", name(to)).map_err(write_error)?;
+            }
+            writeln!(out).map_err(write_error)?;
+            for &from in map.bodies() {
+                write!(out, "{:width$}", name(from)).map_err(write_error)?;
+                for value in map.row(from).unwrap() {
+                    write!(out, " {:>width$.2}", value).map_err(write_error)?;
+                }
+                writeln!(out).map_err(write_error)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use magnetar_data::diff::{diff, merge, ChildDiff, ObjectDiff, Value};
+use magnetar_data::prelude::{load_yaml, to_yaml, Object};
+use ratatui::crossterm::style::Stylize;
+use crate::palette::Palette;
+
+/// Print the structural and field differences between two systems as a tree,
+/// children are matched by name. Returns whether any differences were found.
+pub fn print_file_diff(old: &Object, new: &Object) -> bool {
+    let diff = diff(old, new);
+    let mut lines = Vec::new();
+    push_diff(&diff, 0, &mut lines);
+    for line in &lines {
+        println!("{}", line);
+    }
+    !diff.is_empty()
+}
+
+/// Merge two edited copies of the `base` system file and write the result as YAML to `output` or stdout.
+/// Conflicts are listed on stderr, the merged file keeps our side of them and the merge fails.
+pub fn run_merge(base: &str, ours: &str, theirs: &str, output: Option<&str>) -> Result<(), String> {
+    let load = |path: &str| load_yaml(path).map_err(|e| format!("{}: {}", path, e));
+    let merge = merge(&load(base)?, &load(ours)?, &load(theirs)?);
+    let yaml = to_yaml(&merge.merged)?;
+    match output {
+        Some(path) => fs::write(path, yaml).map_err(|e| format!("{}: {}", path, e))?,
+        None => print!("{}", yaml),
+    }
+    for conflict in &merge.conflicts {
+        eprintln!("{} {}", "conflict:".with(Palette::get().bad()).bold(), conflict);
+    }
+    match merge.conflicts.len() {
+        0 => Ok(()),
+        count => Err(format!("{} conflict(s), kept ours", count)),
+    }
+}
+
+/// Print the anomaly and position deltas of every body between two states of the same system.
+pub fn print_time_diff(from: &Object, to: &Object, from_label: &str, to_label: &str) {
+    println!("{} -> {}", from_label, to_label);
+    print_time_deltas(from, to, 0);
+}
+
+fn push_diff(diff: &ObjectDiff, depth: usize, lines: &mut Vec<String>) {
+    if diff.is_empty() {
+        return;
+    }
+    let indent = "  ".repeat(depth + 1);
+    let palette = Palette::get();
+    let marker = if diff.changes.is_empty() { ' ' } else { '~' };
+    lines.push(format!("{}{} {}", "  ".repeat(depth), marker, diff.name).with(palette.warn()).to_string());
+    for change in &diff.changes {
+        let line = match (&change.old, &change.new) {
+            (Value::Unset, new) => format!("{}+ {}: {}", indent, change.field, new).with(palette.good()).to_string(),
+            (old, Value::Unset) => format!("{}- {}: {}", indent, change.field, old).with(palette.bad()).to_string(),
+            (old, new) => format!("{}  {}: {} -> {}", indent, change.field, old, new),
+        };
+        lines.push(line);
+    }
+    for child in &diff.children {
+        match child {
+            ChildDiff::Changed(child) => push_diff(child, depth + 1, lines),
+            ChildDiff::Removed(child) => push_subtree(child, depth + 1, '-', lines),
+            ChildDiff::Added(child) => push_subtree(child, depth + 1, '+', lines),
+        }
+    }
+}
+
+/// Mark an object and all of its children as added or removed.
+fn push_subtree(object: &Object, depth: usize, marker: char, lines: &mut Vec<String>) {
+    let line = format!("{}{} {}", "  ".repeat(depth), marker, object.name);
+    let color = if marker == '+' { Palette::get().good() } else { Palette::get().bad() };
+    lines.push(line.with(color).to_string());
+    for child in &object.children {
+        push_subtree(child, depth + 1, marker, lines);
+    }
+}
+
+fn print_time_deltas(from: &Object, to: &Object, depth: usize) {
+    let indent = "  ".repeat(depth);
+    if depth == 0 {
+        println!("{}- {}", indent, from.name);
+    } else {
+        // Shortest signed angle between both anomalies
+        let anomaly_delta = (to.orbital_params.mean_anomaly - from.orbital_params.mean_anomaly + 180.0).rem_euclid(360.0) - 180.0;
+        println!("{}- {}: mean anomaly {:+.3}°, position {:.0} km",
+                 indent, from.name, anomaly_delta, to.orbital_params.position().distance(from.orbital_params.position()));
+    }
+    for (from_child, to_child) in from.children.iter().zip(&to.children) {
+        print_time_deltas(from_child, to_child, depth + 1);
+    }
+}
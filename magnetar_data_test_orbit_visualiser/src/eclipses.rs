@@ -0,0 +1,26 @@
+use magnetar_data::eclipse::{find_occultations, OccultationKind};
+use magnetar_data::prelude::{Object, SystemStore};
+use crate::format_sim_time;
+
+/// Print every eclipse, transit and occultation seen from `observer` between `start` and `start + span`.
+pub fn run(system: &Object, observer: &str, start: f64, span: f64, resolution: f64) -> Result<(), String> {
+    if resolution <= 0.0 {
+        return Err("Resolution must be positive".to_string());
+    }
+    let store = SystemStore::new(system.clone());
+    let observer_id = store.find(observer).ok_or(format!("No body named {}", observer))?;
+    let name = |id| store.get(id).map_or("", |object| object.name.as_str());
+
+    println!("Occultations seen from {} (start, peak, end):", observer);
+    for occultation in find_occultations(&store, observer_id, start, start + span, resolution) {
+        let kind = match occultation.kind {
+            OccultationKind::Total => "total",
+            OccultationKind::Transit => "transit",
+            OccultationKind::Partial => "partial",
+        };
+        println!("  {:>12} {:>12} {:>12}  {} in front of {} ({})",
+                 format_sim_time(occultation.start), format_sim_time(occultation.peak), format_sim_time(occultation.end),
+                 name(occultation.front), name(occultation.back), kind);
+    }
+    Ok(())
+}
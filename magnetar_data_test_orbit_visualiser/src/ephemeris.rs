@@ -0,0 +1,66 @@
+use std::io::{self, Write};
+use clap::ValueEnum;
+use magnetar_data::prelude::{Object, SystemStore};
+use crate::format_sim_time;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+/// Output formats for tables
+pub enum TableFormat {
+    /// Aligned columns for reading in a terminal
+    Text,
+    Csv,
+}
+
+/// Column headers of the ephemeris table
+const COLUMNS: [&str; 7] = ["time", "mean_anomaly", "true_anomaly", "radius", "x", "y", "sim_time"];
+
+/// Print an ephemeris table of `name` from `start` over `span` seconds, one row every `interval` seconds.
+/// Positions are relative to the body's parent in km, or to `frame` if given, anomalies are in degrees.
+pub fn run(system: &Object, name: &str, frame: Option<&str>, start: f64, span: f64, interval: f64,
+           format: TableFormat) -> Result<(), String> {
+    if interval <= 0.0 {
+        return Err("Interval must be positive".to_string());
+    }
+    let body = system.find(name).ok_or(format!("No body named {}", name))?;
+    let parent_mass = system.find_parent(name).map_or(0.0, |parent| parent.mass);
+    // Resolving positions in another frame needs the state of the whole system at every row
+    let frame = match frame {
+        Some(frame) => {
+            let store = SystemStore::new(system.clone());
+            let ids = (store.find(name), store.find(frame).ok_or(format!("No body named {}", frame))?);
+            Some((store, ids))
+        }
+        None => None,
+    };
+
+    let mut out = io::stdout().lock();
+    let write_error = |e: io::Error| e.to_string();
+    match format {
+        TableFormat::Text => writeln!(out, "{:>12} {:>12} {:>12} {:>16} {:>16} {:>16}",
+                                      COLUMNS[0], COLUMNS[1], COLUMNS[2], COLUMNS[3], COLUMNS[4], COLUMNS[5]),
+        TableFormat::Csv => writeln!(out, "{}", COLUMNS.join(",")),
+    }.map_err(write_error)?;
+
+    let rows = (span / interval).floor() as usize;
+    for row in 0..=rows {
+        let sim_time = start + row as f64 * interval;
+        let orbit = body.orbital_params.state_at(sim_time, parent_mass);
+        let position = match &frame {
+            Some((epoch, (Some(body), frame))) => {
+                let mut store = epoch.clone();
+                store.step_by(sim_time);
+                store.position_in_frame(*body, *frame).unwrap_or_default()
+            }
+            _ => orbit.position(),
+        };
+        match format {
+            TableFormat::Text => writeln!(out, "{:>12} {:>12.4} {:>12.4} {:>16.1} {:>16.1} {:>16.1}",
+                                          format_sim_time(sim_time), orbit.mean_anomaly, orbit.true_anomaly(),
+                                          orbit.radius(), position.x, position.y),
+            TableFormat::Csv => writeln!(out, "{},{},{},{},{},{},{}",
+                                         format_sim_time(sim_time), orbit.mean_anomaly, orbit.true_anomaly(),
+                                         orbit.radius(), position.x, position.y, sim_time),
+        }.map_err(write_error)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use std::io::{self, Write};
+use magnetar_data::prelude::{Object, SystemStore};
+use magnetar_data::surface::ground_track;
+use crate::ephemeris::TableFormat;
+use crate::format_sim_time;
+
+/// Print the ground track of `satellite` over `body`, its parent if not given, from `start` over `span` seconds
+/// with one row every `interval` seconds
+#[allow(clippy::too_many_arguments)]
+pub fn run(system: &Object, satellite: &str, body: Option<&str>, start: f64, span: f64, interval: f64,
+           format: TableFormat) -> Result<(), String> {
+    if interval <= 0.0 {
+        return Err("Interval must be positive".to_string());
+    }
+    let store = SystemStore::new(system.clone());
+    let satellite_id = store.find(satellite).ok_or(format!("No body named {}", satellite))?;
+    let body_id = match body {
+        Some(body) => store.find(body).ok_or(format!("No body named {}", body))?,
+        None => store.parent(satellite_id).ok_or(format!("{} does not orbit anything", satellite))?,
+    };
+    if store.get(body_id).is_some_and(|body| body.rotation.is_none()) {
+        tracing::warn!("{} has no rotation-period, longitudes are measured from the x axis", store.get(body_id).unwrap().name);
+    }
+
+    let mut out = io::stdout().lock();
+    let write_error = |e: io::Error| e.to_string();
+    match format {
+        TableFormat::Text => writeln!(out, "{:>12} {:>10} {:>11}", "time", "latitude", "longitude"),
+        TableFormat::Csv => writeln!(out, "time,latitude,longitude,sim_time"),
+    }.map_err(write_error)?;
+    for (sim_time, point) in ground_track(&store, satellite_id, body_id, start, start + span, interval) {
+        match format {
+            TableFormat::Text => writeln!(out, "{:>12} {:>10.3} {:>11.3}", format_sim_time(sim_time), point.latitude, point.longitude),
+            TableFormat::Csv => writeln!(out, "{},{},{},{}", format_sim_time(sim_time), point.latitude, point.longitude, sim_time),
+        }.map_err(write_error)?;
+    }
+    Ok(())
+}
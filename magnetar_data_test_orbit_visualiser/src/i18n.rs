@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::fmt;
+use magnetar_data::coloring::ColorMode;
+use magnetar_data::prelude::ObjectType;
+
+/// Locales shipped with the visualiser by language code, the first one is the fallback for missing keys.
+const LOCALES: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.txt")),
+    ("de", include_str!("../locales/de.txt")),
+];
+
+/// Look up a text and fill in its placeholders, e.g. `tr!(catalog, "detail-mass", mass = object.mass)`.
+macro_rules! tr {
+    ($catalog:expr, $key:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $catalog.format($key, &[$((stringify!($name), &$value as &dyn std::fmt::Display)),*])
+    };
+}
+pub(crate) use tr;
+
+/// The texts of one language, loaded from the `locales` directory at compile time.
+pub struct Catalog {
+    messages: HashMap<&'static str, &'static str>,
+    fallback: HashMap<&'static str, &'static str>,
+}
+
+impl Catalog {
+    /// Load the catalog of a language code like "en", fails for languages that are not shipped.
+    pub fn new(language: &str) -> Result<Catalog, String> {
+        let (_, content) = LOCALES.iter()
+            .find(|(code, _)| *code == language)
+            .ok_or(format!("Unknown language {}, available: {}", language, languages().collect::<Vec<_>>().join(", ")))?;
+        Ok(Catalog { messages: parse(content), fallback: parse(LOCALES[0].1) })
+    }
+
+    /// The text for `key`, falling back to English and then to the key itself.
+    pub fn get<'a>(&self, key: &'a str) -> &'a str {
+        self.messages.get(key).or_else(|| self.fallback.get(key)).copied().unwrap_or(key)
+    }
+
+    /// The text for `key` with every `{name}` placeholder replaced by its value, use the `tr!` macro instead.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+        args.iter().fold(self.get(key).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), &value.to_string())
+        })
+    }
+
+    /// Localized name of an object type.
+    pub fn object_type(&self, object_type: &ObjectType) -> &str {
+        self.get(match object_type {
+            ObjectType::Star => "type-star",
+            ObjectType::Rocky => "type-rocky",
+            ObjectType::Jovian => "type-jovian",
+            ObjectType::IceGiant => "type-ice-giant",
+        })
+    }
+
+    /// Localized name of what orbits are colored by.
+    pub fn color_mode(&self, mode: ColorMode) -> &str {
+        self.get(match mode {
+            ColorMode::ObjectType => "color-type",
+            ColorMode::Eccentricity => "color-eccentricity",
+            ColorMode::Period => "color-period",
+            ColorMode::Habitability => "color-habitability",
+        })
+    }
+}
+
+/// Codes of all shipped languages.
+pub fn languages() -> impl Iterator<Item = &'static str> {
+    LOCALES.iter().map(|(code, _)| *code)
+}
+
+/// Parse `key = value` lines, skipping blank lines and `#` comments.
+fn parse(content: &'static str) -> HashMap<&'static str, &'static str> {
+    content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Placeholder names of a text in order of appearance
+    fn placeholders(text: &str) -> Vec<&str> {
+        text.split('{').skip(1).filter_map(|rest| rest.split_once('}')).map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn test_locales_match_english() {
+        let english = parse(LOCALES[0].1);
+        for (code, content) in LOCALES {
+            let messages = parse(content);
+            for (key, text) in &english {
+                let translated = messages.get(key).unwrap_or_else(|| panic!("{} is missing {}", code, key));
+                let (mut expected, mut actual) = (placeholders(text), placeholders(translated));
+                expected.sort();
+                actual.sort();
+                assert_eq!(expected, actual, "placeholders of {} in {}", key, code);
+            }
+            assert!(messages.keys().all(|key| english.contains_key(key)), "{} has keys English does not", code);
+        }
+        let catalog = Catalog::new("de").unwrap();
+        assert_eq!(tr!(catalog, "detail-children", count = 2), "Trabanten: 2");
+        assert!(Catalog::new("xx").is_err());
+    }
+}
@@ -1,15 +1,327 @@
-use std::io::{self, Write};
+mod batch;
+mod delta_v;
+mod diff;
+mod eclipses;
+mod ephemeris;
+mod ground_track;
+mod i18n;
+mod palette;
+mod perturb;
+mod porkchop;
+mod record;
+mod serve;
+mod tui;
+mod validate;
+mod windows;
+
+use std::env;
+use std::fs::{self, File};
+use std::io;
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::process::ExitCode;
 use std::time::Duration;
-use magnetar_data::celestial::Object;
-use magnetar_data::yaml_parser::load_yaml;
+use clap::{Parser, Subcommand};
+use ratatui::crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use ratatui::crossterm::execute;
+use magnetar_data::perturbation::PerturbationBounds;
+use magnetar_data::prelude::load_yaml;
+use magnetar_sim::net::SpectatorClient;
+use magnetar_sim::realism::Preset;
+use magnetar_sim::session::{LifetimeStats, SessionStats};
+use tracing_subscriber::EnvFilter;
+use crate::batch::{OutputFormat, StepSpec};
+use crate::ephemeris::TableFormat;
+use crate::palette::Palette;
+use crate::i18n::Catalog;
+use crate::tui::{App, Timeline};
+
+#[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
+/// Interactive and scripted viewer for Magnetar system files
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// System file to load
+    #[arg(short, long, default_value = "../data/celestial/sol.yaml")]
+    system: String,
+    /// Run non-interactively through a sequence of steps, e.g. "365x1d", and dump every body's state per step
+    #[arg(long, value_parser = parse_steps_arg)]
+    steps: Option<StepSpec>,
+    /// Output format for --steps
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json, requires = "steps")]
+    output: OutputFormat,
+    /// Write the --steps output to a file instead of stdout
+    #[arg(long, requires = "steps")]
+    out_file: Option<String>,
+    /// Reload the system file whenever it changes on disk, keeping the current sim time
+    #[arg(long, conflicts_with = "steps")]
+    watch: bool,
+    /// Follow the system streamed by `serve` on another machine instead of loading one, e.g. "localhost:7878"
+    #[arg(long, conflicts_with_all = ["steps", "watch"])]
+    connect: Option<String>,
+    /// Start of the timeline bar as an offset from the epoch
+    #[arg(long, default_value = "0d", value_parser = parse_seconds_arg, allow_hyphen_values = true, conflicts_with = "steps")]
+    timeline_start: f64,
+    /// End of the timeline bar as an offset from the epoch
+    #[arg(long, default_value = "3650d", value_parser = parse_seconds_arg, allow_hyphen_values = true, conflicts_with = "steps")]
+    timeline_end: f64,
+    /// Colors for reports, "colorblind" avoids telling results apart by red and green alone
+    #[arg(long, value_enum, global = true, default_value_t = Palette::Default)]
+    palette: Palette,
+    /// Language of the interactive view, can be switched at runtime with ":lang <code>"
+    #[arg(long, default_value = "en", conflicts_with = "steps")]
+    lang: String,
+    /// Which effects to simulate: "arcade" keeps orbits on rails, "realistic" adds collisions, decay and burns
+    /// delayed by the light time from the reference frame, "hardcore" also close encounters and atmospheric escape.
+    /// Saved next to the system file, later sessions use the saved preset or "arcade" without one.
+    #[arg(long, conflicts_with = "steps")]
+    preset: Option<Preset>,
+    /// File the statistics of all sessions are kept in, defaults to .magnetar_profile.json in the home directory
+    #[arg(long, conflicts_with = "steps")]
+    profile: Option<String>,
+    /// Write logs to a file instead of stderr, logging is configured through RUST_LOG
+    #[arg(long, global = true)]
+    log_file: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check a system file for schema and physics problems, exits with an error if any are found
+    Validate {
+        /// System file to validate
+        file: String,
+    },
+    /// Compare two system files, or one system at two sim times
+    Diff {
+        /// System file to compare
+        file: String,
+        /// Second system file, compares the structure and fields of both files
+        #[arg(required_unless_present = "times")]
+        other: Option<String>,
+        /// Compare the system at two offsets from its epoch instead, e.g. "--times 0d 365d"
+        #[arg(long, num_args = 2, value_names = ["FROM", "TO"], conflicts_with = "other", allow_hyphen_values = true)]
+        times: Option<Vec<String>>,
+    },
+    /// Three-way merge of two edited copies of a system file, fails if both changed the same value
+    Merge {
+        /// Common ancestor of both copies
+        base: String,
+        /// Our copy, wins every conflict
+        ours: String,
+        /// Their copy
+        theirs: String,
+        /// Write the merged system to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Print an ephemeris table for a single body
+    Ephemeris {
+        /// Name of the body
+        body: String,
+        /// System file to load
+        #[arg(short, long, default_value = "../data/celestial/sol.yaml")]
+        system: String,
+        /// Offset from the epoch of the first row
+        #[arg(long, default_value = "0d", value_parser = parse_seconds_arg, allow_hyphen_values = true)]
+        start: f64,
+        /// Time covered by the table
+        #[arg(long, default_value = "365d", value_parser = parse_seconds_arg)]
+        span: f64,
+        /// Time between two rows
+        #[arg(long, default_value = "10d", value_parser = parse_seconds_arg)]
+        interval: f64,
+        #[arg(long, value_enum, default_value_t = TableFormat::Text)]
+        format: TableFormat,
+        /// Print positions relative to this body instead of the body's parent
+        #[arg(long)]
+        frame: Option<String>,
+    },
+    /// Show how sensitive the position of a body is to small random changes of all orbital elements
+    Perturb {
+        /// Name of the body
+        body: String,
+        /// System file to load
+        #[arg(short, long, default_value = "../data/celestial/sol.yaml")]
+        system: String,
+        /// Number of perturbed copies of the system
+        #[arg(long, default_value_t = 32)]
+        runs: usize,
+        /// Seed of the first copy, the same seed always gives the same result
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Largest relative change of semi-major axes
+        #[arg(long, default_value_t = 0.001)]
+        sma: f64,
+        /// Largest absolute change of eccentricities
+        #[arg(long, default_value_t = 0.001)]
+        eccentricity: f64,
+        /// Largest change of longitudes of periapsis in whole degrees
+        #[arg(long, default_value_t = 0)]
+        periapsis: u16,
+        /// Largest change of mean anomalies in degrees
+        #[arg(long, default_value_t = 0.1)]
+        anomaly: f64,
+        /// Largest relative change of masses
+        #[arg(long, default_value_t = 0.0)]
+        mass: f64,
+        /// Time covered from the epoch
+        #[arg(long, default_value = "3650d", value_parser = parse_seconds_arg, allow_hyphen_values = true)]
+        span: f64,
+        /// Time between two rows
+        #[arg(long, default_value = "365d", value_parser = parse_seconds_arg)]
+        interval: f64,
+    },
+    /// Record positions and orbital elements of some bodies at a fixed cadence as CSV, for offline analysis
+    Record {
+        /// Names of the bodies to record
+        #[arg(required_unless_present = "filter")]
+        bodies: Vec<String>,
+        /// Also record all bodies matching a query, e.g. "type=ROCKY and parent=Sol and sma<2AU"
+        #[arg(long)]
+        filter: Option<String>,
+        /// System file to load
+        #[arg(short, long, default_value = "../data/celestial/sol.yaml")]
+        system: String,
+        /// Time to record from the epoch, negative to record backwards
+        #[arg(long, default_value = "365d", value_parser = parse_seconds_arg, allow_hyphen_values = true)]
+        span: f64,
+        /// Sim time between two samples
+        #[arg(long, default_value = "1d", value_parser = parse_seconds_arg)]
+        cadence: f64,
+        /// Write the recording to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Run a system headless and stream it to visualisers started with --connect
+    Serve {
+        /// System file to load
+        #[arg(short, long, default_value = "../data/celestial/sol.yaml")]
+        system: String,
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        bind: String,
+        /// Sim time passing per real second
+        #[arg(long, default_value = "1d", value_parser = parse_seconds_arg, allow_hyphen_values = true)]
+        speed: f64,
+        /// Real time between two snapshots in milliseconds
+        #[arg(long, default_value_t = 100)]
+        interval: u64,
+    },
+    /// Report transfer windows, conjunctions and closest approaches between two bodies with the same parent
+    Windows {
+        /// Body to depart from
+        origin: String,
+        /// Body to arrive at
+        target: String,
+        /// System file to load
+        #[arg(short, long, default_value = "../data/celestial/sol.yaml")]
+        system: String,
+        /// Offset from the epoch to start scanning at
+        #[arg(long, default_value = "0d", value_parser = parse_seconds_arg, allow_hyphen_values = true)]
+        start: f64,
+        /// Time range to scan
+        #[arg(long, default_value = "3650d", value_parser = parse_seconds_arg)]
+        span: f64,
+        /// Sampling interval of the scan, has to be well below the synodic period
+        #[arg(long, default_value = "1d", value_parser = parse_seconds_arg)]
+        resolution: f64,
+    },
+    /// Print a delta-v table in km/s between low orbits around all planets and moons
+    DeltaV {
+        /// System file to load
+        #[arg(short, long, default_value = "../data/celestial/sol.yaml")]
+        system: String,
+        #[arg(long, value_enum, default_value_t = TableFormat::Text)]
+        format: TableFormat,
+    },
+    /// Print the points on a body's surface directly below an orbiting body over time
+    GroundTrack {
+        /// Name of the orbiting body
+        satellite: String,
+        /// System file to load
+        #[arg(short, long, default_value = "../data/celestial/sol.yaml")]
+        system: String,
+        /// Body to project onto, defaults to the parent of the satellite
+        #[arg(long)]
+        body: Option<String>,
+        /// Offset from the epoch of the first row
+        #[arg(long, default_value = "0d", value_parser = parse_seconds_arg, allow_hyphen_values = true)]
+        start: f64,
+        /// Time covered by the table
+        #[arg(long, default_value = "1d", value_parser = parse_seconds_arg)]
+        span: f64,
+        /// Time between two rows
+        #[arg(long, default_value = "1h", value_parser = parse_seconds_arg)]
+        interval: f64,
+        #[arg(long, value_enum, default_value_t = TableFormat::Text)]
+        format: TableFormat,
+    },
+    /// Plot the delta-v of direct transfers between two bodies with the same parent over departure and arrival times
+    Porkchop {
+        /// Body to depart from
+        origin: String,
+        /// Body to arrive at
+        target: String,
+        /// System file to load
+        #[arg(short, long, default_value = "../data/celestial/sol.yaml")]
+        system: String,
+        /// Offset from the epoch of the first departure
+        #[arg(long, default_value = "0d", value_parser = parse_seconds_arg, allow_hyphen_values = true)]
+        start: f64,
+        /// Time range of departures
+        #[arg(long, default_value = "800d", value_parser = parse_seconds_arg)]
+        span: f64,
+        /// Shortest flight time after the first departure
+        #[arg(long, default_value = "100d", value_parser = parse_seconds_arg)]
+        flight_min: f64,
+        /// Longest flight time after the last departure
+        #[arg(long, default_value = "400d", value_parser = parse_seconds_arg)]
+        flight_max: f64,
+        /// Number of departure times
+        #[arg(long, default_value_t = 72)]
+        columns: usize,
+        /// Number of arrival times
+        #[arg(long, default_value_t = 30)]
+        rows: usize,
+    },
+    /// List eclipses, transits and occultations seen from a body
+    Eclipses {
+        /// Body to observe from
+        observer: String,
+        /// System file to load
+        #[arg(short, long, default_value = "../data/celestial/sol.yaml")]
+        system: String,
+        /// Offset from the epoch to start scanning at
+        #[arg(long, default_value = "0d", value_parser = parse_seconds_arg, allow_hyphen_values = true)]
+        start: f64,
+        /// Time range to scan
+        #[arg(long, default_value = "365d", value_parser = parse_seconds_arg)]
+        span: f64,
+        /// Sampling interval of the scan, has to be shorter than the events to find
+        #[arg(long, default_value = "10m", value_parser = parse_seconds_arg)]
+        resolution: f64,
+    },
+}
+
+fn parse_seconds_arg(input: &str) -> Result<f64, String> {
+    parse_signed_timestep(input).ok_or(format!("Invalid timestep format: {}", input))
+}
+
+fn parse_steps_arg(input: &str) -> Result<StepSpec, String> {
+    batch::parse_step_spec(input).ok_or(format!("Invalid step sequence: {}", input))
+}
 
 /// Parse a timestep string like "1d6h" into a Duration.
 /// Supported units: d (days), h (hours), m (minutes), s (seconds)
 fn parse_timestep(input: &str) -> Option<Duration> {
+    if input.is_empty() {
+        return None;
+    }
     let mut total_seconds: u64 = 0;
     let mut num_buf = String::new();
     for c in input.chars() {
-        if c.is_digit(10) {
+        if c.is_ascii_digit() {
             num_buf.push(c);
         } else {
             if num_buf.is_empty() {
@@ -32,90 +344,236 @@ fn parse_timestep(input: &str) -> Option<Duration> {
     Some(Duration::from_secs(total_seconds))
 }
 
-/// Recursively render an object (and its children) as a block of text lines.
-/// The returned Vec<String> holds the block’s lines with the given indent.
-fn render_object_block(object: &Object, indent: usize) -> Vec<String> {
-    let mut lines = Vec::new();
-    let indent_str = " ".repeat(indent);
-    // First line: object name.
-    lines.push(format!("{}- {}", indent_str, object.name));
-    lines.push(format!("{}  Mean Anomaly: {:.3}°", indent_str, object.orbital_params.mean_anomaly));
-    // Append each child's block (with increased indent) immediately after the parent.
-    for child in &object.children {
-        let child_block = render_object_block(child, indent + 2);
-        lines.extend(child_block);
+/// A time command entered at the prompt.
+enum TimeCommand {
+    /// Step relative to the current sim time by a signed amount of seconds, e.g. "1d" or "-10d".
+    Step(f64),
+    /// Jump to an absolute offset in seconds from the loaded epoch, e.g. "@365d".
+    Jump(f64),
+}
+
+/// Parse a prompt input into a TimeCommand.
+/// A leading '-' steps backwards, a leading '@' jumps to an absolute offset from the epoch.
+fn parse_time_command(input: &str) -> Option<TimeCommand> {
+    if let Some(target) = input.strip_prefix('@') {
+        return parse_signed_timestep(target).map(TimeCommand::Jump);
     }
-    lines
+    parse_signed_timestep(input).map(TimeCommand::Step)
 }
 
-/// Given a slice of objects, render their blocks and print them side by side in four columns.
-/// Each object’s block is kept intact so that its children remain with the parent.
-fn display_siblings(objects: &[Object], indent: usize, col_width: usize) {
-    // Render each object's block.
-    let rendered_blocks: Vec<Vec<String>> = objects.iter()
-        .map(|obj| render_object_block(obj, indent))
-        .collect();
-
-    // Process the blocks in chunks of 4 (four columns per row).
-    for chunk in rendered_blocks.chunks(4) {
-        // Determine the maximum number of lines in this row.
-        let height = chunk.iter().map(|block| block.len()).max().unwrap_or(0);
-        for i in 0..height {
-            for block in chunk {
-                let line = if i < block.len() { &block[i] } else { "" };
-                print!("{:<width$}", line, width = col_width);
-            }
-            println!();
-        }
-        println!(); // Blank line between rows.
+/// Parse a timestep that may be prefixed with '-' into signed seconds.
+fn parse_signed_timestep(input: &str) -> Option<f64> {
+    match input.strip_prefix('-') {
+        Some(rest) => parse_timestep(rest).map(|d| -d.as_secs_f64()),
+        None => parse_timestep(input).map(|d| d.as_secs_f64()),
     }
 }
 
-/// Display the full table. First, print the top-level object (e.g. "Sol"),
-/// then, if it has children, display them in four columns.
-fn display_table(system: &Object, col_width: usize) {
-    // Print the top-level object.
-    println!("- {}", system.name);
-    println!("  Mean Anomaly: {:.3}°", system.orbital_params.mean_anomaly);
-    println!();
-    // Now display the children in four columns if they exist.
-    if !system.children.is_empty() {
-        display_siblings(&system.children, 2, col_width);
+/// Format a signed amount of seconds as days and hours, e.g. "+12d 6h".
+fn format_sim_time(seconds: f64) -> String {
+    let sign = if seconds < 0.0 { '-' } else { '+' };
+    let total_hours = (seconds.abs() / 3600.0).round() as u64;
+    format!("{}{}d {}h", sign, total_hours / 24, total_hours % 24)
+}
+
+/// Print the error of a failed subcommand and turn the result into an exit code.
+fn report(result: Result<(), String>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
     }
 }
 
-fn main() {
-    // Load the celestial system from YAML.
-    let mut system = load_yaml("../data/celestial/sol.yaml")
-        .expect("Failed to load YAML");
-
-    // Clear the screen once at startup.
-    print!("\x1B[2J");
-    io::stdout().flush().unwrap();
-
-    loop {
-        // Move the cursor to the top left to rewrite the same area.
-        print!("\x1B[H");
-        // Display the table with 4 columns (using a fixed column width, e.g., 40 characters).
-        display_table(&system, 40);
-        io::stdout().flush().unwrap();
-
-        // Prompt for a timestep.
-        println!("\nEnter timestep (e.g., 1d6h) or press Enter to exit:");
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        let input = input.trim();
-        if input.is_empty() {
-            break;
+fn run_diff(file: &str, other: Option<&str>, times: Option<&[String]>) -> ExitCode {
+    let system = match load_yaml(file) {
+        Ok(system) => system,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(other) = other {
+        match load_yaml(other) {
+            Ok(other_system) => {
+                if !diff::print_file_diff(&system, &other_system) {
+                    println!("No differences");
+                }
+            }
+            Err(e) => {
+                eprintln!("{}: {}", other, e);
+                return ExitCode::FAILURE;
+            }
         }
-        match parse_timestep(input) {
-            Some(duration) => system.step_forward(duration),
-            None => {
-                println!("Invalid timestep format: {}", input);
-                println!("Press Enter to try again...");
-                let mut dummy = String::new();
-                io::stdin().read_line(&mut dummy).unwrap();
+    } else if let Some([from, to]) = times {
+        let (Some(from_seconds), Some(to_seconds)) = (parse_signed_timestep(from), parse_signed_timestep(to)) else {
+            eprintln!("Invalid timestep format: {} {}", from, to);
+            return ExitCode::FAILURE;
+        };
+        diff::print_time_diff(&system.state_at(from_seconds), &system.state_at(to_seconds),
+                              &format_sim_time(from_seconds), &format_sim_time(to_seconds));
+    }
+    ExitCode::SUCCESS
+}
+
+/// Install a log subscriber if RUST_LOG is set, writing to `log_file` or stderr.
+/// The TUI owns the terminal, so it only logs if a file is given.
+fn init_logging(log_file: Option<&str>, interactive: bool) -> io::Result<()> {
+    let Ok(filter) = EnvFilter::try_from_default_env() else {
+        return Ok(());
+    };
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    match log_file {
+        Some(path) => builder.with_ansi(false).with_writer(Mutex::new(File::create(path)?)).init(),
+        None if !interactive => builder.with_writer(io::stderr).init(),
+        None => {}
+    }
+    Ok(())
+}
+
+fn main() -> io::Result<ExitCode> {
+    let cli = Cli::parse();
+    Palette::init(cli.palette);
+    init_logging(cli.log_file.as_deref(), cli.command.is_none() && cli.steps.is_none())?;
+    if let Some(command) = cli.command {
+        return Ok(match command {
+            Command::Validate { file } => validate::run(&file),
+            Command::Diff { file, other, times } => run_diff(&file, other.as_deref(), times.as_deref()),
+            Command::Merge { base, ours, theirs, output } => {
+                report(diff::run_merge(&base, &ours, &theirs, output.as_deref()))
+            }
+            Command::Ephemeris { body, system, start, span, interval, format, frame } => {
+                report(load_yaml(&system).and_then(|system| {
+                    ephemeris::run(&system, &body, frame.as_deref(), start, span, interval, format)
+                }))
+            }
+            Command::Perturb { body, system, runs, seed, sma, eccentricity, periapsis, anomaly, mass, span, interval } => {
+                let bounds = PerturbationBounds {
+                    semi_major_axis: sma,
+                    eccentricity,
+                    longitude_of_periapsis: periapsis,
+                    mean_anomaly: anomaly,
+                    mass,
+                };
+                report(load_yaml(&system).and_then(|system| perturb::run(&system, &body, &bounds, runs, seed, span, interval)))
+            }
+            Command::Record { bodies, filter, system, span, cadence, output } => {
+                report(load_yaml(&system).and_then(|system| {
+                    record::run(&system, &bodies, filter.as_deref(), span, cadence, output.as_deref())
+                }))
+            }
+            Command::Serve { system, bind, speed, interval } => {
+                report(load_yaml(&system).and_then(|system| serve::run(&system, &bind, speed, Duration::from_millis(interval))))
+            }
+            Command::Windows { origin, target, system, start, span, resolution } => {
+                report(load_yaml(&system).and_then(|system| windows::run(&system, &origin, &target, start, span, resolution)))
+            }
+            Command::DeltaV { system, format } => {
+                report(load_yaml(&system).and_then(|system| delta_v::run(&system, format)))
+            }
+            Command::GroundTrack { satellite, system, body, start, span, interval, format } => {
+                report(load_yaml(&system).and_then(|system| {
+                    ground_track::run(&system, &satellite, body.as_deref(), start, span, interval, format)
+                }))
+            }
+            Command::Porkchop { origin, target, system, start, span, flight_min, flight_max, columns, rows } => {
+                report(load_yaml(&system).and_then(|system| {
+                    porkchop::run(&system, &origin, &target, start, span, flight_min, flight_max, columns, rows)
+                }))
+            }
+            Command::Eclipses { observer, system, start, span, resolution } => {
+                report(load_yaml(&system).and_then(|system| eclipses::run(&system, &observer, start, span, resolution)))
+            }
+        });
+    }
+
+    // Load the celestial system from YAML, this is the epoch all positions are derived from.
+    // Spectators get it from the host instead, followed by a stream of snapshots.
+    let mut snapshots = None;
+    let epoch = match &cli.connect {
+        Some(address) => match SpectatorClient::connect(address) {
+            Ok(mut client) => {
+                let epoch = client.epoch().root().clone();
+                let (sender, receiver) = mpsc::channel();
+                thread::spawn(move || {
+                    while let Ok(Some(snapshot)) = client.receive() {
+                        if sender.send(snapshot).is_err() {
+                            break;
+                        }
+                    }
+                });
+                snapshots = Some(receiver);
+                epoch
             }
+            Err(e) => {
+                eprintln!("error: {}: {}", address, e);
+                return Ok(ExitCode::FAILURE);
+            }
+        },
+        None => load_yaml(&cli.system).expect("Failed to load YAML"),
+    };
+
+    if let Some(spec) = cli.steps {
+        batch::run(&epoch, &spec, cli.output, cli.out_file.as_deref())?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let catalog = match Catalog::new(&cli.lang) {
+        Ok(catalog) => catalog,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    if cli.timeline_end <= cli.timeline_start {
+        eprintln!("error: --timeline-end has to be after --timeline-start");
+        return Ok(ExitCode::FAILURE);
+    }
+
+    let mut terminal = ratatui::init();
+    // The timeline bar can be clicked and dragged
+    execute!(io::stdout(), EnableMouseCapture)?;
+    let mut app = App::new(epoch)
+        .timeline(Timeline { start: cli.timeline_start, end: cli.timeline_end })
+        .preset(cli.preset)
+        .catalog(catalog);
+    if cli.watch {
+        app = app.watch(&cli.system);
+    }
+    match snapshots {
+        Some(snapshots) => app = app.spectate(snapshots),
+        None => app = app.source(&cli.system),
+    }
+    let result = app.run(&mut terminal);
+    execute!(io::stdout(), DisableMouseCapture)?;
+    ratatui::restore();
+    let session = result?;
+
+    let profile = cli.profile.or_else(|| env::var("HOME").ok().map(|home| format!("{}/.magnetar_profile.json", home)));
+    let lifetime = profile.as_deref().and_then(|path| match update_profile(path, &session) {
+        Ok(lifetime) => Some(lifetime),
+        Err(e) => {
+            eprintln!("warning: could not update the profile {}: {}", path, e);
+            None
         }
+    });
+    // The language may have been switched at runtime, but the summary is printed in the one the view started with
+    if let Ok(catalog) = Catalog::new(&cli.lang) {
+        println!("{}", tui::summary(&catalog, &session, lifetime.as_ref()));
     }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Add a finished session to the lifetime statistics in the profile at `path`, creating it if it does not exist yet.
+fn update_profile(path: &str, session: &SessionStats) -> io::Result<LifetimeStats> {
+    let mut lifetime: LifetimeStats = match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(io::Error::other)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => LifetimeStats::default(),
+        Err(e) => return Err(e),
+    };
+    lifetime.add(session);
+    fs::write(path, serde_json::to_string_pretty(&lifetime).map_err(io::Error::other)?)?;
+    Ok(lifetime)
 }
@@ -0,0 +1,58 @@
+use std::sync::OnceLock;
+use clap::ValueEnum;
+use ratatui::crossterm::style::Color;
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+/// Colors used for reports, chosen once at startup with `--palette`
+pub enum Palette {
+    /// Red for errors and removals, green for success and additions
+    #[default]
+    Default,
+    /// Okabe-Ito colors that stay distinguishable with deuteranopia and protanopia
+    Colorblind,
+}
+
+static PALETTE: OnceLock<Palette> = OnceLock::new();
+
+impl Palette {
+    /// Set the palette for the rest of the run, only the first call has an effect.
+    pub fn init(palette: Palette) {
+        let _ = PALETTE.set(palette);
+    }
+
+    pub fn get() -> Palette {
+        PALETTE.get().copied().unwrap_or_default()
+    }
+
+    /// Errors and removed entries
+    pub fn bad(self) -> Color {
+        match self {
+            Palette::Default => Color::Red,
+            Palette::Colorblind => Color::Rgb { r: 213, g: 94, b: 0 },
+        }
+    }
+
+    /// Warnings and changed entries
+    pub fn warn(self) -> Color {
+        match self {
+            Palette::Default => Color::Yellow,
+            Palette::Colorblind => Color::Rgb { r: 240, g: 228, b: 66 },
+        }
+    }
+
+    /// Success and added entries
+    pub fn good(self) -> Color {
+        match self {
+            Palette::Default => Color::Green,
+            Palette::Colorblind => Color::Rgb { r: 0, g: 114, b: 178 },
+        }
+    }
+
+    /// Paths and other highlighted names
+    pub fn accent(self) -> Color {
+        match self {
+            Palette::Default => Color::Cyan,
+            Palette::Colorblind => Color::Rgb { r: 86, g: 180, b: 233 },
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use magnetar_data::perturbation::{envelopes, PerturbationBounds};
+use magnetar_data::prelude::{Object, SystemStore};
+use crate::format_sim_time;
+
+/// Print how far `body` strays from its nominal position over `runs` randomly perturbed copies of the system
+#[allow(clippy::too_many_arguments)]
+pub fn run(system: &Object, body: &str, bounds: &PerturbationBounds, runs: usize, seed: u64, span: f64, interval: f64)
+           -> Result<(), String> {
+    if interval <= 0.0 {
+        return Err("Interval must be positive".to_string());
+    }
+    let store = SystemStore::new(system.clone());
+    let id = store.find(body).ok_or(format!("No body named {}", body))?;
+    let samples = (span.abs() / interval).floor() as usize;
+    let times: Vec<f64> = (0..=samples).map(|sample| sample as f64 * interval.copysign(span)).collect();
+    let envelope = envelopes(system, bounds, runs, seed, &times).swap_remove(id.0 as usize);
+
+    println!("Position spread of {} over {} perturbed runs:", body, runs);
+    for ((time, nominal), radius) in envelope.times.iter().zip(&envelope.nominal).zip(&envelope.radius) {
+        let distance = store.parent(id).map_or(0.0, |_| nominal.length());
+        println!("  {:>12}  {:>14.0} km  ±{:.0} km ({:.3} %)",
+                 format_sim_time(*time), distance, radius, if distance > 0.0 { radius / distance * 100.0 } else { 0.0 });
+    }
+    Ok(())
+}
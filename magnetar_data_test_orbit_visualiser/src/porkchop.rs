@@ -0,0 +1,54 @@
+use std::ptr;
+use ratatui::crossterm::style::Stylize;
+use magnetar_data::porkchop::porkchop;
+use magnetar_data::prelude::Object;
+use crate::format_sim_time;
+use crate::palette::Palette;
+
+/// Delta-v relative to the cheapest transfer up to which a cell gets each shade, from cheapest to most expensive
+const SHADES: [(f64, char); 4] = [(1.1, '█'), (1.3, '▓'), (1.6, '▒'), (2.0, '░')];
+
+/// Print a porkchop heatmap of direct transfers between two bodies with the same parent. Departures run from
+/// `start` to `start + span` left to right, arrivals from `flight_min` after the first to `flight_max` after
+/// the last departure bottom to top.
+#[allow(clippy::too_many_arguments)]
+pub fn run(system: &Object, origin: &str, target: &str, start: f64, span: f64, flight_min: f64, flight_max: f64,
+           columns: usize, rows: usize) -> Result<(), String> {
+    if columns < 2 || rows < 2 || flight_min >= flight_max {
+        return Err("The grid needs at least 2 columns and rows and a positive range of flight times".to_string());
+    }
+    let parent = system.find_parent(origin).ok_or(format!("No orbiting body named {}", origin))?;
+    if !system.find_parent(target).is_some_and(|target_parent| ptr::eq(parent, target_parent)) {
+        return Err(format!("{} and {} have to orbit the same parent", origin, target));
+    }
+    let find = |name: &str| parent.children.iter().find(|child| child.name == name).map(|child| &child.orbital_params);
+    let plot = porkchop(find(origin).unwrap(), find(target).unwrap(), parent.mass,
+                        start..start + span, start + flight_min..start + span + flight_max, columns, rows);
+    let best = plot.best().ok_or("No transfer found on the grid")?;
+    let palette = Palette::get();
+
+    println!("Porkchop {} -> {} around {}, departure left to right, arrival bottom to top", origin, target, parent.name);
+    for arrival in (0..rows).rev() {
+        // Label every fifth row and the first and last one with its arrival time
+        let label = if arrival % 5 == 0 || arrival == rows - 1 { format_sim_time(plot.arrivals[arrival]) } else { String::new() };
+        print!("{:>12} ", label);
+        for departure in 0..columns {
+            let ratio = plot.get(departure, arrival) / best.delta_v;
+            match SHADES.iter().position(|(limit, _)| ratio <= *limit) {
+                Some(0) => print!("{}", SHADES[0].1.with(palette.good())),
+                Some(index) if index < 2 => print!("{}", SHADES[index].1.with(palette.warn())),
+                Some(index) => print!("{}", SHADES[index].1.with(palette.bad())),
+                None => print!(" "),
+            }
+        }
+        println!();
+    }
+    println!("{:>12} {:<width$}{}", "", format_sim_time(plot.departures[0]), format_sim_time(plot.departures[columns - 1]),
+             width = columns.saturating_sub(format_sim_time(plot.departures[columns - 1]).len()));
+    println!("\nShades up to {} times the cheapest transfer, blank beyond",
+             SHADES.iter().map(|(limit, shade)| format!("{} {}", shade, limit)).collect::<Vec<_>>().join(", "));
+    println!("Cheapest: depart {}, arrive {}, flight time {}, delta-v {:.3} km/s",
+             format_sim_time(best.departure), format_sim_time(best.arrival),
+             format_sim_time(best.arrival - best.departure), best.delta_v);
+    Ok(())
+}
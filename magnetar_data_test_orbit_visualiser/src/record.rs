@@ -0,0 +1,35 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use magnetar_data::prelude::{Object, Query, SystemStore};
+use magnetar_data::telemetry::TelemetryRecorder;
+use magnetar_sim::simulation::Simulation;
+
+/// Record the positions and elements of `bodies` and the ones matching `filter` every `cadence` seconds for `span`
+/// as CSV to `out` or stdout
+pub fn run(system: &Object, bodies: &[String], filter: Option<&str>, span: f64, cadence: f64, out: Option<&str>)
+           -> Result<(), String> {
+    let store = SystemStore::new(system.clone());
+    let mut ids = bodies.iter()
+        .map(|name| store.find(name).ok_or_else(|| format!("Unknown body: {}", name)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(filter) = filter {
+        ids.extend(Query::parse(filter)?.select(&store).into_iter().filter(|id| !ids.contains(id)).collect::<Vec<_>>());
+    }
+    if cadence <= 0.0 {
+        return Err("The cadence has to be positive".to_string());
+    }
+    let writer: Box<dyn Write> = match out {
+        Some(path) => Box::new(BufWriter::new(File::create(path).map_err(|e| format!("{}: {}", path, e))?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let write_error = |e: io::Error| e.to_string();
+    let mut recorder = TelemetryRecorder::new(writer, ids, cadence).map_err(write_error)?;
+    let mut simulation = Simulation::new(store);
+    let samples = (span.abs() / cadence).floor() as usize;
+    for sample in 0..=samples {
+        simulation.seek(sample as f64 * cadence.copysign(span));
+        recorder.record(simulation.store(), simulation.sim_time()).map_err(write_error)?;
+    }
+    recorder.finish().map_err(write_error)?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use std::thread;
+use std::time::{Duration, Instant};
+use magnetar_data::prelude::{Object, SystemStore};
+use magnetar_sim::clock::Clock;
+use magnetar_sim::event::Event;
+use magnetar_sim::net::SpectatorHost;
+use magnetar_sim::simulation::Simulation;
+
+/// Run the system headless at `time_scale` sim seconds per second and stream a snapshot to all spectators
+/// every `interval`, until the process is stopped.
+pub fn run(system: &Object, bind: &str, time_scale: f64, interval: Duration) -> Result<(), String> {
+    let mut simulation = Simulation::new(SystemStore::new(system.clone()))
+        .with_clock(Clock::new(time_scale))
+        .with_collisions(true);
+    let mut host = SpectatorHost::bind(bind, simulation.store()).map_err(|e| format!("{}: {}", bind, e))?;
+    println!("Serving {} on {}", system.name, host.local_address().map_err(|e| e.to_string())?);
+    let mut last_tick = Instant::now();
+    loop {
+        thread::sleep(interval);
+        let now = Instant::now();
+        simulation.tick(now - last_tick);
+        last_tick = now;
+        for event in simulation.drain_events() {
+            if let Event::Collision { sim_time, collision } = event {
+                let name = |id| simulation.store().get(id).map_or("", |object| object.name.as_str());
+                println!("{}: {} and {} collided", crate::format_sim_time(sim_time), name(collision.a), name(collision.b));
+            }
+        }
+        host.broadcast(&simulation.store().snapshot(simulation.sim_time())).map_err(|e| e.to_string())?;
+    }
+}
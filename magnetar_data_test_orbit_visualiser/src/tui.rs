@@ -0,0 +1,922 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, SystemTime};
+use magnetar_data::coloring::{ColorMap, ColorMode};
+use magnetar_data::prelude::{load_yaml, DVec3, Object, ObjectId, Query, Selection, SystemStats, SystemStore};
+use magnetar_data::signal::SPEED_OF_LIGHT;
+use magnetar_data::snapshot::Snapshot;
+use magnetar_data::stability::{stability_band, StabilityBand};
+use magnetar_data::telemetry::TelemetryRecorder;
+use magnetar_sim::bookmark::{Bookmarks, CameraBookmark};
+use magnetar_sim::clock::Clock;
+use magnetar_sim::command::Command;
+use magnetar_sim::console::{self, ConsoleCommand};
+use magnetar_sim::realism::{Preset, Scenario};
+use magnetar_sim::session::{LifetimeStats, SessionStats};
+use magnetar_sim::simulation::Simulation;
+use magnetar_sim::tutorial::{Action, Goal, Tutorial, TutorialStep};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use crate::i18n::{tr, Catalog};
+use crate::{format_sim_time, parse_signed_timestep, parse_time_command, TimeCommand};
+
+/// Available step sizes in seconds, cycled through with `+` and `-`.
+const STEP_SIZES: [(f64, &str); 7] = [
+    (3600.0, "1h"),
+    (6.0 * 3600.0, "6h"),
+    (86400.0, "1d"),
+    (10.0 * 86400.0, "10d"),
+    (30.0 * 86400.0, "30d"),
+    (100.0 * 86400.0, "100d"),
+    (365.0 * 86400.0, "365d"),
+];
+
+/// How long to wait for input before redrawing, also the tick rate while running.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Range of sim times covered by the timeline bar, in seconds from the epoch.
+#[derive(Debug, Clone, Copy)]
+pub struct Timeline {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Timeline {
+    /// Sim time at `column` of a bar `width` columns wide.
+    fn time_at(&self, column: u16, width: u16) -> f64 {
+        let fraction = column as f64 / width.saturating_sub(1).max(1) as f64;
+        self.start + fraction.clamp(0.0, 1.0) * (self.end - self.start)
+    }
+
+    /// Column of a bar `width` columns wide showing `sim_time`, `None` if it lies outside of the range.
+    fn column_of(&self, sim_time: f64, width: u16) -> Option<u16> {
+        let fraction = (sim_time - self.start) / (self.end - self.start);
+        (0.0..=1.0).contains(&fraction).then(|| (fraction * width.saturating_sub(1) as f64).round() as u16)
+    }
+}
+
+/// A system file being watched for changes.
+struct Watch {
+    path: String,
+    modified: Option<SystemTime>,
+}
+
+impl Watch {
+    /// Returns whether the file was modified since the last call.
+    fn changed(&mut self) -> bool {
+        let modified = fs::metadata(&self.path).and_then(|metadata| metadata.modified()).ok();
+        if modified.is_some() && modified != self.modified {
+            self.modified = modified;
+            return true;
+        }
+        false
+    }
+}
+
+/// State of the interactive visualiser.
+pub struct App {
+    /// The loaded system and its state at the current sim time, running while its clock is not paused.
+    sim: Simulation,
+    /// Depth of each body in the tree pane, rows are in id order so row indices double as ids.
+    rows: Vec<usize>,
+    tree_state: ListState,
+    /// Bodies marked with `m`, shown as a group in the detail pane.
+    selection: Selection,
+    /// Body positions in the detail pane are shown relative to, the root if `None`.
+    frame: Option<ObjectId>,
+    /// What the names in the tree pane are colored by, cycled with `c`.
+    color_mode: ColorMode,
+    step_index: usize,
+    /// Text typed at the `:` prompt, `None` when not in command mode.
+    command: Option<String>,
+    status: String,
+    /// The system file, loaded again by `:reload` and whenever the watch sees a change
+    source: Option<String>,
+    watch: Option<Watch>,
+    /// Snapshots streamed from a host when spectating, the latest one replaces the local state every tick.
+    spectate: Option<Receiver<Snapshot>>,
+    /// Telemetry of the marked bodies started with `:record`, sampled whenever the sim time changes.
+    recorder: Option<TelemetryRecorder<BufWriter<File>>>,
+    timeline: Timeline,
+    /// Sim times marked with `b`, kept sorted.
+    bookmarks: Vec<f64>,
+    /// Views saved with `:view save`, kept next to the system file.
+    views: Bookmarks,
+    /// Preset picked on the command line, saved next to the system file instead of loading the saved one.
+    preset: Option<Preset>,
+    session: SessionStats,
+    /// Started with `:tutorial`, removed once finished or stopped.
+    tutorial: Option<Tutorial>,
+    /// Inner area of the timeline bar as last drawn, used to map mouse positions to sim times.
+    timeline_area: Rect,
+    text: Catalog,
+    quit: bool,
+}
+
+impl App {
+    pub fn new(epoch: Object) -> App {
+        let epoch = SystemStore::new(epoch);
+        App {
+            rows: tree_rows(&epoch),
+            // Running advances by one step per tick.
+            sim: Simulation::new(epoch).with_clock(Clock { time_scale: STEP_SIZES[2].0 / TICK.as_secs_f64(), paused: true }),
+            tree_state: ListState::default().with_selected(Some(0)),
+            selection: Selection::new(),
+            frame: None,
+            color_mode: ColorMode::default(),
+            step_index: 2,
+            command: None,
+            status: String::new(),
+            source: None,
+            watch: None,
+            spectate: None,
+            recorder: None,
+            timeline: Timeline { start: 0.0, end: 3650.0 * 86400.0 },
+            bookmarks: Vec::new(),
+            views: Bookmarks::new(),
+            preset: None,
+            session: SessionStats::new(),
+            tutorial: None,
+            timeline_area: Rect::default(),
+            text: Catalog::new("en").expect("English is always shipped"),
+            quit: false,
+        }
+    }
+
+    /// The file the system was loaded from, so `:reload` can load it again.
+    pub fn source(mut self, path: &str) -> App {
+        self.source = Some(path.to_string());
+        self.load_views();
+        self.load_scenario();
+        self
+    }
+
+    /// Reload the system from `path` whenever it changes on disk, keeping the current sim time.
+    pub fn watch(mut self, path: &str) -> App {
+        self.source = Some(path.to_string());
+        self.load_views();
+        self.load_scenario();
+        let mut watch = Watch { path: path.to_string(), modified: None };
+        // Record the current modification time so the initial state does not count as a change.
+        watch.changed();
+        self.watch = Some(watch);
+        self
+    }
+
+    /// Follow the states streamed by a host instead of stepping locally.
+    pub fn spectate(mut self, snapshots: Receiver<Snapshot>) -> App {
+        self.spectate = Some(snapshots);
+        self
+    }
+
+    /// Set the range of sim times covered by the timeline bar.
+    pub fn timeline(mut self, timeline: Timeline) -> App {
+        self.timeline = timeline;
+        self
+    }
+
+    /// Simulate the effects of a realism preset and save it with the system file, `None` keeps the one saved
+    /// with it or the default.
+    pub fn preset(mut self, preset: Option<Preset>) -> App {
+        self.preset = preset;
+        self.sim = self.sim.with_realism(preset.unwrap_or_default().realism());
+        self
+    }
+
+    /// Use the texts of another language, can be switched at runtime with `:lang`.
+    pub fn catalog(mut self, catalog: Catalog) -> App {
+        self.text = catalog;
+        self
+    }
+
+    /// Run the event loop until the user quits, returns what happened during the session.
+    pub fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<SessionStats> {
+        while !self.quit {
+            let sim_time = self.sim.sim_time();
+            self.reload_if_changed();
+            self.follow_host();
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(TICK)? {
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_key(key),
+                    Event::Mouse(mouse) => match mouse.kind {
+                        MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left) => {
+                            self.scrub(mouse.column, mouse.row);
+                        }
+                        _ => {}
+                    },
+                    _ => {}
+                }
+            } else if self.sim.tick(TICK) != 0.0 {
+                self.record();
+            }
+            self.session.advance(self.sim.sim_time() - sim_time);
+            // Scheduled commands, e.g. of the autopilot, can move bodies in the tree while stepping
+            if tree_rows(self.sim.store()) != self.rows {
+                self.refresh_tree();
+            }
+            if let Some(object) = self.selected_id().and_then(|id| self.sim.store().get(id)) {
+                self.session.visit(&object.name);
+            }
+            self.update_tutorial();
+        }
+        Ok(self.session)
+    }
+
+    fn reload_if_changed(&mut self) {
+        let Some(watch) = self.watch.as_mut() else {
+            return;
+        };
+        if watch.changed() {
+            self.reload();
+        }
+    }
+
+    /// Load the system file again, keeping the current sim time and the commands executed so far.
+    fn reload(&mut self) {
+        let Some(path) = self.source.clone() else {
+            self.status = self.text.get("status-reload-unavailable").to_string();
+            return;
+        };
+        match load_yaml(&path) {
+            Ok(epoch) => {
+                self.sim.set_epoch(SystemStore::new(epoch));
+                self.refresh_tree();
+                tracing::info!(path, sim_time = self.sim.sim_time(), "reloaded system");
+                self.status = tr!(self.text, "status-reloaded", path = path);
+            }
+            // Keep showing the last good state while the file is being edited.
+            Err(e) => {
+                tracing::warn!(path, error = %e, "reload failed");
+                self.status = tr!(self.text, "status-reload-failed", error = e);
+            }
+        }
+    }
+
+    fn follow_host(&mut self) {
+        let Some(snapshots) = self.spectate.as_ref() else {
+            return;
+        };
+        let mut latest = None;
+        loop {
+            match snapshots.try_recv() {
+                Ok(snapshot) => latest = Some(snapshot),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.spectate = None;
+                    self.status = self.text.get("status-host-disconnected").to_string();
+                    break;
+                }
+            }
+        }
+        if let Some(snapshot) = latest {
+            if self.sim.restore(&snapshot).is_ok() {
+                self.record();
+            }
+        }
+    }
+
+    fn set_sim_time(&mut self, sim_time: f64) {
+        self.sim.seek(sim_time);
+        self.record();
+    }
+
+    fn record(&mut self) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(e) = recorder.record(self.sim.store(), self.sim.sim_time()) {
+                self.recorder = None;
+                self.status = tr!(self.text, "status-recording-failed", error = e);
+            }
+        }
+    }
+
+    /// Start recording the marked bodies, or the highlighted one if none are marked, to `path` every `interval`
+    /// of sim time. Stops the current recording if `path` is empty.
+    fn toggle_recording(&mut self, path: &str, interval: f64) {
+        if let Some(recorder) = self.recorder.take() {
+            let rows = recorder.rows();
+            self.status = match recorder.finish() {
+                Ok(_) => tr!(self.text, "status-recording-stopped", rows = rows),
+                Err(e) => tr!(self.text, "status-recording-failed", error = e),
+            };
+        }
+        if path.is_empty() {
+            return;
+        }
+        let mut bodies: Vec<ObjectId> = self.selection.iter().collect();
+        if bodies.is_empty() {
+            bodies.extend(self.selected_id());
+        }
+        let count = bodies.len();
+        let started = File::create(path)
+            .and_then(|file| TelemetryRecorder::new(BufWriter::new(file), bodies, interval))
+            .and_then(|mut recorder| recorder.record(self.sim.store(), self.sim.sim_time()).map(|_| recorder));
+        self.status = match started {
+            Ok(recorder) => {
+                self.recorder = Some(recorder);
+                tr!(self.text, "status-recording", count = count, path = path)
+            }
+            Err(e) => tr!(self.text, "status-recording-failed", error = e),
+        };
+    }
+
+    fn set_step_index(&mut self, step_index: usize) {
+        self.step_index = step_index;
+        // Clock commands cannot fail
+        let _ = self.sim.execute(Command::SetTimeScale(STEP_SIZES[step_index].0 / TICK.as_secs_f64()));
+    }
+
+    fn step(&mut self, direction: f64) {
+        self.set_sim_time(self.sim.sim_time() + direction * STEP_SIZES[self.step_index].0);
+    }
+
+    /// Jump to the sim time under the mouse if it is on the timeline bar.
+    fn scrub(&mut self, column: u16, row: u16) {
+        let area = self.timeline_area;
+        if row == area.y && (area.x..area.x + area.width).contains(&column) {
+            self.set_sim_time(self.timeline.time_at(column - area.x, area.width));
+        }
+    }
+
+    fn toggle_bookmark(&mut self) {
+        match self.bookmarks.iter().position(|bookmark| *bookmark == self.sim.sim_time()) {
+            Some(index) => {
+                self.bookmarks.remove(index);
+            }
+            None => {
+                self.bookmarks.push(self.sim.sim_time());
+                self.bookmarks.sort_by(f64::total_cmp);
+            }
+        }
+    }
+
+    /// File the views of the system are saved in, `None` without a system file.
+    fn views_path(&self) -> Option<String> {
+        self.source.as_ref().map(|source| format!("{}.views.json", source))
+    }
+
+    /// Load the saved views, a missing or broken file leaves the views empty.
+    fn load_views(&mut self) {
+        let Some(path) = self.views_path() else {
+            return;
+        };
+        if let Ok(content) = fs::read_to_string(&path) {
+            match serde_json::from_str(&content) {
+                Ok(views) => self.views = views,
+                Err(e) => tracing::warn!(path, error = %e, "ignoring broken views file"),
+            }
+        }
+    }
+
+    /// File the scenario of the system is saved in, `None` without a system file.
+    fn scenario_path(&self) -> Option<String> {
+        self.source.as_ref().map(|source| format!("{}.scenario.json", source))
+    }
+
+    /// Save a preset picked on the command line with the system, or use the saved one. A missing or broken file
+    /// keeps the default.
+    fn load_scenario(&mut self) {
+        let Some(path) = self.scenario_path() else {
+            return;
+        };
+        if let Some(preset) = self.preset {
+            let written = serde_json::to_string_pretty(&Scenario { preset }).map_err(io::Error::other)
+                .and_then(|content| fs::write(&path, content));
+            if let Err(e) = written {
+                tracing::warn!(path, error = %e, "could not save the scenario");
+            }
+            return;
+        }
+        if let Ok(content) = fs::read_to_string(&path) {
+            match serde_json::from_str::<Scenario>(&content) {
+                Ok(scenario) => self.sim.set_realism(scenario.preset.realism()),
+                Err(e) => tracing::warn!(path, error = %e, "ignoring broken scenario file"),
+            }
+        }
+    }
+
+    /// Save the highlighted body and frame as a view named `name`, replacing an older one with that name.
+    fn save_view(&mut self, name: &str) {
+        let store = self.sim.store();
+        let name_of = |id: ObjectId| store.get(id).map(|object| object.name.clone());
+        // Wide enough to show both the frame and the followed body.
+        let zoom = self.selected_id()
+            .and_then(|id| store.position_in_frame(id, self.frame.unwrap_or(store.root_id())))
+            .map_or(0.0, |position| 2.0 * position.length());
+        self.views.save(CameraBookmark {
+            name: name.to_string(),
+            frame: self.frame.and_then(name_of),
+            follow: self.selected_id().and_then(name_of),
+            offset: DVec3::ZERO,
+            zoom,
+        });
+        self.status = match self.write_views() {
+            Ok(()) => tr!(self.text, "status-view-saved", name = name),
+            Err(e) => tr!(self.text, "status-view-save-failed", error = e),
+        };
+    }
+
+    fn write_views(&self) -> io::Result<()> {
+        match self.views_path() {
+            Some(path) => fs::write(path, serde_json::to_string_pretty(&self.views).map_err(io::Error::other)?),
+            None => Ok(()),
+        }
+    }
+
+    /// Move the highlight and frame to a saved view.
+    fn jump_to_view(&mut self, view: &CameraBookmark) {
+        let Some(resolved) = view.view(self.sim.store()) else {
+            self.status = tr!(self.text, "status-view-gone", name = view.name);
+            return;
+        };
+        let root = self.sim.store().root_id();
+        self.frame = Some(resolved.frame).filter(|frame| *frame != root);
+        self.tree_state.select(Some(resolved.follow.unwrap_or(resolved.frame).0 as usize));
+        self.status = tr!(self.text, "status-view", name = view.name);
+    }
+
+    /// Start the tutorial on the first body orbiting the root, `:tutorial skip` and `:tutorial stop` move past a step
+    /// or end it early.
+    fn tutorial_command(&mut self, argument: &str) {
+        match argument {
+            "skip" => {
+                if let Some(tutorial) = self.tutorial.as_mut() {
+                    tutorial.skip();
+                }
+            }
+            "stop" => self.tutorial = None,
+            _ => {
+                let store = self.sim.store();
+                let Some(planet) = store.children(store.root_id()).first().and_then(|id| store.get(*id)) else {
+                    self.status = self.text.get("status-tutorial-unavailable").to_string();
+                    return;
+                };
+                let step = |prompt: &str, goal| TutorialStep { prompt: prompt.to_string(), goal };
+                self.tutorial = Some(Tutorial::new(vec![
+                    step("tutorial-select", Goal::Select(planet.name.clone())),
+                    step("tutorial-frame", Goal::Frame(planet.name.clone())),
+                    step("tutorial-run", Goal::Run(STEP_SIZES[3].0 / TICK.as_secs_f64())),
+                    step("tutorial-orbit", Goal::ChangeOrbit(planet.name.clone())),
+                ]));
+            }
+        }
+    }
+
+    /// Report the highlighted body, the frame and the clock to the tutorial, which checks them against its current goal.
+    fn update_tutorial(&mut self) {
+        let Some(tutorial) = self.tutorial.as_mut() else {
+            return;
+        };
+        let store = self.sim.store();
+        let name = |id: ObjectId| store.get(id).map(|object| object.name.as_str());
+        let mut actions = vec![Action::Clock(self.sim.clock())];
+        actions.extend(self.tree_state.selected().and_then(|selected| name(ObjectId(selected as u32))).map(Action::Select));
+        actions.extend(self.frame.and_then(name).map(Action::Frame));
+        for action in actions {
+            tutorial.notify(action);
+        }
+        if tutorial.is_finished() {
+            self.tutorial = None;
+            self.status = self.text.get("status-tutorial-done").to_string();
+        }
+    }
+
+    /// Rebuild the tree pane after bodies may have been added or removed. Ids change with them, so marks, the
+    /// frame and a recording cannot be carried over.
+    fn refresh_tree(&mut self) {
+        self.rows = tree_rows(self.sim.store());
+        if self.tree_state.selected().is_some_and(|selected| selected >= self.rows.len()) {
+            self.tree_state.select_last();
+        }
+        self.toggle_recording("", 0.0);
+        self.selection.clear();
+        self.frame = None;
+    }
+
+    /// Undo or redo the latest change to the system and show how far the history reaches
+    fn step_history(&mut self, redo: bool) {
+        let stepped = if redo { self.sim.redo() } else { self.sim.undo() };
+        if stepped.as_ref().is_some_and(Command::changes_structure) {
+            self.refresh_tree();
+        }
+        let (undo_count, redo_count) = self.sim.history();
+        let key = match stepped {
+            None => "status-history-end",
+            Some(_) if redo => "status-redone",
+            Some(_) => "status-undone",
+        };
+        self.status = tr!(self.text, key, undo = undo_count, redo = redo_count);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        // Status messages are shown until the next key press.
+        self.status.clear();
+        let code = key.code;
+        if let Some(command) = self.command.as_mut() {
+            match code {
+                KeyCode::Enter => {
+                    let input = self.command.take().unwrap_or_default();
+                    self.run_command(input.trim());
+                }
+                KeyCode::Esc => self.command = None,
+                KeyCode::Backspace => {
+                    command.pop();
+                }
+                KeyCode::Char(c) => command.push(c),
+                _ => {}
+            }
+            return;
+        }
+
+        match code {
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => self.step_history(false),
+            KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => self.step_history(true),
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.tree_state.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.tree_state.select_previous(),
+            KeyCode::PageDown => self.tree_state.scroll_down_by(10),
+            KeyCode::PageUp => self.tree_state.scroll_up_by(10),
+            KeyCode::Home => self.tree_state.select_first(),
+            KeyCode::End => self.tree_state.select_last(),
+            KeyCode::Right | KeyCode::Char('.') => self.step(1.0),
+            KeyCode::Left | KeyCode::Char(',') => self.step(-1.0),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.set_step_index((self.step_index + 1).min(STEP_SIZES.len() - 1)),
+            KeyCode::Char('-') => self.set_step_index(self.step_index.saturating_sub(1)),
+            KeyCode::Char(' ') => {
+                let _ = self.sim.execute(Command::SetPaused(!self.sim.clock().paused));
+            }
+            KeyCode::Char(':') | KeyCode::Char('`') => self.command = Some(String::new()),
+            KeyCode::Char('m') => {
+                if let Some(selected) = self.tree_state.selected() {
+                    self.selection.toggle(ObjectId(selected as u32));
+                }
+            }
+            KeyCode::Char('u') => self.selection.clear(),
+            KeyCode::Char('c') => {
+                self.color_mode = self.color_mode.next();
+                self.status = tr!(self.text, "status-color-mode", mode = self.text.color_mode(self.color_mode));
+            }
+            KeyCode::Char('b') => self.toggle_bookmark(),
+            KeyCode::Char(digit @ '1'..='9') => {
+                let slot = digit as usize - '1' as usize;
+                if let Some(view) = self.views.slot(slot).cloned() {
+                    self.jump_to_view(&view);
+                }
+            }
+            KeyCode::Char('[') => {
+                if let Some(bookmark) = self.bookmarks.iter().rev().find(|bookmark| **bookmark < self.sim.sim_time()) {
+                    self.set_sim_time(*bookmark);
+                }
+            }
+            KeyCode::Char(']') => {
+                if let Some(bookmark) = self.bookmarks.iter().find(|bookmark| **bookmark > self.sim.sim_time()) {
+                    self.set_sim_time(*bookmark);
+                }
+            }
+            KeyCode::Char('f') => {
+                // Pressing `f` on the current frame switches back to the root.
+                let selected = self.selected_id();
+                self.frame = if selected == self.frame { None } else { selected };
+            }
+            _ => {}
+        }
+    }
+
+    /// Execute a command typed at the `:` prompt, either `lang <code>`, `mark <query>`, `record [<file> [<interval>]]`,
+    /// `view <name>`, `view save <name>`, `view delete <name>`, `tutorial [skip|stop]`, a time command using the same syntax as the old line prompt or a console command, see [`console::parse`].
+    fn run_command(&mut self, input: &str) {
+        if let Some(query) = input.strip_prefix("mark ") {
+            self.status = match Query::parse(query) {
+                Ok(query) => {
+                    self.selection.clear();
+                    let matches = query.select(self.sim.store());
+                    for id in &matches {
+                        self.selection.insert(*id);
+                    }
+                    tr!(self.text, "status-marked", count = matches.len())
+                }
+                Err(e) => tr!(self.text, "status-invalid-query", error = e),
+            };
+            return;
+        }
+        if let Some(arguments) = input.strip_prefix("record") {
+            let mut arguments = arguments.split_whitespace();
+            let path = arguments.next().unwrap_or("");
+            match arguments.next().map_or(Some(86400.0), parse_signed_timestep) {
+                Some(interval) => self.toggle_recording(path, interval),
+                None => self.status = tr!(self.text, "status-invalid-command", input = input),
+            }
+            return;
+        }
+        if let Some(argument) = input.strip_prefix("tutorial") {
+            self.tutorial_command(argument.trim());
+            return;
+        }
+        if let Some(arguments) = input.strip_prefix("view ") {
+            let arguments = arguments.trim();
+            if let Some(name) = arguments.strip_prefix("save ") {
+                self.save_view(name.trim());
+            } else if let Some(name) = arguments.strip_prefix("delete ") {
+                let name = name.trim();
+                self.status = if !self.views.remove(name) {
+                    tr!(self.text, "status-view-missing", name = name)
+                } else if let Err(e) = self.write_views() {
+                    tr!(self.text, "status-view-save-failed", error = e)
+                } else {
+                    tr!(self.text, "status-view-deleted", name = name)
+                };
+            } else {
+                match self.views.get(arguments).cloned() {
+                    Some(view) => self.jump_to_view(&view),
+                    None => self.status = tr!(self.text, "status-view-missing", name = arguments),
+                }
+            }
+            return;
+        }
+        if let Some(language) = input.strip_prefix("lang ") {
+            let language = language.trim();
+            self.status = match Catalog::new(language) {
+                Ok(catalog) => {
+                    self.text = catalog;
+                    tr!(self.text, "status-language", language = self.text.get("language-name"))
+                }
+                Err(_) => tr!(self.text, "status-unknown-language", language = language),
+            };
+            return;
+        }
+        match parse_time_command(input) {
+            Some(TimeCommand::Step(seconds)) => self.set_sim_time(self.sim.sim_time() + seconds),
+            Some(TimeCommand::Jump(seconds)) => self.set_sim_time(seconds),
+            None => self.run_console_command(input),
+        }
+    }
+
+    fn run_console_command(&mut self, input: &str) {
+        match console::parse(input, self.sim.store()) {
+            Ok(ConsoleCommand::Execute(command)) => {
+                // With signal delay burns are sent from the reference frame
+                let control = self.frame.and_then(|frame| self.sim.store().get(frame)).map(|frame| frame.name.clone());
+                self.sim.set_control(control);
+                match self.sim.execute((*command).clone()) {
+                    Ok(()) => {
+                        self.session.record(&command);
+                        if let Some(tutorial) = self.tutorial.as_mut() {
+                            tutorial.notify(Action::Execute(&command));
+                        }
+                        if command.changes_structure() {
+                            self.refresh_tree();
+                        }
+                    }
+                    Err(e) => self.status = tr!(self.text, "status-command-failed", error = e),
+                }
+            }
+            Ok(ConsoleCommand::Focus(id)) => {
+                self.tree_state.select(Some(id.0 as usize));
+                self.frame = Some(id);
+            }
+            Ok(ConsoleCommand::Reload) => self.reload(),
+            Ok(ConsoleCommand::Autopilot(plan)) => {
+                let now = self.sim.sim_time();
+                for maneuver in plan.maneuvers {
+                    if let Err(e) = self.sim.schedule(now + maneuver.delay, maneuver.command.clone()) {
+                        self.status = tr!(self.text, "status-command-failed", error = e);
+                        return;
+                    }
+                    self.session.record(&maneuver.command);
+                }
+                self.status = tr!(self.text, "status-autopilot", craft = plan.craft, departure = format_sim_time(now + plan.departure),
+                                  arrival = format_sim_time(now + plan.arrival), delta_v = format!("{:.2}", plan.delta_v));
+            }
+            Err(e) => self.status = tr!(self.text, "status-command-failed", error = e),
+        }
+    }
+
+    fn selected_id(&self) -> Option<ObjectId> {
+        self.tree_state.selected().map(|selected| ObjectId(selected as u32))
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, timeline, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(3), Constraint::Length(3)]).areas(frame.area());
+        // Stack the panes on narrow terminals instead of squeezing them side by side.
+        let panes = if main.width < 80 {
+            Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)])
+        } else {
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+        };
+        let [tree, detail] = panes.areas(main);
+
+        self.draw_tree(frame, tree);
+        self.draw_detail(frame, detail);
+        self.draw_timeline(frame, timeline);
+        self.draw_footer(frame, footer);
+    }
+
+    fn draw_timeline(&mut self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title(tr!(self.text, "title-timeline",
+                                                start = format_sim_time(self.timeline.start),
+                                                end = format_sim_time(self.timeline.end)));
+        self.timeline_area = block.inner(area);
+        let width = self.timeline_area.width;
+        let mut bar = vec!['─'; width as usize];
+        for bookmark in &self.bookmarks {
+            if let Some(column) = self.timeline.column_of(*bookmark, width) {
+                bar[column as usize] = '|';
+            }
+        }
+        // Point at the edge the current time lies beyond if it is out of range.
+        let (cursor, symbol) = match self.timeline.column_of(self.sim.sim_time(), width) {
+            Some(column) => (Some(column), '█'),
+            None if self.sim.sim_time() < self.timeline.start => (Some(0), '◀'),
+            None => (width.checked_sub(1), '▶'),
+        };
+        if let Some(cursor) = cursor.filter(|cursor| *cursor < width) {
+            bar[cursor as usize] = symbol;
+        }
+        frame.render_widget(Paragraph::new(bar.into_iter().collect::<String>()).block(block), area);
+    }
+
+    fn draw_tree(&mut self, frame: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self.rows.iter()
+            .enumerate()
+            .map(|(index, depth)| {
+                let id = ObjectId(index as u32);
+                let name = self.sim.store().get(id).map_or("", |object| object.name.as_str());
+                let marker = if self.selection.contains(id) { "*" } else { " " };
+                let frame = if self.frame == Some(id) { self.text.get("tree-frame") } else { "" };
+                let style = self.color_mode.color(self.sim.store(), id).map_or(Style::new(), |color| Style::new().fg(rgb(color)));
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{}{}", marker, "  ".repeat(*depth))),
+                    Span::styled(name.to_string(), style),
+                    Span::raw(format!(" {}", frame)),
+                ]))
+            })
+            .collect();
+        // The legend explains the colors along the bottom border.
+        let legend: Vec<Span> = self.color_mode.legend().into_iter()
+            .flat_map(|entry| [Span::styled("■", Style::new().fg(rgb(entry.color))), Span::raw(format!(" {} ", entry.label))])
+            .collect();
+        let block = Block::bordered()
+            .title(self.text.get("title-system"))
+            .title_bottom(Line::from(legend));
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.tree_state);
+    }
+
+    fn draw_detail(&self, frame: &mut Frame, area: Rect) {
+        let mut lines = self.group_lines();
+        if let Some(id) = self.selected_id() {
+            lines.extend(self.detail_lines(id));
+            if id == self.sim.store().root_id() {
+                lines.extend(stats_lines(&self.text, &SystemStats::compute(self.sim.store().root())));
+            }
+        }
+        frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(self.text.get("title-details"))), area);
+    }
+
+    fn detail_lines(&self, id: ObjectId) -> Vec<Line<'static>> {
+        let frame = self.frame.unwrap_or(self.sim.store().root_id());
+        let (Some(object), Some(frame_object), Some(position)) =
+            (self.sim.store().get(id), self.sim.store().get(frame), self.sim.store().position_in_frame(id, frame)) else {
+            return Vec::new();
+        };
+        detail_lines(&self.text, object, position, &frame_object.name, stability_band(self.sim.store(), id).as_ref())
+    }
+
+    /// Summary of the marked bodies, empty unless at least two are marked.
+    fn group_lines(&self) -> Vec<Line<'static>> {
+        let Some(group) = self.selection.group(self.sim.epoch()).filter(|group| group.count > 1) else {
+            return Vec::new();
+        };
+        let shared_parent = group.shared_parent
+            .and_then(|parent| self.sim.epoch().get(parent))
+            .map_or(self.text.get("group-no-parent"), |parent| parent.name.as_str());
+        vec![
+            Line::from(tr!(self.text, "group-title", count = group.count)).style(Style::new().add_modifier(Modifier::BOLD)),
+            Line::from(tr!(self.text, "group-mass", mass = format!("{:.4e}", group.combined_mass))),
+            Line::from(tr!(self.text, "group-parent", parent = shared_parent)),
+            Line::from(""),
+        ]
+    }
+
+    fn draw_footer(&self, frame: &mut Frame, area: Rect) {
+        let text = match &self.command {
+            Some(command) => format!(":{}", command),
+            None if !self.status.is_empty() => self.status.clone(),
+            None => {
+                let key = if !self.sim.clock().paused { "footer-status-running" } else { "footer-status" };
+                let status = tr!(self.text, key, time = format_sim_time(self.sim.sim_time()), step = STEP_SIZES[self.step_index].1);
+                // The tutorial prompt replaces the key help while it runs.
+                let help = match self.tutorial.as_ref().and_then(|tutorial| tutorial.current().map(|step| (tutorial.progress(), step))) {
+                    Some(((done, steps), step)) => tr!(self.text, "tutorial-progress", step = done + 1, steps = steps,
+                                                       prompt = tr!(self.text, &step.prompt, body = step.goal.body().unwrap_or_default())),
+                    None => self.text.get("footer-help").to_string(),
+                };
+                format!("{}  |  {}", status, help)
+            }
+        };
+        frame.render_widget(Paragraph::new(text).block(Block::bordered()), area);
+    }
+}
+
+/// Summary printed after the interactive view closes, with the totals of all sessions if a profile is kept.
+pub fn summary(text: &Catalog, session: &SessionStats, lifetime: Option<&LifetimeStats>) -> String {
+    let mut lines = vec![
+        text.get("summary-title").to_string(),
+        tr!(text, "summary-sim-time", time = format_sim_time(session.sim_time)),
+        tr!(text, "summary-visited", count = session.visited.len()),
+        tr!(text, "summary-changes", maneuvers = session.maneuvers, changes = session.changes),
+    ];
+    if let Some(lifetime) = lifetime {
+        lines.push(tr!(text, "summary-lifetime", sessions = lifetime.sessions, time = format_sim_time(lifetime.sim_time),
+                       visited = lifetime.visited.len(), maneuvers = lifetime.maneuvers));
+    }
+    lines.join("\n")
+}
+
+/// Format a signal delay in seconds as e.g. "8 min 19 s" or "1 h 12 min".
+fn format_delay(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    match seconds {
+        0..60 => format!("{} s", seconds),
+        60..3600 => format!("{} min {} s", seconds / 60, seconds % 60),
+        _ => format!("{} h {} min", seconds / 3600, seconds / 60 % 60),
+    }
+}
+
+/// Terminal color of a linear RGB color from the data layer.
+fn rgb(color: [f32; 3]) -> Color {
+    let [r, g, b] = color.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+    Color::Rgb(r, g, b)
+}
+
+/// Depth of every body in id order, which is the order of the tree pane.
+fn tree_rows(system: &SystemStore) -> Vec<usize> {
+    system.root().iter().map(|item| item.depth).collect()
+}
+
+/// Summary of the whole system, shown below the details of the root.
+fn stats_lines(text: &Catalog, stats: &SystemStats) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(text.get("stats-title").to_string()).style(Style::new().add_modifier(Modifier::BOLD)),
+        Line::from(tr!(text, "stats-bodies", count = stats.bodies(), mass = format!("{:.4e}", stats.total_mass))),
+        Line::from(tr!(text, "stats-types", stars = stats.stars, rocky = stats.rocky, jovian = stats.jovian,
+                       ice_giants = stats.ice_giants, moons = stats.moons)),
+        Line::from(tr!(text, "stats-atmospheres", count = stats.with_atmosphere)),
+    ];
+    if let (Some(innermost), Some(outermost)) = (&stats.innermost, &stats.outermost) {
+        lines.push(Line::from(tr!(text, "stats-orbits", inner = innermost.name, inner_distance = format!("{:.0}", innermost.value),
+                                  outer = outermost.name, outer_distance = format!("{:.0}", outermost.value))));
+    }
+    if let Some(moon) = &stats.largest_moon {
+        lines.push(Line::from(tr!(text, "stats-largest-moon", name = moon.name, mass = format!("{:.4e}", moon.value))));
+    }
+    lines
+}
+
+/// Lines shown in the detail pane for the highlighted body.
+fn detail_lines(text: &Catalog, object: &Object, position: DVec3, frame: &str, band: Option<&StabilityBand>) -> Vec<Line<'static>> {
+    let orbit = &object.orbital_params;
+    let mut lines = vec![
+        Line::from(object.name.clone()).style(Style::new().add_modifier(Modifier::BOLD)),
+        Line::from(tr!(text, "detail-type", type = text.object_type(&object.object_type))),
+        Line::from(tr!(text, "detail-mass", mass = format!("{:.4e}", object.mass))),
+        Line::from(tr!(text, "detail-radius", radius = object.radius)),
+        Line::from(""),
+        Line::from(tr!(text, "detail-semi-major-axis", value = orbit.semi_major_axis)),
+        Line::from(tr!(text, "detail-eccentricity", value = orbit.eccentricity)),
+        Line::from(tr!(text, "detail-longitude-of-periapsis", value = orbit.longitude_of_periapsis)),
+        Line::from(tr!(text, "detail-mean-anomaly", value = format!("{:.3}", orbit.mean_anomaly))),
+        Line::from(tr!(text, "detail-true-anomaly", value = format!("{:.3}", orbit.true_anomaly()))),
+        Line::from(tr!(text, "detail-periapsis", distance = format!("{:.0}", orbit.periapsis()),
+                       longitude = orbit.longitude_of_periapsis)),
+        Line::from(tr!(text, "detail-apoapsis", distance = format!("{:.0}", orbit.apoapsis()),
+                       longitude = (orbit.longitude_of_periapsis + 180) % 360)),
+        Line::from(tr!(text, "detail-children", count = object.children.len())),
+        Line::from(tr!(text, "detail-position", frame = frame,
+                       x = format!("{:.0}", position.x), y = format!("{:.0}", position.y))),
+        Line::from(tr!(text, "detail-light-delay", frame = frame, delay = format_delay(position.length() / SPEED_OF_LIGHT))),
+    ];
+    if let Some(band) = band {
+        lines.push(Line::from(tr!(text, "detail-stable-orbits", inner = format!("{:.0}", band.inner),
+                                  outer = format!("{:.0}", band.outer), slots = band.capacity())));
+    }
+    if !object.atmosphere.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(text.get("detail-atmosphere").to_string()));
+        let mut gases: Vec<_> = object.atmosphere.iter().collect();
+        gases.sort_by(|a, b| b.1.total_cmp(a.1));
+        for (gas, pressure) in gases {
+            lines.push(Line::from(format!("  {}", tr!(text, "detail-partial-pressure", gas = gas, pressure = pressure))));
+        }
+    }
+    lines
+}
@@ -0,0 +1,49 @@
+use std::process::ExitCode;
+use magnetar_data::prelude::{check_yaml, load_yaml, validate, Issue, Severity, SystemStats};
+use ratatui::crossterm::style::Stylize;
+use crate::palette::Palette;
+
+/// Validate a system file and print a colored report followed by a summary of the system, fails if any errors were found.
+pub fn run(file_path: &str) -> ExitCode {
+    let palette = Palette::get();
+    let mut issues = match check_yaml(file_path) {
+        Ok(issues) => issues,
+        Err(e) => {
+            eprintln!("{} {}", "error:".with(palette.bad()).bold(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+    // The physics pass needs a fully parsed tree, only run it once the schema is sound.
+    let mut stats = None;
+    if issues.iter().all(|issue| issue.severity != Severity::Error) {
+        match load_yaml(file_path) {
+            Ok(system) => {
+                issues.extend(validate(&system));
+                stats = Some(SystemStats::compute(&system));
+            }
+            Err(e) => issues.push(Issue::error("", e)),
+        }
+    }
+
+    for issue in &issues {
+        let severity = match issue.severity {
+            Severity::Error => "error:".with(palette.bad()).bold(),
+            Severity::Warning => "warning:".with(palette.warn()).bold(),
+        };
+        println!("{} {} {}", severity, issue.path.clone().with(palette.accent()), issue.message);
+    }
+
+    let errors = issues.iter().filter(|issue| issue.severity == Severity::Error).count();
+    let warnings = issues.len() - errors;
+    let summary = format!("{}: {} error(s), {} warning(s)", file_path, errors, warnings);
+    if errors > 0 {
+        println!("{}", summary.with(palette.bad()));
+        ExitCode::FAILURE
+    } else {
+        println!("{}", summary.with(palette.good()));
+        if let Some(stats) = stats {
+            print!("{}", stats);
+        }
+        ExitCode::SUCCESS
+    }
+}
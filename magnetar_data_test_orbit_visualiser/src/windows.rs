@@ -0,0 +1,44 @@
+use std::ptr;
+use magnetar_data::approach::{closest_approaches, conjunctions};
+use magnetar_data::prelude::Object;
+use magnetar_data::transfer::{hohmann, synodic_period, transfer_windows};
+use crate::format_sim_time;
+
+/// Print the Hohmann transfer windows, conjunctions and closest approaches between two bodies
+/// orbiting the same parent between `start` and `start + span`.
+pub fn run(system: &Object, origin: &str, target: &str, start: f64, span: f64, resolution: f64) -> Result<(), String> {
+    if resolution <= 0.0 {
+        return Err("Resolution must be positive".to_string());
+    }
+    let parent = system.find_parent(origin).ok_or(format!("No orbiting body named {}", origin))?;
+    if !system.find_parent(target).is_some_and(|target_parent| ptr::eq(parent, target_parent)) {
+        return Err(format!("{} and {} have to orbit the same parent", origin, target));
+    }
+    let find = |name: &str| parent.children.iter().find(|child| child.name == name).map(|child| &child.orbital_params);
+    let (origin_orbit, target_orbit) = (find(origin).unwrap(), find(target).unwrap());
+    let end = start + span;
+
+    let transfer = hohmann(origin_orbit, target_orbit, parent.mass);
+    println!("Hohmann transfer {} -> {} around {}", origin, target, parent.name);
+    println!("  Delta-v: {:.3} + {:.3} = {:.3} km/s",
+             transfer.departure_delta_v, transfer.arrival_delta_v, transfer.total_delta_v());
+    println!("  Flight time: {}", format_sim_time(transfer.transfer_time));
+    println!("  Phase angle: {:.2}°", transfer.phase_angle);
+    println!("  Synodic period: {}", format_sim_time(synodic_period(origin_orbit, target_orbit, parent.mass)));
+
+    println!("\nTransfer windows (departure, arrival):");
+    for departure in transfer_windows(origin_orbit, target_orbit, parent.mass, start, end, resolution) {
+        println!("  {:>12} {:>12}", format_sim_time(departure), format_sim_time(departure + transfer.transfer_time));
+    }
+
+    println!("\nConjunctions (time, separation):");
+    for conjunction in conjunctions(origin_orbit, target_orbit, parent.mass, start, end, resolution) {
+        println!("  {:>12} {:>16.0} km", format_sim_time(conjunction.time), conjunction.distance);
+    }
+
+    println!("\nClosest approaches (time, separation):");
+    for approach in closest_approaches(origin_orbit, target_orbit, parent.mass, start, end, resolution) {
+        println!("  {:>12} {:>16.0} km", format_sim_time(approach.time), approach.distance);
+    }
+    Ok(())
+}
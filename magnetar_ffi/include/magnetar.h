@@ -0,0 +1,94 @@
+#ifndef MAGNETAR_H
+#define MAGNETAR_H
+
+/* Generated by cbindgen from magnetar_ffi, do not edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Opaque handle to a loaded system
+ */
+typedef struct MagnetarSystem MagnetarSystem;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Load a system from the contents of a YAML system file.
+ * Returns null if `yaml` is null, not valid UTF-8 or not a valid system.
+ *
+ * # Safety
+ * `yaml` has to be null or a valid nul terminated string.
+ */
+struct MagnetarSystem *magnetar_system_load(const char *yaml);
+
+/**
+ * Free a system returned by `magnetar_system_load`, null is ignored.
+ *
+ * # Safety
+ * `system` has to be null or a handle returned by `magnetar_system_load` which was not freed yet.
+ */
+void magnetar_system_free(struct MagnetarSystem *system);
+
+/**
+ * Step the whole system by a signed amount of seconds.
+ *
+ * # Safety
+ * `system` has to be null or a valid handle.
+ */
+void magnetar_system_step(struct MagnetarSystem *system, double seconds);
+
+/**
+ * Number of bodies in the system including the root, 0 for null.
+ *
+ * # Safety
+ * `system` has to be null or a valid handle.
+ */
+uintptr_t magnetar_system_body_count(const struct MagnetarSystem *system);
+
+/**
+ * Index of the first body with the given name, -1 if there is none.
+ *
+ * # Safety
+ * `system` has to be null or a valid handle, `name` null or a valid nul terminated string.
+ */
+int64_t magnetar_system_find(const struct MagnetarSystem *system, const char *name);
+
+/**
+ * Name of the body at `index`, null if out of range.
+ * The string is owned by the system and valid until it is freed.
+ *
+ * # Safety
+ * `system` has to be null or a valid handle.
+ */
+const char *magnetar_system_body_name(const struct MagnetarSystem *system, uintptr_t index);
+
+/**
+ * Index of the parent of the body at `index`, -1 for the root or if out of range.
+ *
+ * # Safety
+ * `system` has to be null or a valid handle.
+ */
+int64_t magnetar_system_body_parent(const struct MagnetarSystem *system, uintptr_t index);
+
+/**
+ * Write the position of the body at `index` relative to the root in km to `x` and `y`.
+ * Returns false and leaves both untouched if the index is out of range.
+ *
+ * # Safety
+ * `system` has to be null or a valid handle, `x` and `y` valid pointers to writable doubles.
+ */
+bool magnetar_system_body_position(const struct MagnetarSystem *system,
+                                   uintptr_t index,
+                                   double *x,
+                                   double *y);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MAGNETAR_H */
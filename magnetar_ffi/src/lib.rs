@@ -0,0 +1,136 @@
+//! Minimal C ABI for embedding the Magnetar orbital model in other tools and engines.
+//!
+//! Systems are loaded from YAML strings into an opaque `MagnetarSystem` handle, bodies are addressed by
+//! their index in depth first order, the root is always index 0. The header lives in `include/magnetar.h`.
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+use magnetar_data::store::{ObjectId, SystemStore};
+use magnetar_data::yaml_parser::load_yaml_str;
+
+/// Opaque handle to a loaded system
+pub struct MagnetarSystem {
+    store: SystemStore,
+    /// Names of all bodies indexed like the store, kept alive for `magnetar_system_body_name`
+    names: Vec<CString>,
+}
+
+impl MagnetarSystem {
+    fn id(&self, index: usize) -> Option<ObjectId> {
+        (index < self.store.len()).then_some(ObjectId(index as u32))
+    }
+}
+
+/// Load a system from the contents of a YAML system file.
+/// Returns null if `yaml` is null, not valid UTF-8 or not a valid system.
+///
+/// # Safety
+/// `yaml` has to be null or a valid nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn magnetar_system_load(yaml: *const c_char) -> *mut MagnetarSystem {
+    if yaml.is_null() {
+        return ptr::null_mut();
+    }
+    let Ok(yaml) = CStr::from_ptr(yaml).to_str() else {
+        return ptr::null_mut();
+    };
+    let Ok(system) = load_yaml_str(yaml) else {
+        return ptr::null_mut();
+    };
+    let store = SystemStore::new(system);
+    let names = store.ids()
+        .map(|id| {
+            let name = store.get(id).map_or("", |object| object.name.as_str());
+            // Names with interior nul bytes cannot be represented and are returned as empty strings
+            CString::new(name).unwrap_or_default()
+        })
+        .collect();
+    Box::into_raw(Box::new(MagnetarSystem { store, names }))
+}
+
+/// Free a system returned by `magnetar_system_load`, null is ignored.
+///
+/// # Safety
+/// `system` has to be null or a handle returned by `magnetar_system_load` which was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn magnetar_system_free(system: *mut MagnetarSystem) {
+    if !system.is_null() {
+        drop(Box::from_raw(system));
+    }
+}
+
+/// Step the whole system by a signed amount of seconds.
+///
+/// # Safety
+/// `system` has to be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn magnetar_system_step(system: *mut MagnetarSystem, seconds: f64) {
+    if let Some(system) = system.as_mut() {
+        system.store.step_by(seconds);
+    }
+}
+
+/// Number of bodies in the system including the root, 0 for null.
+///
+/// # Safety
+/// `system` has to be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn magnetar_system_body_count(system: *const MagnetarSystem) -> usize {
+    system.as_ref().map_or(0, |system| system.store.len())
+}
+
+/// Index of the first body with the given name, -1 if there is none.
+///
+/// # Safety
+/// `system` has to be null or a valid handle, `name` null or a valid nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn magnetar_system_find(system: *const MagnetarSystem, name: *const c_char) -> i64 {
+    let (Some(system), false) = (system.as_ref(), name.is_null()) else {
+        return -1;
+    };
+    CStr::from_ptr(name).to_str().ok()
+        .and_then(|name| system.store.find(name))
+        .map_or(-1, |id| id.0 as i64)
+}
+
+/// Name of the body at `index`, null if out of range.
+/// The string is owned by the system and valid until it is freed.
+///
+/// # Safety
+/// `system` has to be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn magnetar_system_body_name(system: *const MagnetarSystem, index: usize) -> *const c_char {
+    system.as_ref()
+        .and_then(|system| system.names.get(index))
+        .map_or(ptr::null(), |name| name.as_ptr())
+}
+
+/// Index of the parent of the body at `index`, -1 for the root or if out of range.
+///
+/// # Safety
+/// `system` has to be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn magnetar_system_body_parent(system: *const MagnetarSystem, index: usize) -> i64 {
+    system.as_ref()
+        .and_then(|system| system.id(index).and_then(|id| system.store.parent(id)))
+        .map_or(-1, |parent| parent.0 as i64)
+}
+
+/// Write the position of the body at `index` relative to the root in km to `x` and `y`.
+/// Returns false and leaves both untouched if the index is out of range.
+///
+/// # Safety
+/// `system` has to be null or a valid handle, `x` and `y` valid pointers to writable doubles.
+#[no_mangle]
+pub unsafe extern "C" fn magnetar_system_body_position(system: *const MagnetarSystem, index: usize,
+                                                       x: *mut f64, y: *mut f64) -> bool {
+    if x.is_null() || y.is_null() {
+        return false;
+    }
+    let Some(position) = system.as_ref()
+        .and_then(|system| system.id(index).and_then(|id| system.store.absolute_position(id))) else {
+        return false;
+    };
+    (*x, *y) = (position.x, position.y);
+    true
+}
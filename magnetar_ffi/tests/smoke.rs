@@ -0,0 +1,41 @@
+use std::ffi::{CStr, CString};
+use magnetar_ffi::*;
+
+/// Loads a system through the C ABI, steps it and reads positions back
+#[test]
+fn test_round_trip() {
+    let yaml = CString::new(include_str!("../../magnetar_data/test_data/valid.yaml")).unwrap();
+    unsafe {
+        let system = magnetar_system_load(yaml.as_ptr());
+        assert!(!system.is_null());
+        assert_eq!(magnetar_system_body_count(system), 3);
+
+        let luna = CString::new("Luna").unwrap();
+        let index = magnetar_system_find(system, luna.as_ptr());
+        assert_eq!(index, 2);
+        assert_eq!(CStr::from_ptr(magnetar_system_body_name(system, 2)).to_str(), Ok("Luna"));
+        assert_eq!(magnetar_system_body_parent(system, 2), 1);
+        assert_eq!(magnetar_system_body_parent(system, 0), -1);
+
+        let (mut x, mut y) = (0.0, 0.0);
+        assert!(magnetar_system_body_position(system, 2, &mut x, &mut y));
+        let before = (x, y);
+        magnetar_system_step(system, 86400.0);
+        assert!(magnetar_system_body_position(system, 2, &mut x, &mut y));
+        assert_ne!(before, (x, y));
+        assert!(!magnetar_system_body_position(system, 3, &mut x, &mut y));
+
+        magnetar_system_free(system);
+    }
+}
+
+#[test]
+fn test_invalid_input() {
+    let yaml = CString::new("not: [a system").unwrap();
+    unsafe {
+        assert!(magnetar_system_load(yaml.as_ptr()).is_null());
+        assert!(magnetar_system_load(std::ptr::null()).is_null());
+        assert_eq!(magnetar_system_body_count(std::ptr::null()), 0);
+        magnetar_system_free(std::ptr::null_mut());
+    }
+}
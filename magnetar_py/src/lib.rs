@@ -0,0 +1,191 @@
+//! Python bindings for the Magnetar orbital model, built as the `magnetar` module with maturin.
+//!
+//! Bodies are addressed by name, all values use the units of the data crate: km, kg, s and degrees.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use pyo3::exceptions::{PyIOError, PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use magnetar_data::prelude::{load_yaml, load_yaml_str, to_yaml, validate, ObjectId, Query, SystemStore};
+use magnetar_data::tidal::tidal_heating;
+
+/// A loaded system and the time it was stepped by since loading
+#[pyclass(name = "System")]
+struct PySystem {
+    store: SystemStore,
+    /// Offset from the loaded epoch in seconds
+    sim_time: f64,
+}
+
+impl PySystem {
+    fn new(store: SystemStore) -> PySystem {
+        PySystem { store, sim_time: 0.0 }
+    }
+
+    fn id(&self, name: &str) -> PyResult<ObjectId> {
+        self.store.find(name).ok_or_else(|| PyKeyError::new_err(format!("no body named {}", name)))
+    }
+}
+
+#[pymethods]
+impl PySystem {
+    /// Offset from the loaded epoch in seconds
+    #[getter]
+    fn sim_time(&self) -> f64 {
+        self.sim_time
+    }
+
+    /// Step the whole system by a signed amount of seconds
+    fn step(&mut self, seconds: f64) {
+        self.store.step_by(seconds);
+        self.sim_time += seconds;
+    }
+
+    /// Names of all bodies in depth first order, starting with the root
+    fn bodies(&self) -> Vec<String> {
+        self.store.root().iter().map(|item| item.object.name.clone()).collect()
+    }
+
+    /// Names of the bodies matching a query like "type=ROCKY and parent=Sol and sma<2AU", in depth first order
+    fn select(&self, query: &str) -> PyResult<Vec<String>> {
+        let query = Query::parse(query).map_err(PyValueError::new_err)?;
+        Ok(query.select(&self.store).into_iter().map(|id| self.store.get(id).unwrap().name.clone()).collect())
+    }
+
+    /// Name of the body the given one orbits, `None` for the root
+    fn parent(&self, name: &str) -> PyResult<Option<String>> {
+        let parent = self.store.parent(self.id(name)?);
+        Ok(parent.and_then(|parent| self.store.get(parent)).map(|parent| parent.name.clone()))
+    }
+
+    /// Current orbital elements of a body
+    fn elements(&self, name: &str) -> PyResult<HashMap<&'static str, f64>> {
+        let orbit = &self.store.get(self.id(name)?).unwrap().orbital_params;
+        Ok(HashMap::from([
+            ("semi_major_axis", orbit.semi_major_axis),
+            ("eccentricity", orbit.eccentricity),
+            ("longitude_of_periapsis", orbit.longitude_of_periapsis as f64),
+            ("mean_anomaly", orbit.mean_anomaly),
+        ]))
+    }
+
+    /// Physical properties and quantities derived from the current orbit of a body
+    fn derived(&self, name: &str) -> PyResult<HashMap<&'static str, f64>> {
+        let id = self.id(name)?;
+        let object = self.store.get(id).unwrap();
+        let orbit = &object.orbital_params;
+        let parent_mass = self.store.parent_mass(id);
+        Ok(HashMap::from([
+            ("mass", object.mass),
+            ("radius", object.radius),
+            ("period", orbit.period(parent_mass)),
+            ("mean_motion", orbit.mean_motion(parent_mass)),
+            ("eccentric_anomaly", orbit.eccentric_anomaly()),
+            ("true_anomaly", orbit.true_anomaly()),
+            ("true_longitude", orbit.true_longitude()),
+            ("periapsis", orbit.periapsis()),
+            ("apoapsis", orbit.apoapsis()),
+            ("distance", orbit.radius()),
+            ("speed", orbit.velocity(parent_mass).length()),
+            ("tidal_heating", tidal_heating(object, parent_mass)),
+        ]))
+    }
+
+    /// Position of a body relative to the root in km
+    fn position(&self, name: &str) -> PyResult<(f64, f64)> {
+        let position = self.store.absolute_position(self.id(name)?).unwrap();
+        Ok((position.x, position.y))
+    }
+
+    /// Velocity of a body relative to its parent in km/s
+    fn velocity(&self, name: &str) -> PyResult<(f64, f64)> {
+        let id = self.id(name)?;
+        let velocity = self.store.get(id).unwrap().orbital_params.velocity(self.store.parent_mass(id));
+        Ok((velocity.x, velocity.y))
+    }
+
+    /// Run the physics validation pass, one formatted line per issue
+    fn validate(&self) -> Vec<String> {
+        validate(self.store.root()).iter().map(ToString::to_string).collect()
+    }
+
+    /// Serialize the current state in the system file format
+    fn to_yaml(&self) -> PyResult<String> {
+        to_yaml(self.store.root()).map_err(PyValueError::new_err)
+    }
+
+    /// Write the state of every body for `steps` steps of `step_seconds` to a CSV file,
+    /// starting with the current state as step 0. The system itself is not stepped.
+    fn export_csv(&self, path: &str, steps: usize, step_seconds: f64) -> PyResult<()> {
+        let file = File::create(path).map_err(|e| PyIOError::new_err(e.to_string()))?;
+        write_csv(&self.store, self.sim_time, steps, step_seconds, BufWriter::new(file))
+            .map_err(|e| PyIOError::new_err(e.to_string()))
+    }
+
+    fn __len__(&self) -> usize {
+        self.store.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<System {} with {} bodies at {} s>", self.store.root().name, self.store.len(), self.sim_time)
+    }
+}
+
+/// Load a system from a YAML system file
+#[pyfunction]
+fn load_system(path: &str) -> PyResult<PySystem> {
+    load_yaml(path).map(|system| PySystem::new(SystemStore::new(system))).map_err(PyValueError::new_err)
+}
+
+/// Load a system from the contents of a YAML system file
+#[pyfunction]
+fn load_system_str(yaml: &str) -> PyResult<PySystem> {
+    load_yaml_str(yaml).map(|system| PySystem::new(SystemStore::new(system))).map_err(PyValueError::new_err)
+}
+
+#[pymodule]
+fn magnetar(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PySystem>()?;
+    module.add_function(wrap_pyfunction!(load_system, module)?)?;
+    module.add_function(wrap_pyfunction!(load_system_str, module)?)?;
+    Ok(())
+}
+
+/// Write one row per body and step, positions are relative to the root.
+fn write_csv(store: &SystemStore, sim_time: f64, steps: usize, step_seconds: f64, mut writer: impl Write) -> io::Result<()> {
+    writeln!(writer, "step,sim_time,body,parent,semi_major_axis,eccentricity,longitude_of_periapsis,mean_anomaly,x,y")?;
+    let mut store = store.clone();
+    for step in 0..=steps {
+        if step > 0 {
+            store.step_by(step_seconds);
+        }
+        for id in store.ids() {
+            let object = store.get(id).unwrap();
+            let orbit = &object.orbital_params;
+            let parent = store.parent(id).and_then(|parent| store.get(parent)).map_or("", |parent| parent.name.as_str());
+            let position = store.absolute_position(id).unwrap();
+            writeln!(writer, "{},{},{},{},{},{},{},{},{},{}",
+                     step, sim_time + step as f64 * step_seconds, object.name, parent, orbit.semi_major_axis,
+                     orbit.eccentricity, orbit.longitude_of_periapsis, orbit.mean_anomaly, position.x, position.y)?;
+        }
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_csv() {
+        let system = load_yaml_str(include_str!("../../magnetar_data/test_data/valid.yaml")).unwrap();
+        let mut out = Vec::new();
+        write_csv(&SystemStore::new(system), 0.0, 2, 86400.0, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 1 + 3 * 3);
+        assert!(lines[1].starts_with("0,0,Sol,,"));
+        assert!(lines[9].starts_with("2,172800,Luna,Earth,"));
+    }
+}
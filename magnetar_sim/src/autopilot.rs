@@ -0,0 +1,173 @@
+use magnetar_data::lambert::lambert;
+use magnetar_data::orbital::{OrbitalParameters, GRAVITATIONAL_CONSTANT};
+use magnetar_data::stability::hill_radius;
+use magnetar_data::store::SystemStore;
+use magnetar_data::transfer::{hohmann, synodic_period, transfer_windows};
+use crate::clock::Clock;
+use crate::command::Command;
+
+/// Radius of the parking orbit around the target in radii of the target
+const PARKING_RADII: f64 = 2.0;
+/// Largest parking orbit as a fraction of the Hill radius of the target, beyond it the parent pulls the craft away
+const MAX_PARKING_HILL_FRACTION: f64 = 0.3;
+/// Steps per period of the faster body when searching for the departure window
+const WINDOW_RESOLUTION: f64 = 72.0;
+/// Fraction of the transfer after which a correction burn takes out the error of the departure burn
+const CORRECTION_FRACTION: f64 = 0.75;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A command of a [`TransferPlan`] together with when to execute it
+pub struct Maneuver {
+    /// Seconds from the state the plan was made in
+    pub delay: f64,
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Commands flying a craft to another body, made by [`plan_transfer`]
+pub struct TransferPlan {
+    pub craft: String,
+    pub target: String,
+    /// Seconds from now until the departure burn
+    pub departure: f64,
+    /// Seconds from now until the craft enters the orbit of the target
+    pub arrival: f64,
+    /// Delta-v of the departure, correction and capture burns in km/s
+    pub delta_v: f64,
+    /// To execute at their delay, e.g. with [`Simulation::schedule`](crate::simulation::Simulation::schedule)
+    pub maneuvers: Vec<Maneuver>,
+}
+
+/// Plans a transfer of `craft` to the body `target` orbiting the same parent: a burn at the next Hohmann transfer
+/// window, refined with [`lambert`] to meet the target, a mid-course correction, and on arrival a switch to the
+/// target's sphere of influence on a circular parking orbit. Orbits store the longitude of periapsis in whole
+/// degrees, so burns never land exactly on the intercept. The plan flies the burns on a copy of the system and
+/// fails if the craft would still end up outside the target's Hill sphere. The capture burns at the periapsis of the
+/// parking orbit, starting from the speed the craft arrives with relative to the target.
+pub fn plan_transfer(store: &SystemStore, craft: &str, target: &str) -> Result<TransferPlan, String> {
+    let craft_id = store.find(craft).ok_or_else(|| format!("No object named '{}'", craft))?;
+    let target_id = store.find(target).ok_or_else(|| format!("No object named '{}'", target))?;
+    if craft_id == target_id {
+        return Err(format!("'{}' cannot fly to itself", craft));
+    }
+    let parent = store.parent(craft_id).ok_or("The root of the system cannot fly anywhere")?;
+    if store.parent(target_id) != Some(parent) {
+        return Err(format!("'{}' and '{}' do not orbit the same body", craft, target));
+    }
+
+    let parent_mass = store.parent_mass(craft_id);
+    let (origin, destination) = (&store.get(craft_id).unwrap().orbital_params, &store.get(target_id).unwrap().orbital_params);
+    let synodic = synodic_period(origin, destination, parent_mass);
+    if !synodic.is_finite() {
+        return Err(format!("'{}' and '{}' share an orbit, there is no transfer window", craft, target));
+    }
+    let resolution = origin.period(parent_mass).min(destination.period(parent_mass)) / WINDOW_RESOLUTION;
+    let transfer = hohmann(origin, destination, parent_mass);
+    let departure = *transfer_windows(origin, destination, parent_mass, 0.0, synodic + resolution, resolution)
+        .first()
+        .ok_or("No transfer window found")?;
+
+    // Aim at where the target actually is on arrival, which differs from the Hohmann estimate for eccentric
+    // orbits. Arcs of exactly half a revolution have no unique solution, those keep the Hohmann burn.
+    let arrival = departure + transfer.transfer_time;
+    let departure_orbit = origin.state_at(departure, parent_mass);
+    let velocity = departure_orbit.velocity(parent_mass);
+    let target_position = destination.state_at(arrival, parent_mass).position();
+    let parent_mu = GRAVITATIONAL_CONSTANT * parent_mass;
+    let delta_v = match lambert(departure_orbit.position(), target_position, transfer.transfer_time, parent_mu) {
+        Ok(solution) => solution.departure_velocity - velocity,
+        Err(_) => {
+            // Prograde to raise the orbit, retrograde to lower it
+            let direction = if destination.semi_major_axis > origin.semi_major_axis { 1.0 } else { -1.0 };
+            velocity.normalize_or_zero() * direction * transfer.departure_delta_v
+        }
+    };
+
+    // Fly the departure burn to find where the craft actually is when correcting
+    let correction = departure + CORRECTION_FRACTION * transfer.transfer_time;
+    let mut flight = store.clone();
+    let burn = |flight: &mut SystemStore, delta_v| {
+        Command::ApplyBurn { body: craft.to_string(), delta_v }.apply(flight, &mut Clock::default())
+    };
+    flight.step_by(departure);
+    burn(&mut flight, delta_v)?;
+    flight.step_by(correction - departure);
+    let drifted = &flight.get(craft_id).unwrap().orbital_params;
+    let course = lambert(drifted.position(), target_position, arrival - correction, parent_mu)?;
+    let correction_delta_v = course.departure_velocity - drifted.velocity(parent_mass);
+    burn(&mut flight, correction_delta_v)?;
+    flight.step_by(arrival - correction);
+
+    let hill = hill_radius(store, target_id).unwrap_or(f64::INFINITY);
+    let miss = flight.absolute_position(craft_id).unwrap().distance(flight.absolute_position(target_id).unwrap());
+    if miss > hill {
+        return Err(format!("'{}' would pass '{}' at {:.0} km, outside of its Hill sphere of {:.0} km", craft, target, miss, hill));
+    }
+
+    let target_object = store.get(target_id).unwrap();
+    let parking_radius = (PARKING_RADII * target_object.radius).min(MAX_PARKING_HILL_FRACTION * hill);
+    if parking_radius <= target_object.radius {
+        return Err(format!("'{}' has no room for a parking orbit", target));
+    }
+    let mu = GRAVITATIONAL_CONSTANT * target_object.mass;
+    let arrival_speed = (course.arrival_velocity - destination.state_at(arrival, parent_mass).velocity(parent_mass)).length();
+    let capture_delta_v = (arrival_speed.powi(2) + 2.0 * mu / parking_radius).sqrt() - (mu / parking_radius).sqrt();
+
+    let maneuver = |delay, command| Maneuver { delay, command };
+    Ok(TransferPlan {
+        craft: craft.to_string(),
+        target: target.to_string(),
+        departure,
+        arrival,
+        delta_v: delta_v.length() + correction_delta_v.length() + capture_delta_v,
+        maneuvers: vec![
+            maneuver(departure, Command::ApplyBurn { body: craft.to_string(), delta_v }),
+            maneuver(correction, Command::ApplyBurn { body: craft.to_string(), delta_v: correction_delta_v }),
+            maneuver(arrival, Command::Reparent { body: craft.to_string(), parent: target.to_string() }),
+            maneuver(arrival, Command::SetOrbit {
+                body: craft.to_string(),
+                orbit: OrbitalParameters { semi_major_axis: parking_radius, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 0.0 },
+            }),
+        ],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use magnetar_data::yaml_parser::load_yaml_str;
+    use crate::simulation::Simulation;
+    use super::*;
+
+    #[test]
+    fn test_transfer_reaches_target() {
+        let mut system = load_yaml_str(include_str!("../../magnetar_data/test_data/valid.yaml")).unwrap();
+        let mut probe = system.find("Luna").unwrap().clone();
+        (probe.name, probe.mass, probe.radius) = ("Probe".to_string(), 1000.0, 0.005);
+        probe.orbital_params = OrbitalParameters { semi_major_axis: 1.1e8, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 0.0 };
+        system.children.push(probe);
+        let mut simulation = Simulation::new(SystemStore::new(system));
+        simulation.seek(86400.0);
+
+        assert!(plan_transfer(simulation.store(), "Probe", "Luna").is_err());
+        let plan = plan_transfer(simulation.store(), "Probe", "Earth").unwrap();
+        // Venus to Earth takes about 146 days, this orbit is a bit further in
+        assert!((plan.arrival - plan.departure) / 86400.0 > 100.0 && (plan.arrival - plan.departure) / 86400.0 < 160.0);
+        assert_eq!(plan.maneuvers.len(), 4);
+        for maneuver in plan.maneuvers {
+            simulation.schedule(86400.0 + maneuver.delay, maneuver.command).unwrap();
+        }
+
+        // After the correction the craft arrives well inside Earth's Hill sphere
+        simulation.seek(86400.0 + plan.arrival - 1.0);
+        let store = simulation.store();
+        let (probe, earth) = (store.find("Probe").unwrap(), store.find("Earth").unwrap());
+        let miss = store.absolute_position(probe).unwrap().distance(store.absolute_position(earth).unwrap());
+        assert!(miss < hill_radius(store, earth).unwrap(), "{}", miss);
+
+        simulation.seek(86400.0 + plan.arrival + 1.0);
+        let store = simulation.store();
+        let probe = store.find("Probe").unwrap();
+        assert_eq!(store.parent(probe), store.find("Earth"));
+        assert_eq!(store.get(probe).unwrap().orbital_params.semi_major_axis, 2.0 * store.get(store.find("Earth").unwrap()).unwrap().radius);
+    }
+}
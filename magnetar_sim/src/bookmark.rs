@@ -0,0 +1,132 @@
+use magnetar_data::store::{ObjectId, SystemStore};
+use magnetar_data::vector::DVec3;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// A saved view of the system. Bodies are stored by name like in [`Command`](crate::command::Command)s, so
+/// bookmarks survive objects being added or removed and can be written to save files.
+pub struct CameraBookmark {
+    pub name: String,
+    /// Body the view is relative to, the root of the system if `None`
+    pub frame: Option<String>,
+    /// Body the camera follows, it looks at `offset` from the frame if `None`
+    pub follow: Option<String>,
+    /// Point looked at in km relative to the frame, ignored while following a body
+    pub offset: DVec3,
+    /// Width of the view in km
+    pub zoom: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Where a [`CameraBookmark`] points in the current state of a system
+pub struct View {
+    pub frame: ObjectId,
+    pub follow: Option<ObjectId>,
+    /// Point looked at in km relative to the root of the system
+    pub center: DVec3,
+    pub zoom: f64,
+}
+
+impl CameraBookmark {
+    /// Where the bookmark points now, `None` if its frame or followed body no longer exists
+    pub fn view(&self, store: &SystemStore) -> Option<View> {
+        let frame = match &self.frame {
+            Some(name) => store.find(name)?,
+            None => store.root_id(),
+        };
+        let follow = match &self.follow {
+            Some(name) => Some(store.find(name)?),
+            None => None,
+        };
+        let center = match follow {
+            Some(id) => store.absolute_position(id)?,
+            None => store.absolute_position(frame)? + self.offset,
+        };
+        Some(View { frame, follow, center, zoom: self.zoom })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+/// Named camera bookmarks in the order they were first saved, so the first nine can be bound to number keys
+pub struct Bookmarks {
+    bookmarks: Vec<CameraBookmark>,
+}
+
+impl Bookmarks {
+    pub fn new() -> Bookmarks {
+        Bookmarks::default()
+    }
+
+    /// Saves a bookmark, replacing the one with the same name in place
+    pub fn save(&mut self, bookmark: CameraBookmark) {
+        match self.bookmarks.iter_mut().find(|existing| existing.name == bookmark.name) {
+            Some(existing) => *existing = bookmark,
+            None => self.bookmarks.push(bookmark),
+        }
+    }
+
+    /// Removes a bookmark, returns whether one with that name existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        let count = self.bookmarks.len();
+        self.bookmarks.retain(|bookmark| bookmark.name != name);
+        self.bookmarks.len() != count
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CameraBookmark> {
+        self.bookmarks.iter().find(|bookmark| bookmark.name == name)
+    }
+
+    /// The bookmark at `index` in the order they were first saved, e.g. for hotkeys
+    pub fn slot(&self, index: usize) -> Option<&CameraBookmark> {
+        self.bookmarks.get(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CameraBookmark> {
+        self.bookmarks.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bookmarks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use magnetar_data::yaml_parser::load_yaml_str;
+    use super::*;
+
+    #[test]
+    fn test_bookmarks_follow_bodies() {
+        let mut store = SystemStore::new(load_yaml_str(include_str!("../../magnetar_data/test_data/valid.yaml")).unwrap());
+        let mut bookmarks = Bookmarks::new();
+        let overview = CameraBookmark { name: "Overview".to_string(), frame: None, follow: None, offset: DVec3::ZERO, zoom: 4e8 };
+        let moon = CameraBookmark {
+            name: "Moon".to_string(),
+            frame: Some("Earth".to_string()),
+            follow: Some("Luna".to_string()),
+            offset: DVec3::ZERO,
+            zoom: 1e4,
+        };
+        bookmarks.save(overview.clone());
+        bookmarks.save(moon.clone());
+        bookmarks.save(CameraBookmark { zoom: 1e9, ..overview });
+        assert_eq!((bookmarks.len(), bookmarks.slot(0).unwrap().zoom), (2, 1e9));
+
+        let luna = store.find("Luna").unwrap();
+        store.step_by(86400.0);
+        let view = bookmarks.get("Moon").unwrap().view(&store).unwrap();
+        assert_eq!((view.frame, view.follow, view.center), (store.find("Earth").unwrap(), Some(luna), store.absolute_position(luna).unwrap()));
+
+        let mut root = store.root().clone();
+        root.children[0].children.clear();
+        assert_eq!(moon.view(&SystemStore::new(root)), None);
+        assert!(bookmarks.remove("Moon") && !bookmarks.remove("Moon"));
+    }
+}
@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Turns real time into sim time
+pub struct Clock {
+    /// Sim seconds passing per real second, negative to run backwards
+    pub time_scale: f64,
+    pub paused: bool,
+}
+
+impl Default for Clock {
+    /// A paused clock running at real time
+    fn default() -> Clock {
+        Clock { time_scale: 1.0, paused: true }
+    }
+}
+
+impl Clock {
+    pub fn new(time_scale: f64) -> Clock {
+        Clock { time_scale, paused: false }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Sim seconds that pass in `real` time, 0 while paused
+    pub fn advance(&self, real: Duration) -> f64 {
+        if self.paused { 0.0 } else { real.as_secs_f64() * self.time_scale }
+    }
+}
@@ -0,0 +1,187 @@
+use magnetar_data::celestial::Object;
+use magnetar_data::orbital::OrbitalParameters;
+use magnetar_data::store::{ObjectId, SystemStore};
+use magnetar_data::vector::DVec3;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::clock::Clock;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// A change to a running simulation, executed with [`Simulation::execute`](crate::simulation::Simulation::execute).
+/// Objects are addressed by name so commands stay valid when adding or removing objects renumbers the ids.
+pub enum Command {
+    /// Add an object with its children, e.g. a newly built station, to the orbit of `parent`
+    AddBody {
+        parent: String,
+        body: Object,
+    },
+    /// Remove an object together with its children
+    RemoveBody {
+        name: String,
+    },
+    /// Move an object with its children to the orbit of another parent, keeping its orbital elements
+    Reparent {
+        body: String,
+        parent: String,
+    },
+    /// Instantly change the velocity of an object relative to its parent
+    ApplyBurn {
+        body: String,
+        /// Change of velocity in km/s
+        delta_v: DVec3,
+    },
+    /// Replace the properties of the object with the same name as `body`, its children are kept and the
+    /// children of `body` ignored
+    SetBody {
+        body: Object,
+    },
+    /// Put an object on a new orbit around the same parent
+    SetOrbit {
+        body: String,
+        orbit: OrbitalParameters,
+    },
+    /// Sim seconds per real second, see [`Clock`]
+    SetTimeScale(f64),
+    SetPaused(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// A command together with the sim time it was executed at, the log of these plus the epoch is a replay
+pub struct LoggedCommand {
+    pub sim_time: f64,
+    pub command: Command,
+}
+
+impl Command {
+    /// Whether the command changes the simulated system, the others only change how it is played back
+    pub fn changes_system(&self) -> bool {
+        !matches!(self, Command::SetTimeScale(_) | Command::SetPaused(_))
+    }
+
+    /// Whether the command adds, removes or moves objects in the tree, which renumbers all ids
+    pub fn changes_structure(&self) -> bool {
+        matches!(self, Command::AddBody { .. } | Command::RemoveBody { .. } | Command::Reparent { .. })
+    }
+
+    /// Applies the command, returns whether objects were added or removed, which renumbers all ids
+    pub(crate) fn apply(&self, store: &mut SystemStore, clock: &mut Clock) -> Result<bool, String> {
+        match self {
+            Command::AddBody { parent, body } => {
+                find(store, parent)?;
+                if let Some(existing) = body.iter().find(|item| store.find(&item.object.name).is_some()) {
+                    return Err(format!("An object named '{}' already exists", existing.object.name));
+                }
+                let mut root = store.root().clone();
+                root.find_mut(parent).unwrap().children.push(body.clone());
+                *store = SystemStore::new(root);
+                Ok(true)
+            }
+            Command::RemoveBody { name } => {
+                let id = find(store, name)?;
+                let parent = store.parent(id).ok_or("Cannot remove the root of the system")?;
+                let mut root = store.root().clone();
+                let parent_name = store.get(parent).unwrap().name.clone();
+                root.find_mut(&parent_name).unwrap().children.retain(|child| child.name != *name);
+                *store = SystemStore::new(root);
+                Ok(true)
+            }
+            Command::Reparent { body, parent } => {
+                let id = find(store, body)?;
+                let new_parent = find(store, parent)?;
+                let old_parent = store.parent(id).ok_or("Cannot reparent the root of the system")?;
+                if store.get(id).unwrap().find(parent).is_some() {
+                    return Err(format!("Cannot move '{}' into its own orbit", body));
+                }
+                if old_parent == new_parent {
+                    return Ok(false);
+                }
+                let mut root = store.root().clone();
+                let old_parent_name = store.get(old_parent).unwrap().name.clone();
+                let siblings = &mut root.find_mut(&old_parent_name).unwrap().children;
+                let moved = siblings.remove(siblings.iter().position(|child| child.name == *body).unwrap());
+                root.find_mut(parent).unwrap().children.push(moved);
+                *store = SystemStore::new(root);
+                Ok(true)
+            }
+            Command::ApplyBurn { body, delta_v } => {
+                let id = find(store, body)?;
+                if store.parent(id).is_none() {
+                    return Err("The root of the system has no orbit to change".to_string());
+                }
+                let parent_mass = store.parent_mass(id);
+                let orbit = store.orbit_mut(id).unwrap();
+                *orbit = OrbitalParameters::from_state(orbit.position(), orbit.velocity(parent_mass) + *delta_v, parent_mass)?;
+                Ok(false)
+            }
+            Command::SetBody { body } => {
+                find(store, &body.name)?;
+                let mut root = store.root().clone();
+                let object = root.find_mut(&body.name).unwrap();
+                let children = std::mem::take(&mut object.children);
+                *object = Object { children, ..body.clone() };
+                *store = SystemStore::new(root);
+                Ok(false)
+            }
+            Command::SetOrbit { body, orbit } => {
+                let id = find(store, body)?;
+                if store.parent(id).is_none() {
+                    return Err("The root of the system has no orbit to change".to_string());
+                }
+                *store.orbit_mut(id).unwrap() = orbit.clone();
+                Ok(false)
+            }
+            Command::SetTimeScale(scale) => {
+                clock.time_scale = *scale;
+                Ok(false)
+            }
+            Command::SetPaused(paused) => {
+                clock.paused = *paused;
+                Ok(false)
+            }
+        }
+    }
+}
+
+fn find(store: &SystemStore, name: &str) -> Result<ObjectId, String> {
+    store.find(name).ok_or_else(|| format!("No object named '{}'", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use magnetar_data::yaml_parser::load_yaml_str;
+    use crate::simulation::Simulation;
+    use super::*;
+
+    #[test]
+    fn test_commands_replay_after_rewind() {
+        let system = load_yaml_str(include_str!("../../magnetar_data/test_data/valid.yaml")).unwrap();
+        let mut station = system.find("Luna").unwrap().clone();
+        (station.name, station.mass, station.radius) = ("Station".to_string(), 400_000.0, 0.05);
+        station.orbital_params.semi_major_axis = 42_164.0;
+        let mut simulation = Simulation::new(SystemStore::new(system));
+
+        simulation.seek(3600.0);
+        simulation.execute(Command::AddBody { parent: "Earth".to_string(), body: station.clone() }).unwrap();
+        assert!(simulation.execute(Command::AddBody { parent: "Earth".to_string(), body: station }).is_err());
+        simulation.seek(7200.0);
+        simulation.execute(Command::ApplyBurn { body: "Station".to_string(), delta_v: DVec3::new(0.0, 0.5, 0.0) }).unwrap();
+        simulation.execute(Command::SetPaused(false)).unwrap();
+        assert_eq!(simulation.log().len(), 2);
+        simulation.seek(86400.0);
+        let expected = simulation.store().root().clone();
+
+        simulation.seek(1800.0);
+        assert!(simulation.store().find("Station").is_none());
+        simulation.seek(86400.0);
+        assert!(simulation.store().root().approx_eq(&expected, 1e-9));
+
+        // A new command after rewinding replaces the ones that came later
+        simulation.seek(5400.0);
+        simulation.execute(Command::RemoveBody { name: "Station".to_string() }).unwrap();
+        assert_eq!(simulation.log().len(), 2);
+        simulation.seek(86400.0);
+        assert!(simulation.store().find("Station").is_none());
+    }
+}
@@ -0,0 +1,175 @@
+use magnetar_data::celestial::{Object, ObjectType};
+use magnetar_data::diff::{Field, Value};
+use magnetar_data::orbital::OrbitalParameters;
+use magnetar_data::query::Query;
+use magnetar_data::store::{ObjectId, SystemStore};
+use magnetar_data::surface::Rotation;
+use crate::autopilot::{plan_transfer, TransferPlan};
+use crate::command::Command;
+
+/// Mass in kg of objects created with `spawn`
+const PROBE_MASS: f64 = 1000.0;
+/// Radius in km of objects created with `spawn`
+const PROBE_RADIUS: f64 = 0.005;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A line typed into a debug console, see [`parse`]
+pub enum ConsoleCommand {
+    /// A change to run with [`Simulation::execute`](crate::simulation::Simulation::execute)
+    Execute(Box<Command>),
+    /// Move the view to an object
+    Focus(ObjectId),
+    /// Load the system from where it came from again
+    Reload,
+    /// Maneuvers to schedule so a craft flies to another body
+    Autopilot(TransferPlan),
+}
+
+/// Parses a console line, looking up the objects it names in `store`:
+///
+/// * `spawn <name> <parent> [<semi-major axis>]` - a one tonne probe on a circular orbit, by default at twice
+///   the radius of the parent
+/// * `tp <body or query>` - focus a body by name or the first body matching a [`Query`]
+/// * `goto <craft> <target>` - fly a body to another one orbiting the same parent, see [`plan_transfer`]
+/// * `time <scale>x`, `time pause` or `time resume` - change the clock
+/// * `set <path>.<field> <value>` - set a field as named in system files, where the path is a name or the names
+///   from the root down, e.g. `set Sol.Earth.eccentricity 0.02` or `set Luna.atmosphere.Ar unset`
+/// * `reload` or `reload data` - load the system again
+pub fn parse(input: &str, store: &SystemStore) -> Result<ConsoleCommand, String> {
+    let (keyword, arguments) = input.trim().split_once(' ').unwrap_or((input.trim(), ""));
+    let arguments = arguments.trim();
+    match keyword {
+        "spawn" => {
+            let words: Vec<&str> = arguments.split_whitespace().collect();
+            let (name, parent, semi_major_axis) = match words[..] {
+                [name, parent] => (name, parent, None),
+                [name, parent, semi_major_axis] => (name, parent, Some(positive(semi_major_axis, "semi-major axis")?)),
+                _ => return Err("Usage: spawn <name> <parent> [<semi-major axis>]".to_string()),
+            };
+            let parent_object = store.find(parent).and_then(|id| store.get(id)).ok_or_else(|| format!("No object named '{}'", parent))?;
+            let body = Object {
+                name: name.to_string(),
+                object_type: ObjectType::Rocky,
+                mass: PROBE_MASS,
+                radius: PROBE_RADIUS,
+                orbital_params: OrbitalParameters {
+                    semi_major_axis: semi_major_axis.unwrap_or(2.0 * parent_object.radius),
+                    eccentricity: 0.0,
+                    longitude_of_periapsis: 0,
+                    mean_anomaly: 0.0,
+                },
+                atmosphere: Default::default(),
+                scale_height: None,
+                rotation: None,
+                icon: None,
+                children: Vec::new(),
+            };
+            Ok(ConsoleCommand::Execute(Box::new(Command::AddBody { parent: parent.to_string(), body })))
+        }
+        "tp" => match store.find(arguments) {
+            Some(id) => Ok(ConsoleCommand::Focus(id)),
+            None => Query::parse(arguments)?
+                .select(store)
+                .first()
+                .map(|id| ConsoleCommand::Focus(*id))
+                .ok_or_else(|| format!("Nothing matches '{}'", arguments)),
+        },
+        "goto" => match arguments.split_whitespace().collect::<Vec<_>>()[..] {
+            [craft, target] => Ok(ConsoleCommand::Autopilot(plan_transfer(store, craft, target)?)),
+            _ => Err("Usage: goto <craft> <target>".to_string()),
+        },
+        "time" => match arguments {
+            "pause" => Ok(ConsoleCommand::Execute(Box::new(Command::SetPaused(true)))),
+            "resume" => Ok(ConsoleCommand::Execute(Box::new(Command::SetPaused(false)))),
+            scale => {
+                let scale = scale.strip_suffix('x').ok_or("Usage: time <scale>x, time pause or time resume")?;
+                Ok(ConsoleCommand::Execute(Box::new(Command::SetTimeScale(positive(scale, "time scale")?))))
+            }
+        },
+        "set" => {
+            let (path, value) = arguments.split_once(' ').ok_or("Usage: set <path>.<field> <value>")?;
+            let (mut body, field) = resolve(path, store)?;
+            let value = match (value.trim(), &field) {
+                ("unset", Field::ScaleHeight | Field::Rotation | Field::Icon | Field::Gas(_)) => Value::Unset,
+                ("unset", _) => return Err(format!("'{}' cannot be unset", field)),
+                (period, Field::Rotation) => Value::Rotation(Rotation {
+                    period: positive(period, "rotation period")?,
+                    prime_meridian: body.rotation.map_or(0.0, |rotation| rotation.prime_meridian),
+                }),
+                (icon, Field::Icon) => Value::Text(icon.to_string()),
+                (_, Field::Type) => return Err("The type cannot be set from the console".to_string()),
+                (value, Field::Mass | Field::Radius | Field::SemiMajorAxis) => Value::Number(positive(value, &field.to_string())?),
+                (value, _) => Value::Number(number(value)?),
+            };
+            field.set(&mut body, value);
+            Ok(ConsoleCommand::Execute(Box::new(Command::SetBody { body })))
+        }
+        "reload" if arguments.is_empty() || arguments == "data" => Ok(ConsoleCommand::Reload),
+        _ => Err(format!("Unknown command '{}'", input.trim())),
+    }
+}
+
+/// Splits `Sol.Earth.eccentricity` into a copy of the object and the field, the path either starts at the root
+/// or with the name of any object
+fn resolve(path: &str, store: &SystemStore) -> Result<(Object, Field), String> {
+    let mut segments = path.split('.');
+    let first = segments.next().unwrap_or_default();
+    let mut object = store.find(first).and_then(|id| store.get(id)).ok_or_else(|| format!("No object named '{}'", first))?;
+    let mut rest: Vec<&str> = segments.collect();
+    while let Some(child) = rest.first().and_then(|name| object.children.iter().find(|child| child.name == *name)) {
+        object = child;
+        rest.remove(0);
+    }
+    let field = rest.join(".").parse()?;
+    Ok((object.clone(), field))
+}
+
+/// Parses a finite number, NaN and infinities would poison every position computed from them
+fn number(value: &str) -> Result<f64, String> {
+    value.parse().ok().filter(|number: &f64| number.is_finite()).ok_or_else(|| format!("Invalid number '{}'", value))
+}
+
+/// Parses a number which has to be above 0, like a size or a time scale
+fn positive(value: &str, what: &str) -> Result<f64, String> {
+    let number = number(value)?;
+    if number <= 0.0 {
+        return Err(format!("The {} has to be positive, not {}", what, value));
+    }
+    Ok(number)
+}
+
+#[cfg(test)]
+mod tests {
+    use magnetar_data::yaml_parser::load_yaml_str;
+    use crate::simulation::Simulation;
+    use super::*;
+
+    #[test]
+    fn test_console_commands() {
+        let mut simulation = Simulation::new(SystemStore::new(load_yaml_str(include_str!("../../magnetar_data/test_data/valid.yaml")).unwrap()));
+        let earth = simulation.store().find("Earth").unwrap();
+        let mut run = |input: &str| match parse(input, simulation.store())? {
+            ConsoleCommand::Execute(command) => simulation.execute(*command).map(|_| None),
+            other => Ok(Some(other)),
+        };
+        assert_eq!(run("set Sol.Earth.eccentricity 0.1"), Ok(None));
+        assert_eq!(run("set Luna.mass unset").unwrap_err(), "'mass' cannot be unset");
+        assert_eq!(run("spawn Probe Luna"), Ok(None));
+        assert_eq!(run("time 1000x"), Ok(None));
+        assert_eq!(run("reload data"), Ok(Some(ConsoleCommand::Reload)));
+        assert!(run("launch Probe").is_err());
+        assert_eq!(run("time NaNx").unwrap_err(), "Invalid number 'NaN'");
+        assert_eq!(run("time 0x").unwrap_err(), "The time scale has to be positive, not 0");
+        assert_eq!(run("spawn Lost Sol -5").unwrap_err(), "The semi-major axis has to be positive, not -5");
+        assert_eq!(run("set Luna.radius 0").unwrap_err(), "The radius has to be positive, not 0");
+        assert!(run("set Luna.mean-anomaly inf").is_err());
+        assert_eq!(run("tp type=ROCKY and mass>1e24"), Ok(Some(ConsoleCommand::Focus(earth))));
+
+        let store = simulation.store();
+        assert_eq!(store.get(earth).unwrap().orbital_params.eccentricity, 0.1);
+        let (probe, luna) = (store.find("Probe").unwrap(), store.find("Luna").unwrap());
+        assert_eq!(store.parent(probe), Some(luna));
+        assert_eq!(store.get(probe).unwrap().orbital_params.semi_major_axis, 2.0 * store.get(luna).unwrap().radius);
+        assert_eq!(simulation.clock().time_scale, 1000.0);
+    }
+}
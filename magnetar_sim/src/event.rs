@@ -0,0 +1,31 @@
+use magnetar_data::collision::Collision;
+use magnetar_data::decay::Deorbit;
+use magnetar_data::radiation::Escape;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Something that happened while stepping a [`Simulation`](crate::simulation::Simulation)
+pub enum Event {
+    /// Two objects started touching
+    Collision {
+        /// Sim time of the step the contact was detected at
+        sim_time: f64,
+        collision: Collision,
+    },
+    /// Atmospheric drag lowered an orbit into the surface of its parent
+    Deorbit {
+        /// Sim time the periapsis reached the surface
+        sim_time: f64,
+        deorbit: Deorbit,
+    },
+    /// A gas escaped from an atmosphere heated by starlight
+    Escape {
+        /// Sim time the partial pressure dropped below the minimum
+        sim_time: f64,
+        escape: Escape,
+    },
+    /// The simulation was rewound to an earlier sim time
+    Rewound {
+        from: f64,
+        to: f64,
+    },
+}
@@ -0,0 +1,19 @@
+//! Engine agnostic simulation loop on top of `magnetar_data`: a clock turning real time into sim time,
+//! stepping with collision and decay checks, events and snapshots for rewinding, and commands for every change
+//! made to the system while it runs.
+//!
+//! Frontends, headless servers and tools all drive a [`simulation::Simulation`] the same way, by calling
+//! [`simulation::Simulation::tick`] with the real time that passed or seeking to a sim time directly.
+
+pub mod autopilot;
+pub mod bookmark;
+pub mod clock;
+pub mod command;
+pub mod console;
+pub mod event;
+#[cfg(feature = "net")]
+pub mod net;
+pub mod realism;
+pub mod session;
+pub mod simulation;
+pub mod tutorial;
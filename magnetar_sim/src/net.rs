@@ -0,0 +1,240 @@
+//! Spectator streaming: a host sends its system once and then snapshots of it to any number of clients,
+//! which follow along without sending anything back.
+//!
+//! The protocol is line based text over TCP, so it can be inspected with `nc`:
+//!
+//! ```text
+//! system <bytes>
+//! <the system as YAML, exactly <bytes> long>
+//! snapshot <sim time> <objects>
+//! <semi-major axis> <eccentricity> <longitude of periapsis> <mean anomaly> <rotation period or -> <prime meridian or ->
+//! ...
+//! ```
+//!
+//! Floats are written in their shortest form that reads back to the same value.
+
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use magnetar_data::orbital::OrbitalParameters;
+use magnetar_data::snapshot::Snapshot;
+use magnetar_data::store::SystemStore;
+use magnetar_data::surface::Rotation;
+use magnetar_data::yaml_parser::{load_yaml_str, to_yaml};
+
+/// Longest a write to a spectator may block before it counts as disconnected
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Accepts spectators and streams snapshots to them. Waiting clients are accepted without blocking, and a
+/// spectator that stops reading is dropped once a write to it blocks for longer than [`WRITE_TIMEOUT`].
+pub struct SpectatorHost {
+    listener: TcpListener,
+    /// The system as sent to every new client
+    system: String,
+    clients: Vec<BufWriter<TcpStream>>,
+}
+
+impl SpectatorHost {
+    /// Listen on `address` for spectators of `store`, port 0 picks a free port
+    pub fn bind(address: impl ToSocketAddrs, store: &SystemStore) -> io::Result<SpectatorHost> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let system = to_yaml(store.root()).map_err(io::Error::other)?;
+        Ok(SpectatorHost { listener, system, clients: Vec::new() })
+    }
+
+    pub fn local_address(&self) -> io::Result<std::net::SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Number of connected spectators as of the last broadcast
+    pub fn clients(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Accepts waiting spectators and sends them the system, then sends `snapshot` to everyone.
+    /// Spectators that cannot be written to or time out are dropped.
+    pub fn broadcast(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, address)) => {
+                    if let Err(e) = stream.set_nodelay(true) {
+                        tracing::warn!(%address, error = %e, "failed to disable Nagle's algorithm for spectator");
+                    }
+                    // Some platforms pass on the non-blocking mode of the listener, writes block up to the timeout
+                    if let Err(e) = stream.set_nonblocking(false).and_then(|_| stream.set_write_timeout(Some(WRITE_TIMEOUT))) {
+                        tracing::warn!(%address, error = %e, "failed to set up spectator, dropping it");
+                        continue;
+                    }
+                    let mut writer = BufWriter::new(stream);
+                    if write!(writer, "system {}\n{}", self.system.len(), self.system).is_ok() {
+                        tracing::info!(%address, "spectator connected");
+                        self.clients.push(writer);
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        let message = encode_snapshot(snapshot);
+        self.clients.retain_mut(|client| {
+            let sent = client.write_all(message.as_bytes()).and_then(|_| client.flush());
+            if let Err(e) = &sent {
+                tracing::info!(error = %e, "spectator disconnected");
+            }
+            sent.is_ok()
+        });
+        Ok(())
+    }
+}
+
+/// Connection of a spectator to a [`SpectatorHost`]
+pub struct SpectatorClient {
+    reader: BufReader<TcpStream>,
+    epoch: SystemStore,
+}
+
+impl SpectatorClient {
+    /// Connects and waits for the host to send its system
+    pub fn connect(address: impl ToSocketAddrs) -> io::Result<SpectatorClient> {
+        let mut reader = BufReader::new(TcpStream::connect(address)?);
+        let header = read_line(&mut reader)?;
+        let length = header.strip_prefix("system ")
+            .and_then(|length| length.parse::<usize>().ok())
+            .ok_or_else(|| invalid(format!("Expected the system instead of '{}'", header)))?;
+        let mut yaml = vec![0; length];
+        reader.read_exact(&mut yaml)?;
+        let yaml = String::from_utf8(yaml).map_err(|e| invalid(e.to_string()))?;
+        let epoch = SystemStore::new(load_yaml_str(&yaml).map_err(invalid)?);
+        Ok(SpectatorClient { reader, epoch })
+    }
+
+    /// The system as sent by the host, apply received snapshots to a copy of it
+    pub fn epoch(&self) -> &SystemStore {
+        &self.epoch
+    }
+
+    /// Waits for the next snapshot, `None` once the host closed the connection
+    pub fn receive(&mut self) -> io::Result<Option<Snapshot>> {
+        let header = match read_line(&mut self.reader) {
+            Ok(header) => header,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut fields = header.split(' ');
+        let (Some("snapshot"), Some(sim_time), Some(count), None) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+            return Err(invalid(format!("Expected a snapshot instead of '{}'", header)));
+        };
+        let sim_time = parse(sim_time)?;
+        let count: usize = parse(count)?;
+        let mut orbits = Vec::with_capacity(count);
+        let mut rotations = Vec::with_capacity(count);
+        for _ in 0..count {
+            let line = read_line(&mut self.reader)?;
+            let fields: Vec<&str> = line.split(' ').collect();
+            let [semi_major_axis, eccentricity, longitude_of_periapsis, mean_anomaly, period, prime_meridian] = fields[..] else {
+                return Err(invalid(format!("Malformed orbit '{}'", line)));
+            };
+            orbits.push(OrbitalParameters {
+                semi_major_axis: parse(semi_major_axis)?,
+                eccentricity: parse(eccentricity)?,
+                longitude_of_periapsis: parse(longitude_of_periapsis)?,
+                mean_anomaly: parse(mean_anomaly)?,
+            });
+            rotations.push(match (period, prime_meridian) {
+                ("-", "-") => None,
+                (period, prime_meridian) => Some(Rotation { period: parse(period)?, prime_meridian: parse(prime_meridian)? }),
+            });
+        }
+        Snapshot::new(sim_time, orbits, rotations).map(Some).map_err(invalid)
+    }
+}
+
+fn encode_snapshot(snapshot: &Snapshot) -> String {
+    let mut message = format!("snapshot {} {}\n", snapshot.sim_time, snapshot.orbits().len());
+    for (orbit, rotation) in snapshot.orbits().iter().zip(snapshot.rotations()) {
+        message += &format!("{} {} {} {} ", orbit.semi_major_axis, orbit.eccentricity, orbit.longitude_of_periapsis, orbit.mean_anomaly);
+        message += &match rotation {
+            Some(rotation) => format!("{} {}\n", rotation.period, rotation.prime_meridian),
+            None => "- -\n".to_string(),
+        };
+    }
+    message
+}
+
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(line.trim_end().to_string())
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> io::Result<T> {
+    value.parse().map_err(|_| invalid(format!("Invalid number '{}'", value)))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+    use magnetar_data::yaml_parser::load_yaml_str;
+    use super::*;
+
+    #[test]
+    fn test_host_drops_spectators_that_do_not_read() {
+        let mut store = SystemStore::new(load_yaml_str(include_str!("../../magnetar_data/test_data/valid.yaml")).unwrap());
+        let mut host = SpectatorHost::bind("127.0.0.1:0", &store).unwrap();
+        let _stalled = TcpStream::connect(host.local_address().unwrap()).unwrap();
+
+        // Once the socket buffers are full the write times out instead of stalling the host
+        let mut sim_time = 0.0;
+        let mut connected = false;
+        while host.clients() > 0 || !connected {
+            store.step_by(60.0);
+            sim_time += 60.0;
+            host.broadcast(&store.snapshot(sim_time)).unwrap();
+            connected |= host.clients() > 0;
+        }
+        assert_eq!(host.clients(), 0);
+    }
+
+    #[test]
+    fn test_spectator_follows_host() {
+        let mut store = SystemStore::new(load_yaml_str(include_str!("../../magnetar_data/test_data/valid.yaml")).unwrap());
+        let mut host = SpectatorHost::bind("127.0.0.1:0", &store).unwrap();
+        let address = host.local_address().unwrap();
+        let client = thread::spawn(move || {
+            let mut client = SpectatorClient::connect(address).unwrap();
+            let mut replica = client.epoch().clone();
+            let mut received = 0;
+            while let Some(snapshot) = client.receive().unwrap() {
+                replica.restore(&snapshot).unwrap();
+                received += 1;
+            }
+            (replica, received)
+        });
+
+        // Keep broadcasting until the spectator was accepted, then send a few more steps
+        let mut sim_time = 0.0;
+        let mut sent_after_connect = 0;
+        while sent_after_connect < 3 {
+            store.step_by(3600.0);
+            sim_time += 3600.0;
+            host.broadcast(&store.snapshot(sim_time)).unwrap();
+            if host.clients() > 0 {
+                sent_after_connect += 1;
+            } else {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+        drop(host);
+        let (replica, received) = client.join().unwrap();
+        assert_eq!(received, 3);
+        assert_eq!(replica.root(), store.root());
+    }
+}
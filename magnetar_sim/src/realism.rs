@@ -0,0 +1,92 @@
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Which effects a session simulates, the simulation loop handles all of them and frontends can check them in
+/// [`Simulation::realism`](crate::simulation::Simulation::realism).
+pub struct Realism {
+    /// Report objects that start touching
+    pub collisions: bool,
+    /// Let orbits inside atmospheres decay
+    pub decay: bool,
+    /// Integrate siblings within each other's Hill sphere as N-body regions instead of keeping every orbit on rails
+    pub perturbations: bool,
+    /// Burns happen once the signal from the control body reaches the craft
+    pub signal_delay: bool,
+    /// Starlight heats atmospheres until their lighter gases escape
+    pub radiation: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "lowercase"))]
+/// Named bundles of [`Realism`] settings to pick from
+pub enum Preset {
+    /// Orbits on rails, nothing that can go wrong
+    #[default]
+    Arcade,
+    /// Collisions, decay and signal delay
+    Realistic,
+    /// Everything that is simulated
+    Hardcore,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Arcade, Preset::Realistic, Preset::Hardcore];
+
+    pub fn realism(self) -> Realism {
+        match self {
+            Preset::Arcade => Realism::default(),
+            Preset::Realistic => Realism { collisions: true, decay: true, perturbations: false, signal_delay: true, radiation: false },
+            Preset::Hardcore => Realism { collisions: true, decay: true, perturbations: true, signal_delay: true, radiation: true },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+/// Settings saved next to a system file, so every session with the system runs with the same effects
+pub struct Scenario {
+    pub preset: Preset,
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Preset::Arcade => "arcade",
+            Preset::Realistic => "realistic",
+            Preset::Hardcore => "hardcore",
+        })
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Preset, String> {
+        Preset::ALL.into_iter()
+            .find(|preset| preset.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown preset '{}', expected arcade, realistic or hardcore", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use magnetar_data::store::SystemStore;
+    use magnetar_data::yaml_parser::load_yaml_str;
+    use crate::simulation::Simulation;
+    use super::*;
+
+    #[test]
+    fn test_presets_configure_simulation() {
+        let preset: Preset = "Hardcore".parse().unwrap();
+        assert!("easy".parse::<Preset>().is_err());
+        let store = SystemStore::new(load_yaml_str(include_str!("../../magnetar_data/test_data/valid.yaml")).unwrap());
+        let mut simulation = Simulation::new(store).with_realism(preset.realism()).with_decay(false);
+        assert_eq!(*simulation.realism(), Realism { decay: false, ..Preset::Hardcore.realism() });
+        simulation.set_realism(Scenario::default().preset.realism());
+        assert_eq!(*simulation.realism(), Realism::default());
+    }
+}
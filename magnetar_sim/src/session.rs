@@ -0,0 +1,92 @@
+use std::collections::BTreeSet;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use crate::command::Command;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// What happened during one session, updated by the frontend as the player plays
+pub struct SessionStats {
+    /// Sim seconds played forward, rewinding does not subtract from it
+    pub sim_time: f64,
+    /// Names of the bodies the player looked at
+    pub visited: BTreeSet<String>,
+    /// Burns and orbit changes
+    pub maneuvers: usize,
+    /// Other changes to the system, like adding or editing bodies
+    pub changes: usize,
+}
+
+impl SessionStats {
+    pub fn new() -> SessionStats {
+        SessionStats::default()
+    }
+
+    /// Counts the sim time that passed, negative values from rewinding are ignored
+    pub fn advance(&mut self, seconds: f64) {
+        self.sim_time += seconds.max(0.0);
+    }
+
+    pub fn visit(&mut self, name: &str) {
+        self.visited.insert(name.to_string());
+    }
+
+    /// Counts an executed command, commands which only change the clock are not counted
+    pub fn record(&mut self, command: &Command) {
+        match command {
+            Command::ApplyBurn { .. } | Command::SetOrbit { .. } => self.maneuvers += 1,
+            command if command.changes_system() => self.changes += 1,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+/// Totals over all sessions of a player, e.g. kept in a profile file
+pub struct LifetimeStats {
+    pub sessions: usize,
+    pub sim_time: f64,
+    pub visited: BTreeSet<String>,
+    pub maneuvers: usize,
+    pub changes: usize,
+}
+
+impl LifetimeStats {
+    /// Adds a finished session to the totals
+    pub fn add(&mut self, session: &SessionStats) {
+        self.sessions += 1;
+        self.sim_time += session.sim_time;
+        self.visited.extend(session.visited.iter().cloned());
+        self.maneuvers += session.maneuvers;
+        self.changes += session.changes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use magnetar_data::vector::DVec3;
+    use magnetar_data::yaml_parser::load_yaml_str;
+    use super::*;
+
+    #[test]
+    fn test_session_adds_to_lifetime() {
+        let mut session = SessionStats::new();
+        session.advance(3600.0);
+        session.advance(-1800.0);
+        session.visit("Earth");
+        session.visit("Earth");
+        session.record(&Command::ApplyBurn { body: "Luna".to_string(), delta_v: DVec3::ZERO });
+        let system = load_yaml_str(include_str!("../../magnetar_data/test_data/valid.yaml")).unwrap();
+        session.record(&Command::SetBody { body: system.find("Luna").unwrap().clone() });
+        session.record(&Command::SetTimeScale(1000.0));
+        assert_eq!((session.sim_time, session.visited.len(), session.maneuvers, session.changes), (3600.0, 1, 1, 1));
+
+        let mut lifetime = LifetimeStats::default();
+        lifetime.add(&session);
+        session.visit("Luna");
+        lifetime.add(&session);
+        assert_eq!(lifetime.visited.iter().map(String::as_str).collect::<Vec<_>>(), ["Earth", "Luna"]);
+        assert_eq!((lifetime.sessions, lifetime.sim_time, lifetime.maneuvers), (2, 7200.0, 2));
+    }
+}
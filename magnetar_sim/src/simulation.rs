@@ -0,0 +1,667 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+use magnetar_data::celestial::Object;
+use magnetar_data::collision::CollisionDetector;
+use magnetar_data::decay::apply_decay;
+use magnetar_data::encounter::encounters;
+use magnetar_data::radiation::apply_escape;
+use magnetar_data::signal::light_delay_between;
+use magnetar_data::snapshot::{Snapshot, SnapshotBuffer};
+use magnetar_data::store::{ObjectId, SystemStore};
+use crate::clock::Clock;
+use crate::command::{Command, LoggedCommand};
+use crate::event::Event;
+use crate::realism::Realism;
+
+/// Commands that can be undone unless set with [`Simulation::with_undo_limit`]
+pub const DEFAULT_UNDO_LIMIT: usize = 100;
+
+/// The simulation loop shared by every frontend: steps a system from its epoch, checks for collisions and
+/// orbital decay after every step, records events and keeps snapshots to rewind to.
+///
+/// Without decay, perturbations or radiation the state only depends on the sim time, so seeking backwards simply
+/// steps backwards. With them it depends on the path taken, seeking backwards restores the latest snapshot before
+/// the target, or the epoch, and steps forward from there. Snapshots do not keep atmospheres, so with radiation
+/// it always starts over from the epoch.
+///
+/// Changes to the system go through [`Simulation::execute`] and are logged with their sim time. Stepping
+/// forward over a logged command applies it again, so the log doubles as a replay of the session and
+/// rewinding before a command restores the state without it. The latest commands can be undone, which
+/// drops them from the log and derives the current state again without them.
+///
+/// With signal delay burns are sent from a control body, the root unless set with [`Simulation::set_control`],
+/// and scheduled for when the signal reaches the craft instead of happening right away.
+///
+/// Subsystems far from what the player looks at can be frozen: the planet keeps moving, but its moons are left
+/// where they are relative to it and caught up analytically once they are thawed again. Snapshots, commands
+/// and rewinding catch them up first, so only [`Simulation::store`] shows them lagging behind.
+pub struct Simulation {
+    epoch: SystemStore,
+    store: SystemStore,
+    /// Offset from the epoch in seconds
+    sim_time: f64,
+    clock: Clock,
+    /// Longest single step in seconds, longer steps are split so collisions and decay are checked in between
+    max_step: f64,
+    realism: Realism,
+    /// Name of the body commands are sent from when signals are delayed, the root if `None`
+    control: Option<String>,
+    /// Objects whose orbit already decayed into their parent, reported only once
+    deorbited: BTreeSet<ObjectId>,
+    collisions: Option<CollisionDetector>,
+    snapshots: SnapshotBuffer,
+    /// Sim time between two snapshots in seconds, `None` to take none
+    snapshot_interval: Option<f64>,
+    events: Vec<Event>,
+    /// Commands that changed the system, ordered by sim time
+    log: Vec<LoggedCommand>,
+    /// Number of logged commands the current state includes
+    applied: usize,
+    /// Number of the latest logged commands that can be undone
+    undoable: usize,
+    undo_limit: usize,
+    /// Undone commands, the most recently undone last
+    redo: Vec<LoggedCommand>,
+    /// Objects whose subsystems are frozen, with the sim time their subsystem is at
+    frozen: BTreeMap<ObjectId, f64>,
+}
+
+impl Simulation {
+    /// A simulation at the epoch with a paused clock, without collision checks, decay or snapshots
+    pub fn new(epoch: SystemStore) -> Simulation {
+        Simulation {
+            store: epoch.clone(),
+            epoch,
+            sim_time: 0.0,
+            clock: Clock::default(),
+            max_step: f64::INFINITY,
+            realism: Realism::default(),
+            control: None,
+            deorbited: BTreeSet::new(),
+            collisions: None,
+            snapshots: SnapshotBuffer::new(0),
+            snapshot_interval: None,
+            events: Vec::new(),
+            log: Vec::new(),
+            applied: 0,
+            undoable: 0,
+            undo_limit: DEFAULT_UNDO_LIMIT,
+            redo: Vec::new(),
+            frozen: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Simulation {
+        self.clock = clock;
+        self
+    }
+
+    /// Split steps longer than `seconds`, checks only run between steps so they can miss short events otherwise
+    pub fn with_max_step(mut self, seconds: f64) -> Simulation {
+        self.max_step = seconds.abs();
+        self
+    }
+
+    /// Report objects that start touching as [`Event::Collision`]
+    pub fn with_collisions(mut self, enabled: bool) -> Simulation {
+        self.realism.collisions = enabled;
+        self.collisions = enabled.then(CollisionDetector::new);
+        self
+    }
+
+    /// Let orbits inside atmospheres decay while stepping forward, see [`apply_decay`]
+    pub fn with_decay(mut self, enabled: bool) -> Simulation {
+        self.realism.decay = enabled;
+        self
+    }
+
+    /// Simulate the effects enabled in `realism`, e.g. from a [`Preset`](crate::realism::Preset)
+    pub fn with_realism(mut self, realism: Realism) -> Simulation {
+        self.set_realism(realism);
+        self
+    }
+
+    /// Keep up to `capacity` snapshots, one every `interval` seconds of sim time, starting with the current state
+    pub fn with_snapshots(mut self, interval: f64, capacity: usize) -> Simulation {
+        self.snapshots = SnapshotBuffer::new(capacity);
+        self.snapshot_interval = Some(interval.abs());
+        self.take_snapshot();
+        self
+    }
+
+    /// Keep at most `limit` commands to undo
+    pub fn with_undo_limit(mut self, limit: usize) -> Simulation {
+        self.undo_limit = limit;
+        self.undoable = self.undoable.min(limit);
+        self
+    }
+
+    /// Commands to apply when stepping over their sim time, e.g. from a replay file
+    pub fn with_log(mut self, mut log: Vec<LoggedCommand>) -> Simulation {
+        log.retain(|entry| entry.command.changes_system());
+        log.sort_by(|a, b| a.sim_time.total_cmp(&b.sim_time));
+        self.log = log;
+        self.reset();
+        self
+    }
+
+    /// The effects this simulation runs with, systems outside of the loop check theirs here
+    pub fn realism(&self) -> &Realism {
+        &self.realism
+    }
+
+    /// Switch to the effects enabled in `realism`, e.g. for a preset saved with the system
+    pub fn set_realism(&mut self, realism: Realism) {
+        self.collisions = realism.collisions.then(CollisionDetector::new);
+        self.realism = realism;
+    }
+
+    /// Send commands from the body named `control`, or the root if `None`, see [`Simulation`] for signal delay
+    pub fn set_control(&mut self, control: Option<String>) {
+        self.control = control;
+    }
+
+    /// The unstepped system everything is derived from
+    pub fn epoch(&self) -> &SystemStore {
+        &self.epoch
+    }
+
+    /// The system at the current sim time
+    pub fn store(&self) -> &SystemStore {
+        &self.store
+    }
+
+    /// Offset from the epoch in seconds
+    pub fn sim_time(&self) -> f64 {
+        self.sim_time
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    pub fn clock_mut(&mut self) -> &mut Clock {
+        &mut self.clock
+    }
+
+    pub fn snapshots(&self) -> &SnapshotBuffer {
+        &self.snapshots
+    }
+
+    /// Every command that changed the system, including ones after the current sim time that were rewound
+    pub fn log(&self) -> &[LoggedCommand] {
+        &self.log
+    }
+
+    /// Replace the epoch, e.g. after reloading the system file, and derive the state at the current sim time
+    /// from it. Snapshots and decay progress of the old epoch are dropped.
+    pub fn set_epoch(&mut self, epoch: SystemStore) {
+        self.epoch = epoch;
+        self.rederive();
+    }
+
+    /// Advance by the sim time that passes on the clock in `real` time, returns the sim seconds stepped
+    pub fn tick(&mut self, real: Duration) -> f64 {
+        let seconds = self.clock.advance(real);
+        if seconds != 0.0 {
+            self.step_by(seconds);
+        }
+        seconds
+    }
+
+    /// Step by a signed amount of seconds, split into steps of at most the maximum step
+    pub fn step_by(&mut self, seconds: f64) {
+        self.seek(self.sim_time + seconds);
+    }
+
+    /// Go to a sim time, see [`Simulation`] for how earlier times are reached
+    pub fn seek(&mut self, sim_time: f64) {
+        let before_command = self.applied > 0 && sim_time < self.log[self.applied - 1].sim_time;
+        let realism = self.realism;
+        if sim_time < self.sim_time && (realism.decay || realism.perturbations || realism.radiation || before_command) {
+            let from = self.sim_time;
+            // Snapshots are only kept since the last command, so they never skip one
+            match self.snapshots.rewind_to(sim_time).filter(|_| !realism.radiation).cloned() {
+                Some(snapshot) if self.store.restore(&snapshot).is_ok() => self.set_restored_time(snapshot.sim_time),
+                _ => self.reset(),
+            }
+            // Objects can only have decayed into their parent on the way forward
+            self.deorbited.clear();
+            self.events.push(Event::Rewound { from, to: sim_time });
+        }
+        while let Some(entry) = self.log.get(self.applied).filter(|entry| entry.sim_time <= sim_time).cloned() {
+            self.advance_to(entry.sim_time);
+            if let Err(e) = self.apply(&entry.command) {
+                tracing::warn!(sim_time = entry.sim_time, command = ?entry.command, error = %e, "skipping logged command");
+            }
+            self.applied += 1;
+        }
+        self.advance_to(sim_time);
+    }
+
+    /// Execute a command at the current sim time. Commands changing the system are logged, replacing any
+    /// logged after the current sim time, since those were given in a timeline that no longer happens.
+    /// With signal delay burns are scheduled for when the signal arrives instead.
+    pub fn execute(&mut self, command: Command) -> Result<(), String> {
+        if let Some(delay) = self.signal_delay(&command) {
+            return self.schedule(self.sim_time + delay, command);
+        }
+        self.apply(&command)?;
+        if command.changes_system() {
+            self.log.truncate(self.applied);
+            self.log.push(LoggedCommand { sim_time: self.sim_time, command });
+            self.applied += 1;
+            self.undoable = (self.undoable + 1).min(self.undo_limit);
+            self.redo.clear();
+        }
+        Ok(())
+    }
+
+    /// Log a command to execute once the simulation reaches `sim_time`, e.g. a burn of a planned transfer.
+    /// Executing a command before then drops it like any other command logged after the current sim time.
+    pub fn schedule(&mut self, sim_time: f64, command: Command) -> Result<(), String> {
+        if sim_time < self.sim_time {
+            return Err(format!("Cannot schedule a command in the past at {} s", sim_time));
+        }
+        if !command.changes_system() {
+            return Err("Only commands changing the system can be scheduled".to_string());
+        }
+        let index = self.applied + self.log[self.applied..].partition_point(|entry| entry.sim_time <= sim_time);
+        self.log.insert(index, LoggedCommand { sim_time, command });
+        self.redo.clear();
+        // Commands scheduled for right now are applied immediately
+        self.seek(self.sim_time);
+        Ok(())
+    }
+
+    /// Seconds a command takes from the control body to the body it is for, `None` if it applies right away
+    fn signal_delay(&self, command: &Command) -> Option<f64> {
+        let Command::ApplyBurn { body, .. } = command else {
+            return None;
+        };
+        if !self.realism.signal_delay {
+            return None;
+        }
+        let control = match &self.control {
+            Some(name) => self.store.find(name)?,
+            None => self.store.root_id(),
+        };
+        light_delay_between(&self.store, control, self.store.find(body)?).filter(|delay| *delay > 0.0)
+    }
+
+    /// Undo the latest command the current state includes, returns it or `None` if there is nothing to undo.
+    /// Commands logged after the current sim time are dropped like when executing a new one.
+    pub fn undo(&mut self) -> Option<Command> {
+        if self.undoable == 0 || self.applied == 0 {
+            return None;
+        }
+        self.log.truncate(self.applied);
+        let entry = self.log.pop()?;
+        self.undoable -= 1;
+        self.rederive();
+        let command = entry.command.clone();
+        self.redo.push(entry);
+        Some(command)
+    }
+
+    /// Execute the most recently undone command again at its original sim time, returns it or `None` if
+    /// there is nothing to redo
+    pub fn redo(&mut self) -> Option<Command> {
+        let entry = self.redo.pop()?;
+        let command = entry.command.clone();
+        self.log.truncate(self.applied);
+        self.log.push(entry);
+        self.undoable = (self.undoable + 1).min(self.undo_limit);
+        self.rederive();
+        Some(command)
+    }
+
+    /// Number of commands that can be undone and redone, e.g. to show the position in the history
+    pub fn history(&self) -> (usize, usize) {
+        (self.undoable, self.redo.len())
+    }
+
+    /// Jump to the state of a snapshot of this system, e.g. one streamed from a host. Events and snapshots
+    /// are left untouched.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), String> {
+        self.store.restore(snapshot)?;
+        self.set_restored_time(snapshot.sim_time);
+        Ok(())
+    }
+
+    /// Stop stepping everything orbiting `id` until it is thawed, does nothing inside an already frozen subsystem
+    pub fn freeze(&mut self, id: ObjectId) {
+        if self.frozen.contains_key(&id) || self.ancestors(id).any(|ancestor| self.frozen.contains_key(&ancestor)) {
+            return;
+        }
+        let inside: Vec<ObjectId> = self.frozen.keys().copied().filter(|&frozen| self.ancestors(frozen).any(|ancestor| ancestor == id)).collect();
+        for frozen in inside {
+            self.thaw(frozen);
+        }
+        self.frozen.insert(id, self.sim_time);
+    }
+
+    /// Catch up the subsystem of `id` to the current sim time and step it along again
+    pub fn thaw(&mut self, id: ObjectId) {
+        if let Some(since) = self.frozen.remove(&id) {
+            self.store.step_children_by(id, self.sim_time - since);
+        }
+    }
+
+    /// Objects whose subsystems are currently frozen
+    pub fn frozen(&self) -> impl Iterator<Item = ObjectId> + '_ {
+        self.frozen.keys().copied()
+    }
+
+    /// Freeze the subsystems of objects farther than `distance` km from `focus` and thaw all others, e.g. whenever
+    /// the camera or the selection moves. Subsystems containing the focus are never frozen.
+    pub fn update_lod(&mut self, focus: ObjectId, distance: f64) {
+        let Some(center) = self.store.absolute_position(focus) else {
+            return;
+        };
+        for id in self.store.ids() {
+            if self.store.children(id).is_empty() || self.store.parent(id).is_none() {
+                continue;
+            }
+            let contains_focus = id == focus || self.ancestors(focus).any(|ancestor| ancestor == id);
+            let far = self.store.absolute_position(id).is_some_and(|position| position.distance(center) > distance);
+            if far && !contains_focus {
+                self.freeze(id);
+            } else {
+                self.thaw(id);
+            }
+        }
+    }
+
+    /// Take all events since the last call, oldest first
+    pub fn drain_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Derive the state at the current sim time from the epoch again, after the log changed
+    fn rederive(&mut self) {
+        let sim_time = self.sim_time;
+        self.reset();
+        self.seek(sim_time);
+    }
+
+    fn ancestors(&self, id: ObjectId) -> impl Iterator<Item = ObjectId> + '_ {
+        std::iter::successors(self.store.parent(id), |&parent| self.store.parent(parent))
+    }
+
+    /// After restoring a complete state, frozen subsystems start lagging from there
+    fn set_restored_time(&mut self, sim_time: f64) {
+        self.sim_time = sim_time;
+        for since in self.frozen.values_mut() {
+            *since = sim_time;
+        }
+    }
+
+    /// Bring frozen subsystems to the current sim time, keeping them frozen
+    fn catch_up(&mut self) {
+        for (&id, since) in self.frozen.iter_mut() {
+            self.store.step_children_by(id, self.sim_time - *since);
+            *since = self.sim_time;
+        }
+    }
+
+    /// Take a snapshot of the complete state at the current sim time
+    fn take_snapshot(&mut self) {
+        self.catch_up();
+        self.snapshots.push(self.store.snapshot(self.sim_time));
+    }
+
+    fn reset(&mut self) {
+        self.store = self.epoch.clone();
+        self.sim_time = 0.0;
+        self.applied = 0;
+        self.frozen.clear();
+        self.deorbited.clear();
+        self.snapshots.clear();
+        if self.snapshot_interval.is_some() {
+            self.take_snapshot();
+        }
+    }
+
+    fn apply(&mut self, command: &Command) -> Result<(), String> {
+        self.catch_up();
+        let name = |id: &ObjectId| self.store.get(*id).map(|object| object.name.clone());
+        let deorbited: Vec<String> = self.deorbited.iter().filter_map(name).collect();
+        let frozen: Vec<String> = self.frozen.keys().filter_map(name).collect();
+        let renumbered = command.apply(&mut self.store, &mut self.clock)?;
+        if renumbered {
+            self.deorbited = deorbited.iter().filter_map(|name| self.store.find(name)).collect();
+            self.frozen = frozen.iter().filter_map(|name| self.store.find(name)).map(|id| (id, self.sim_time)).collect();
+            // Contacts that already existed are not new collisions
+            if let Some(detector) = self.collisions.as_mut() {
+                *detector = CollisionDetector::new();
+                detector.check(&self.store);
+            }
+        } else if let Command::ApplyBurn { body, .. } | Command::SetOrbit { body, .. } | Command::SetBody { body: Object { name: body, .. } } = command {
+            // A new orbit can decay again
+            self.store.find(body).map(|id| self.deorbited.remove(&id));
+        }
+        if command.changes_system() {
+            // Snapshots from before the change could not be restored into the system after it
+            self.snapshots.clear();
+            if self.snapshot_interval.is_some() {
+                self.take_snapshot();
+            }
+        }
+        Ok(())
+    }
+
+    /// Step to a later or earlier sim time without applying commands, split into steps of at most the maximum step
+    fn advance_to(&mut self, sim_time: f64) {
+        let seconds = sim_time - self.sim_time;
+        let steps = (seconds.abs() / self.max_step).ceil().max(1.0);
+        for _ in 0..steps as u64 {
+            self.single_step(seconds / steps);
+        }
+        // Avoid summing up rounding errors of the split steps
+        self.sim_time = sim_time;
+    }
+
+    fn single_step(&mut self, seconds: f64) {
+        let start = self.sim_time;
+        let frozen = &self.frozen;
+        let is_frozen = |id: ObjectId| std::iter::successors(Some(id), |id| self.store.parent(*id)).any(|id| frozen.contains_key(&id));
+        // Encounters start from the state before the step, they are stepped like everything else but replace the
+        // orbits of their members afterwards
+        let mut encounters = if self.realism.perturbations && seconds > 0.0 {
+            encounters(&self.store).into_iter().filter(|encounter| !is_frozen(encounter.parent)).collect()
+        } else {
+            Vec::new()
+        };
+        self.store.step_by_except(seconds, |id| frozen.contains_key(&id));
+        for encounter in &mut encounters {
+            encounter.step_by(seconds);
+            encounter.apply(&mut self.store);
+        }
+        self.sim_time += seconds;
+        if self.realism.decay && seconds > 0.0 {
+            for deorbit in apply_decay(&mut self.store, seconds) {
+                if self.deorbited.insert(deorbit.id) {
+                    self.events.push(Event::Deorbit { sim_time: start + deorbit.time, deorbit });
+                }
+            }
+        }
+        if self.realism.radiation && seconds > 0.0 {
+            let events = apply_escape(&mut self.store, seconds).into_iter()
+                .map(|escape| Event::Escape { sim_time: start + escape.time, escape });
+            self.events.extend(events);
+        }
+        if let Some(detector) = self.collisions.as_mut() {
+            let sim_time = self.sim_time;
+            self.events.extend(detector.check(&self.store).into_iter().map(|collision| Event::Collision { sim_time, collision }));
+        }
+        if let Some(interval) = self.snapshot_interval {
+            let due = self.snapshots.latest().is_none_or(|latest| (self.sim_time - latest.sim_time).abs() >= interval);
+            if due {
+                self.take_snapshot();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use magnetar_data::orbital::OrbitalParameters;
+    use magnetar_data::vector::DVec3;
+    use magnetar_data::yaml_parser::load_yaml_str;
+    use super::*;
+
+    fn system() -> SystemStore {
+        let mut system = load_yaml_str(include_str!("../../magnetar_data/test_data/valid.yaml")).unwrap();
+        let mut station = system.children[0].children[0].clone();
+        (station.name, station.mass, station.radius) = ("Station".to_string(), 400_000.0, 0.05);
+        station.orbital_params = OrbitalParameters { semi_major_axis: 6371.0 + 115.0, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly: 0.0 };
+        system.children[0].children.push(station);
+        SystemStore::new(system)
+    }
+
+    #[test]
+    fn test_tick_follows_clock() {
+        let mut simulation = Simulation::new(system()).with_clock(Clock::new(86400.0));
+        assert_eq!(simulation.tick(Duration::from_millis(500)), 43200.0);
+        simulation.clock_mut().toggle_pause();
+        assert_eq!(simulation.tick(Duration::from_secs(1)), 0.0);
+        assert_eq!(simulation.sim_time(), 43200.0);
+        let mut expected = simulation.epoch().clone();
+        expected.step_by(43200.0);
+        assert!(simulation.store().root().approx_eq(expected.root(), 1e-9));
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut simulation = Simulation::new(system()).with_undo_limit(2);
+        simulation.seek(3600.0);
+        let original = simulation.store().root().clone();
+        let orbit = OrbitalParameters { semi_major_axis: 7000.0, eccentricity: 0.1, longitude_of_periapsis: 90, mean_anomaly: 0.0 };
+        simulation.execute(Command::SetOrbit { body: "Station".to_string(), orbit: orbit.clone() }).unwrap();
+        simulation.execute(Command::Reparent { body: "Luna".to_string(), parent: "Sol".to_string() }).unwrap();
+        simulation.execute(Command::RemoveBody { name: "Station".to_string() }).unwrap();
+        assert!(simulation.execute(Command::Reparent { body: "Earth".to_string(), parent: "Earth".to_string() }).is_err());
+        assert_eq!(simulation.history(), (2, 0));
+
+        assert_eq!(simulation.undo(), Some(Command::RemoveBody { name: "Station".to_string() }));
+        assert!(simulation.undo().is_some());
+        // The oldest command fell out of the bounded history
+        assert_eq!(simulation.undo(), None);
+        assert_eq!(simulation.history(), (0, 2));
+        let station = simulation.store().find("Station").unwrap();
+        assert_eq!(simulation.store().get(station).unwrap().orbital_params, orbit);
+        assert_eq!(simulation.store().parent(simulation.store().find("Luna").unwrap()), simulation.store().find("Earth"));
+
+        assert!(simulation.redo().is_some());
+        assert_eq!(simulation.store().parent(simulation.store().find("Luna").unwrap()), Some(simulation.store().root_id()));
+        assert_eq!(simulation.history(), (1, 1));
+        // A new command forgets what could have been redone
+        simulation.execute(Command::SetOrbit { body: "Station".to_string(), orbit: original.find("Station").unwrap().orbital_params.clone() }).unwrap();
+        assert_eq!(simulation.redo(), None);
+    }
+
+    #[test]
+    fn test_signal_delay_schedules_burns() {
+        let burn = Command::ApplyBurn { body: "Station".to_string(), delta_v: DVec3::new(0.0, 0.1, 0.0) };
+        let mut simulation = Simulation::new(system()).with_realism(Realism { signal_delay: true, ..Realism::default() });
+        simulation.execute(burn.clone()).unwrap();
+        // Sent from Sol the burn happens once the signal covered the 8 light minutes to Earth
+        let [entry] = simulation.log() else { panic!("{:?}", simulation.log()) };
+        assert!((490.0..510.0).contains(&entry.sim_time), "{}", entry.sim_time);
+        assert_eq!(simulation.store().get(simulation.store().find("Station").unwrap()).unwrap().orbital_params,
+                   simulation.epoch().get(simulation.epoch().find("Station").unwrap()).unwrap().orbital_params);
+
+        // From Earth it takes a fraction of a second
+        simulation.set_control(Some("Earth".to_string()));
+        simulation.execute(burn).unwrap();
+        assert!(simulation.log()[0].sim_time < 0.1, "{:?}", simulation.log());
+        assert!(simulation.execute(Command::ApplyBurn { body: "Nowhere".to_string(), delta_v: DVec3::default() }).is_err());
+    }
+
+    #[test]
+    fn test_perturbations_leave_the_rails() {
+        let mut system = system().root().clone();
+        // Two small moons within each other's Hill sphere
+        for (name, mean_anomaly) in [("A", 0.0), ("B", 0.5)] {
+            let mut moon = system.children[0].children[0].clone();
+            (moon.name, moon.mass, moon.radius, moon.children) = (name.to_string(), 1e20, 30.0, Vec::new());
+            moon.orbital_params = OrbitalParameters { semi_major_axis: 100_000.0, eccentricity: 0.0, longitude_of_periapsis: 0, mean_anomaly };
+            system.children[0].children.push(moon);
+        }
+        let mut simulation = Simulation::new(SystemStore::new(system)).with_max_step(3600.0)
+            .with_realism(Realism { perturbations: true, ..Realism::default() });
+        simulation.seek(86400.0);
+        let mut rails = simulation.epoch().clone();
+        rails.step_by(86400.0);
+        let (a, luna) = (simulation.store().find("A").unwrap(), simulation.store().find("Luna").unwrap());
+        assert_ne!(simulation.store().get(a).unwrap().orbital_params, rails.get(a).unwrap().orbital_params);
+        assert!(simulation.store().get(luna).unwrap().approx_eq(rails.get(luna).unwrap(), 1e-9));
+
+        // The moons took a path of their own, so going back starts over from the epoch
+        simulation.seek(0.0);
+        assert!(matches!(simulation.drain_events().as_slice(), [Event::Rewound { .. }]));
+        assert_eq!(simulation.store().get(a).unwrap().orbital_params, simulation.epoch().get(a).unwrap().orbital_params);
+    }
+
+    #[test]
+    fn test_radiation_escape_events_and_rewind() {
+        let mut system = system().root().clone();
+        let mut cinder = system.children[0].clone();
+        (cinder.name, cinder.mass, cinder.radius, cinder.children) = ("Cinder".to_string(), 6.4e23, 3390.0, Vec::new());
+        cinder.orbital_params.semi_major_axis *= 0.05;
+        cinder.atmosphere = [("H2".to_string(), 0.1), ("N2".to_string(), 0.5)].into();
+        system.children.push(cinder);
+        let mut simulation = Simulation::new(SystemStore::new(system)).with_max_step(1e9).with_snapshots(1e9, 64)
+            .with_realism(Realism { radiation: true, ..Realism::default() });
+        let cinder = simulation.store().find("Cinder").unwrap();
+        simulation.seek(3e10);
+        let events = simulation.drain_events();
+        assert!(matches!(events.as_slice(), [Event::Escape { sim_time, escape }] if escape.id == cinder && escape.gas == "H2"
+                         && (1e10..2e10).contains(sim_time)), "{:?}", events);
+
+        // Snapshots do not keep the hydrogen, rewinding brings it back from the epoch
+        simulation.seek(5e9);
+        assert!(simulation.store().get(cinder).unwrap().atmosphere.contains_key("H2"));
+        simulation.seek(3e10);
+        assert!(!simulation.store().get(cinder).unwrap().atmosphere.contains_key("H2"));
+    }
+
+    #[test]
+    fn test_frozen_subsystems_catch_up() {
+        let mut simulation = Simulation::new(system());
+        let (sol, earth, luna) = (simulation.store().root_id(), simulation.store().find("Earth").unwrap(), simulation.store().find("Luna").unwrap());
+        simulation.update_lod(sol, 1.0e6);
+        assert_eq!(simulation.frozen().collect::<Vec<_>>(), vec![earth]);
+        let moon = simulation.store().get(luna).unwrap().orbital_params.clone();
+        simulation.seek(86400.0);
+        assert_eq!(simulation.store().get(luna).unwrap().orbital_params, moon);
+        assert_ne!(simulation.store().get(earth).unwrap().orbital_params, simulation.epoch().get(earth).unwrap().orbital_params);
+
+        // Looking at Earth thaws its moons, which end up where they would have been without freezing
+        simulation.update_lod(luna, 1.0e6);
+        assert_eq!(simulation.frozen().count(), 0);
+        let mut expected = simulation.epoch().clone();
+        expected.step_by(86400.0);
+        assert!(simulation.store().root().approx_eq(expected.root(), 1e-9));
+    }
+
+    #[test]
+    fn test_decay_events_and_rewind() {
+        let mut simulation = Simulation::new(system()).with_max_step(60.0).with_decay(true).with_snapshots(3600.0, 48);
+        let station = simulation.store().find("Station").unwrap();
+        simulation.seek(86400.0);
+        let events = simulation.drain_events();
+        assert!(matches!(events.as_slice(), [Event::Deorbit { deorbit, .. }] if deorbit.id == station), "{:?}", events);
+
+        // At this altitude the station reaches the surface within seconds, a second in it has only sunk a bit
+        simulation.seek(1.0);
+        let events = simulation.drain_events();
+        assert!(matches!(events.as_slice(), [Event::Rewound { from, to }] if *from == 86400.0 && *to == 1.0), "{:?}", events);
+        assert_eq!(simulation.sim_time(), 1.0);
+        let radius = simulation.store().get(station).unwrap().orbital_params.semi_major_axis;
+        assert!(radius < 6371.0 + 115.0 && radius > 6371.0, "{}", radius);
+        simulation.seek(60.0);
+        assert!(matches!(simulation.drain_events().as_slice(), [Event::Deorbit { .. }]));
+    }
+}
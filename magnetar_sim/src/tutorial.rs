@@ -0,0 +1,122 @@
+use crate::clock::Clock;
+use crate::command::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Something the player did, reported to [`Tutorial::notify`] by the frontend's input and selection handling
+pub enum Action<'a> {
+    /// A body was highlighted or selected
+    Select(&'a str),
+    /// The view was centered on a body or switched to its reference frame
+    Frame(&'a str),
+    /// The clock was changed, or its current state if the frontend reports it every frame
+    Clock(&'a Clock),
+    /// A command was executed successfully
+    Execute(&'a Command),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// What completes a step of a tutorial
+pub enum Goal {
+    Select(String),
+    Frame(String),
+    /// Let time run at a time scale of at least this many sim seconds per real second
+    Run(f64),
+    /// Burn or otherwise change the orbit of a body, any command editing the body counts
+    ChangeOrbit(String),
+}
+
+impl Goal {
+    pub fn is_reached_by(&self, action: &Action) -> bool {
+        match (self, action) {
+            (Goal::Select(goal), Action::Select(name)) | (Goal::Frame(goal), Action::Frame(name)) => goal == name,
+            (Goal::Run(time_scale), Action::Clock(clock)) => !clock.paused && clock.time_scale >= *time_scale,
+            (Goal::ChangeOrbit(goal), Action::Execute(command)) => match command {
+                Command::ApplyBurn { body, .. } | Command::SetOrbit { body, .. } => body == goal,
+                Command::SetBody { body } => body.name == *goal,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// The body the goal is about, e.g. to fill into a prompt
+    pub fn body(&self) -> Option<&str> {
+        match self {
+            Goal::Select(name) | Goal::Frame(name) | Goal::ChangeOrbit(name) => Some(name),
+            Goal::Run(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A prompt shown to the player until they reach its goal
+pub struct TutorialStep {
+    /// Text or localization key of the prompt, up to the frontend
+    pub prompt: String,
+    pub goal: Goal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A guided sequence of steps, advanced by reporting what the player does with [`Tutorial::notify`]
+pub struct Tutorial {
+    steps: Vec<TutorialStep>,
+    current: usize,
+}
+
+impl Tutorial {
+    pub fn new(steps: Vec<TutorialStep>) -> Tutorial {
+        Tutorial { steps, current: 0 }
+    }
+
+    /// The step to show, `None` once all are done
+    pub fn current(&self) -> Option<&TutorialStep> {
+        self.steps.get(self.current)
+    }
+
+    /// Number of completed steps and of all steps, e.g. for a progress indicator
+    pub fn progress(&self) -> (usize, usize) {
+        (self.current, self.steps.len())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+
+    /// Checks `action` against the goal of the current step, returns whether it completed the step
+    pub fn notify(&mut self, action: Action) -> bool {
+        let reached = self.current().is_some_and(|step| step.goal.is_reached_by(&action));
+        if reached {
+            self.current += 1;
+        }
+        reached
+    }
+
+    /// Moves on to the next step without reaching the goal of the current one
+    pub fn skip(&mut self) {
+        self.current = (self.current + 1).min(self.steps.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use magnetar_data::vector::DVec3;
+    use super::*;
+
+    #[test]
+    fn test_tutorial_advances_on_goals() {
+        let step = |prompt: &str, goal| TutorialStep { prompt: prompt.to_string(), goal };
+        let mut tutorial = Tutorial::new(vec![
+            step("select", Goal::Select("Earth".to_string())),
+            step("run", Goal::Run(1000.0)),
+            step("burn", Goal::ChangeOrbit("Earth".to_string())),
+        ]);
+        assert!(!tutorial.notify(Action::Select("Luna")));
+        assert!(!tutorial.notify(Action::Frame("Earth")));
+        assert!(tutorial.notify(Action::Select("Earth")));
+        assert!(!tutorial.notify(Action::Clock(&Clock { time_scale: 5000.0, paused: true })));
+        assert!(tutorial.notify(Action::Clock(&Clock::new(5000.0))));
+        assert_eq!((tutorial.current().unwrap().prompt.as_str(), tutorial.progress()), ("burn", (2, 3)));
+        assert!(tutorial.notify(Action::Execute(&Command::ApplyBurn { body: "Earth".to_string(), delta_v: DVec3::ZERO })));
+        assert!(tutorial.is_finished() && tutorial.current().is_none());
+    }
+}